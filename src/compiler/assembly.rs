@@ -0,0 +1,312 @@
+//! Papyrus assembly, the textual form of [Pex] used by the official `PapyrusAssembler`.
+//!
+//! ```text
+//! .object Test Form
+//!     .userFlags 0
+//!     .docString ""
+//!     .autoState
+//! ```
+
+use super::game::Game;
+use super::pex::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+mod assemble;
+
+pub use assemble::{assemble, Error};
+
+/// Builds a listing line by line, indenting the contents of each block.
+#[derive(Debug, Default)]
+struct Listing {
+	out: String,
+	indent: usize,
+}
+
+impl Listing {
+	fn line(&mut self, line: impl AsRef<str>) {
+		for _ in 0..self.indent {
+			self.out.push('\t');
+		}
+		self.out.push_str(line.as_ref());
+		self.out.push('\n');
+	}
+
+	/// Writes `open`, what `body` writes one level deeper, then the matching `.end` directive.
+	fn block(&mut self, open: impl AsRef<str>, end: &str, body: impl FnOnce(&mut Self)) {
+		self.line(open);
+		self.indent += 1;
+		body(self);
+		self.indent -= 1;
+		self.line(end);
+	}
+
+	fn info(&mut self, header: &Header, debug_info: Option<&DebugInfo>) {
+		self.block(".info", ".endInfo", |l| {
+			l.line(format!(".source {:?}", header.source));
+			// Only files with debug info know when their source was last changed.
+			if let Some(debug_info) = debug_info {
+				l.line(format!(".modifyTime {}", debug_info.modification_time));
+			}
+			l.line(format!(".compileTime {}", header.compilation_time));
+			l.line(format!(".user {:?}", header.user));
+			l.line(format!(".computer {:?}", header.machine));
+		});
+	}
+
+	fn object(&mut self, pex: &Pex, object: &Object) {
+		let game = pex.header.game;
+		let mut open = format!(".object {}", object.name);
+		if !object.parent.is_empty() {
+			write!(open, " {}", object.parent).unwrap();
+		}
+		if object.is_const {
+			open.push_str(" const");
+		}
+
+		self.block(open, ".endObject", |l| {
+			l.line(format!(".userFlags {}", object.user_flags));
+			l.line(format!(".docString {:?}", object.doc));
+			l.line(format!(".autoState {}", object.auto_state).trim_end());
+
+			if game == Game::Fallout4 {
+				l.block(".structTable", ".endStructTable", |l| {
+					for r#struct in &object.structs {
+						l.block(format!(".struct {}", r#struct.name), ".endStruct", |l| {
+							for member in &r#struct.members {
+								l.member(member);
+							}
+						});
+					}
+				});
+			}
+
+			l.block(".variableTable", ".endVariableTable", |l| {
+				for variable in &object.variables {
+					l.variable(variable);
+				}
+			});
+
+			// Groups are part of the debug info.
+			if game == Game::Fallout4 {
+				let groups = pex
+					.debug_info
+					.iter()
+					.flat_map(|debug_info| &debug_info.property_groups)
+					.filter(|group| group.object.eq_ignore_ascii_case(&object.name));
+				l.block(".propertyGroupTable", ".endPropertyGroupTable", |l| {
+					for group in groups {
+						let open = format!(".propertyGroup {}", group.name);
+						l.block(open.trim_end(), ".endPropertyGroup", |l| {
+							l.line(format!(".userFlags {}", group.user_flags));
+							l.line(format!(".docString {:?}", group.doc));
+							for property in &group.properties {
+								l.line(format!(".property {property}"));
+							}
+						});
+					}
+				});
+			}
+
+			l.block(".propertyTable", ".endPropertyTable", |l| {
+				for property in &object.properties {
+					l.property(pex, object, property);
+				}
+			});
+
+			l.block(".stateTable", ".endStateTable", |l| {
+				for state in &object.states {
+					let open = format!(".state {}", state.name);
+					l.block(open.trim_end(), ".endState", |l| {
+						for function in &state.functions {
+							let lines = debug_lines(
+								pex,
+								object,
+								&state.name,
+								&function.name,
+								FunctionKind::Normal,
+							);
+							l.function(&function.name, function, lines);
+						}
+					});
+				}
+			});
+		});
+	}
+
+	fn variable(&mut self, variable: &Variable) {
+		let mut open = format!(".variable {} {}", variable.name, variable.ty);
+		if variable.is_const {
+			open.push_str(" const");
+		}
+
+		self.block(open, ".endVariable", |l| {
+			l.line(format!(".userFlags {}", variable.user_flags));
+			l.line(format!(".initialValue {}", variable.value));
+		});
+	}
+
+	fn member(&mut self, member: &StructMember) {
+		let mut open = format!(".variable {} {}", member.name, member.ty);
+		if member.is_const {
+			open.push_str(" const");
+		}
+
+		self.block(open, ".endVariable", |l| {
+			l.line(format!(".userFlags {}", member.user_flags));
+			l.line(format!(".initialValue {}", member.value));
+			l.line(format!(".docString {:?}", member.doc));
+		});
+	}
+
+	fn property(&mut self, pex: &Pex, object: &Object, property: &Property) {
+		let mut open = format!(".property {} {}", property.name, property.ty);
+		if let PropertyKind::Auto(_) = property.kind {
+			open.push_str(" auto");
+		}
+
+		self.block(open, ".endProperty", |l| {
+			l.line(format!(".userFlags {}", property.user_flags));
+			l.line(format!(".docString {:?}", property.doc));
+			match &property.kind {
+				PropertyKind::Auto(variable) => l.line(format!(".autoVar {variable}")),
+				PropertyKind::Full { get, set } => {
+					let accessors = [
+						("get", get, FunctionKind::Getter),
+						("set", set, FunctionKind::Setter),
+					];
+					for (name, function, kind) in accessors {
+						if let Some(function) = function {
+							let lines = debug_lines(pex, object, "", &property.name, kind);
+							l.function(name, function, lines);
+						}
+					}
+				}
+			}
+		});
+	}
+
+	fn function(&mut self, name: &str, function: &Function, lines: Option<&[u16]>) {
+		let mut open = format!(".function {name}");
+		if function.is_global {
+			open.push_str(" static");
+		}
+		if function.is_native {
+			open.push_str(" native");
+		}
+
+		self.block(open, ".endFunction", |l| {
+			l.line(format!(".userFlags {}", function.user_flags));
+			l.line(format!(".docString {:?}", function.doc));
+			l.line(format!(".return {}", function.return_type));
+			l.block(".paramTable", ".endParamTable", |l| {
+				for (name, ty) in &function.parameters {
+					l.line(format!(".param {name} {ty}"));
+				}
+			});
+			l.block(".localTable", ".endLocalTable", |l| {
+				for (name, ty) in &function.locals {
+					l.line(format!(".local {name} {ty}"));
+				}
+			});
+			l.block(".code", ".endCode", |l| {
+				l.code(&function.instructions, lines)
+			});
+		});
+	}
+
+	/// Writes instructions with labels in place of relative jumps, annotated with source lines if known.
+	fn code(&mut self, instructions: &[Instruction], lines: Option<&[u16]>) {
+		let target = |i: usize, instruction| match jump_offset(instruction) {
+			Some(Value::Int(offset)) => Some(i as i64 + *offset as i64),
+			_ => None,
+		};
+		let labels = instructions
+			.iter()
+			.enumerate()
+			.filter_map(|(i, instruction)| target(i, instruction))
+			.collect::<BTreeSet<_>>()
+			.into_iter()
+			.enumerate()
+			.map(|(n, target)| (target, format!("label{n}")))
+			.collect::<BTreeMap<_, _>>();
+
+		for (i, instruction) in instructions.iter().enumerate() {
+			if let Some(label) = labels.get(&(i as i64)) {
+				self.line(format!("{label}:"));
+			}
+
+			let mut line = instruction.opcode.name().to_owned();
+			let (args, jump) = match target(i, instruction) {
+				Some(target) => (
+					&instruction.args[..instruction.args.len() - 1],
+					Some(target),
+				),
+				None => (&instruction.args[..], None),
+			};
+			for arg in args {
+				write!(line, " {arg}").unwrap();
+			}
+			if let Some(target) = jump {
+				write!(line, " {}", labels[&target]).unwrap();
+			}
+			if let Some(number) = lines.and_then(|lines| lines.get(i)) {
+				write!(line, " ;@line {number}").unwrap();
+			}
+			self.line(line);
+		}
+
+		// Jumps past the last instruction leave the function.
+		if let Some(label) = labels.get(&(instructions.len() as i64)) {
+			self.line(format!("{label}:"));
+		}
+	}
+}
+
+/// Relative target of a jump, which is always its last operand.
+fn jump_offset(instruction: &Instruction) -> Option<&Value> {
+	match instruction.opcode {
+		Opcode::Jmp | Opcode::JmpT | Opcode::JmpF => instruction.args.last(),
+		_ => None,
+	}
+}
+
+/// Source lines of a function, if the debug info has them.
+fn debug_lines<'a>(
+	pex: &'a Pex,
+	object: &Object,
+	state: &str,
+	function: &str,
+	kind: FunctionKind,
+) -> Option<&'a [u16]> {
+	let functions = &pex.debug_info.as_ref()?.functions;
+	functions
+		.iter()
+		.find(|f| {
+			f.kind == kind
+				&& f.object.eq_ignore_ascii_case(&object.name)
+				&& f.state.eq_ignore_ascii_case(state)
+				&& f.function.eq_ignore_ascii_case(function)
+		})
+		.map(|f| f.lines.as_slice())
+}
+
+/// Renders a script as Papyrus assembly, with the source line of each instruction when there is debug info.
+pub fn emit(pex: &Pex) -> String {
+	let mut listing = Listing::default();
+	listing.info(&pex.header, pex.debug_info.as_ref());
+
+	listing.block(".userFlagsRef", ".endUserFlagsRef", |l| {
+		for flag in &pex.user_flags {
+			l.line(format!(".flag {} {}", flag.name, flag.bit));
+		}
+	});
+
+	listing.block(".objectTable", ".endObjectTable", |l| {
+		for object in &pex.objects {
+			l.object(pex, object);
+		}
+	});
+
+	listing.out
+}
@@ -0,0 +1,725 @@
+//! Reads assembly listings back into a [Pex], resolving labels to relative jumps.
+//! Instructions annotated with `;@line` give their function debug info, if the listing has a `.modifyTime`.
+
+use crate::compiler::game::Game;
+use crate::compiler::pex::*;
+use std::collections::HashMap;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+	#[error("Expected {expected}, but got {got} at line {line}")]
+	Expected {
+		expected: &'static str,
+		got: String,
+		line: usize,
+	},
+
+	#[error("Expected {0}, but got end of input")]
+	UnexpectedEnd(&'static str),
+
+	#[error("Unterminated string at line {0}")]
+	UnterminatedString(usize),
+
+	#[error("Unknown opcode {name} at line {line}")]
+	UnknownOpcode { name: String, line: usize },
+
+	#[error("{opcode} is not supported by {game} at line {line}")]
+	Unsupported {
+		opcode: &'static str,
+		game: Game,
+		line: usize,
+	},
+
+	#[error("{opcode} takes {expected} operands, but got {got} at line {line}")]
+	Operands {
+		opcode: &'static str,
+		expected: String,
+		got: usize,
+		line: usize,
+	},
+
+	#[error("Label {name} is not defined at line {line}")]
+	UndefinedLabel { name: String, line: usize },
+
+	#[error("Label {name} is defined twice at line {line}")]
+	DuplicateLabel { name: String, line: usize },
+
+	#[error("Instruction has no source line, unlike others of its function at line {0}")]
+	MissingLine(usize),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Word(String),
+	/// Quoted, with its escapes resolved.
+	String(String),
+}
+
+impl Token {
+	fn text(&self) -> String {
+		match self {
+			Self::Word(word) => word.clone(),
+			Self::String(value) => format!("{value:?}"),
+		}
+	}
+}
+
+/// Tokens of a line that has any, and the source line it is annotated with.
+#[derive(Debug)]
+struct Line {
+	number: usize,
+	tokens: Vec<Token>,
+	source_line: Option<u16>,
+}
+
+impl Line {
+	/// Whether the line is the directive `name`.
+	fn is(&self, name: &str) -> bool {
+		matches!(self.tokens.first(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(name))
+	}
+
+	fn directive(&self) -> String {
+		self.tokens[0].text().to_ascii_lowercase()
+	}
+
+	fn error(&self, index: usize, expected: &'static str) -> Error {
+		Error::Expected {
+			expected,
+			got: self
+				.tokens
+				.get(index)
+				.map_or_else(|| "end of line".to_owned(), Token::text),
+			line: self.number,
+		}
+	}
+
+	fn word(&self, index: usize, expected: &'static str) -> Result<String> {
+		match self.tokens.get(index) {
+			Some(Token::Word(word)) => Ok(word.clone()),
+			_ => Err(self.error(index, expected)),
+		}
+	}
+
+	fn string(&self, index: usize, expected: &'static str) -> Result<String> {
+		match self.tokens.get(index) {
+			Some(Token::String(value)) => Ok(value.clone()),
+			_ => Err(self.error(index, expected)),
+		}
+	}
+
+	fn number<T: FromStr>(&self, index: usize, expected: &'static str) -> Result<T> {
+		self.word(index, expected)?
+			.parse()
+			.map_err(|_| self.error(index, expected))
+	}
+
+	/// Optional name following the directive, like that of the empty state.
+	fn name(&self) -> Result<String> {
+		match self.tokens.len() {
+			1 => Ok(String::new()),
+			_ => self.word(1, "a name"),
+		}
+	}
+
+	/// Name, type and whether it is `const`, of a declaration like `.variable x Int const`.
+	fn declaration(&self) -> Result<(String, String, bool)> {
+		let is_const = match self.tokens.get(3) {
+			None => false,
+			Some(Token::Word(word)) if word.eq_ignore_ascii_case("const") => true,
+			_ => return Err(self.error(3, "const")),
+		};
+		Ok((self.word(1, "a name")?, self.word(2, "a type")?, is_const))
+	}
+}
+
+/// Resolves the escapes of a string, which are written like Rust's.
+fn unescape(chars: &mut std::str::Chars, line: usize) -> Result<String> {
+	let mut out = String::new();
+	loop {
+		match chars.next().ok_or(Error::UnterminatedString(line))? {
+			'"' => return Ok(out),
+			'\\' => match chars.next().ok_or(Error::UnterminatedString(line))? {
+				'n' => out.push('\n'),
+				't' => out.push('\t'),
+				'r' => out.push('\r'),
+				'0' => out.push('\0'),
+				'u' => {
+					let code = chars
+						.by_ref()
+						.skip_while(|&c| c == '{')
+						.take_while(|&c| c != '}')
+						.collect::<String>();
+					let c = u32::from_str_radix(&code, 16)
+						.ok()
+						.and_then(char::from_u32)
+						.ok_or(Error::UnterminatedString(line))?;
+					out.push(c);
+				}
+				c => out.push(c),
+			},
+			c => out.push(c),
+		}
+	}
+}
+
+fn tokenize(source: &str) -> Result<Vec<Line>> {
+	let mut lines = vec![];
+	for (i, text) in source.lines().enumerate() {
+		let number = i + 1;
+		let mut line = Line {
+			number,
+			tokens: vec![],
+			source_line: None,
+		};
+
+		let mut chars = text.chars();
+		let mut word = String::new();
+		while let Some(c) = chars.next() {
+			if !word.is_empty() && (c.is_whitespace() || c == ';' || c == '"') {
+				line.tokens.push(Token::Word(std::mem::take(&mut word)));
+			}
+
+			match c {
+				'"' => line
+					.tokens
+					.push(Token::String(unescape(&mut chars, number)?)),
+				';' => {
+					let comment = chars.as_str().trim();
+					line.source_line = comment
+						.strip_prefix("@line")
+						.and_then(|n| n.trim().parse().ok());
+					break;
+				}
+				c if c.is_whitespace() => (),
+				c => word.push(c),
+			}
+		}
+		if !word.is_empty() {
+			line.tokens.push(Token::Word(word));
+		}
+
+		if !line.tokens.is_empty() {
+			lines.push(line);
+		}
+	}
+	Ok(lines)
+}
+
+/// Operand of an instruction, or the initial value of a variable.
+fn value(token: &Token) -> Value {
+	let word = match token {
+		Token::String(value) => return Value::String(value.clone()),
+		Token::Word(word) => word,
+	};
+
+	match word.to_ascii_lowercase().as_str() {
+		"none" => Value::None,
+		"true" => Value::Bool(true),
+		"false" => Value::Bool(false),
+		"1.#inf" => Value::Float(f32::INFINITY),
+		"-1.#inf" => Value::Float(f32::NEG_INFINITY),
+		"1.#qnan" => Value::Float(f32::NAN),
+		_ => match (word.parse(), word.parse()) {
+			(Ok(value), _) => Value::Int(value),
+			(_, Ok(value)) if word.starts_with(|c: char| c.is_ascii_digit() || c == '-') => {
+				Value::Float(value)
+			}
+			_ => Value::Ident(word.clone()),
+		},
+	}
+}
+
+struct Assembler {
+	lines: std::vec::IntoIter<Line>,
+	game: Game,
+	/// Debug info of every function, in the order they are listed.
+	functions: Vec<DebugFunction>,
+	groups: Vec<PropertyGroup>,
+}
+
+impl Assembler {
+	fn next(&mut self, expected: &'static str) -> Result<Line> {
+		self.lines.next().ok_or(Error::UnexpectedEnd(expected))
+	}
+
+	/// Calls `f` with each line up to the directive `end`.
+	fn block(
+		&mut self,
+		end: &'static str,
+		mut f: impl FnMut(&mut Self, Line) -> Result<()>,
+	) -> Result<()> {
+		loop {
+			let line = self.next(end)?;
+			if line.is(end) {
+				return Ok(());
+			}
+			f(self, line)?;
+		}
+	}
+
+	fn object(&mut self, line: &Line) -> Result<Object> {
+		let mut words = (1..line.tokens.len())
+			.map(|i| line.word(i, "a name"))
+			.collect::<Result<Vec<_>>>()?;
+		let is_const = words
+			.last()
+			.is_some_and(|word| word.eq_ignore_ascii_case("const"));
+		if is_const {
+			words.pop();
+		}
+		if words.is_empty() || words.len() > 2 {
+			return Err(line.error(words.len().max(1), "an object name and parent"));
+		}
+
+		let mut object = Object {
+			name: words[0].clone(),
+			parent: words.get(1).cloned().unwrap_or_default(),
+			doc: String::new(),
+			is_const,
+			user_flags: 0,
+			auto_state: String::new(),
+			structs: vec![],
+			variables: vec![],
+			properties: vec![],
+			states: vec![],
+		};
+
+		self.block(".endObject", |a, line| {
+			match line.directive().as_str() {
+				".userflags" => object.user_flags = line.number(1, "user flags")?,
+				".docstring" => object.doc = line.string(1, "a doc string")?,
+				".autostate" => object.auto_state = line.name()?,
+				".structtable" if a.game.has_structs() => {
+					a.block(".endStructTable", |a, line| {
+						if !line.is(".struct") {
+							return Err(line.error(0, ".struct"));
+						}
+						let name = line.word(1, "a struct name")?;
+						let mut members = vec![];
+						a.block(".endStruct", |a, line| {
+							members.push(a.variable(&line)?);
+							Ok(())
+						})?;
+						object.structs.push(Struct { name, members });
+						Ok(())
+					})?
+				}
+				".variabletable" => a.block(".endVariableTable", |a, line| {
+					let member = a.variable(&line)?;
+					object.variables.push(Variable {
+						name: member.name,
+						ty: member.ty,
+						user_flags: member.user_flags,
+						value: member.value,
+						is_const: member.is_const,
+					});
+					Ok(())
+				})?,
+				".propertygrouptable" => a.block(".endPropertyGroupTable", |a, line| {
+					let group = a.group(&object.name, &line)?;
+					a.groups.push(group);
+					Ok(())
+				})?,
+				".propertytable" => a.block(".endPropertyTable", |a, line| {
+					let property = a.property(&object.name, &line)?;
+					object.properties.push(property);
+					Ok(())
+				})?,
+				".statetable" => a.block(".endStateTable", |a, line| {
+					if !line.is(".state") {
+						return Err(line.error(0, ".state"));
+					}
+					let name = line.name()?;
+					let mut functions = vec![];
+					a.block(".endState", |a, line| {
+						let (function, lines) = a.function(&line)?;
+						a.debug_function(
+							&object.name,
+							&name,
+							&function.name,
+							FunctionKind::Normal,
+							lines,
+						);
+						functions.push(function);
+						Ok(())
+					})?;
+					object.states.push(State { name, functions });
+					Ok(())
+				})?,
+				_ => return Err(line.error(0, "a part of an object")),
+			}
+			Ok(())
+		})?;
+
+		Ok(object)
+	}
+
+	/// Reads a variable, or a member of a struct which can also have documentation.
+	fn variable(&mut self, line: &Line) -> Result<StructMember> {
+		if !line.is(".variable") {
+			return Err(line.error(0, ".variable"));
+		}
+
+		let (name, ty, is_const) = line.declaration()?;
+		let mut member = StructMember {
+			name,
+			ty,
+			user_flags: 0,
+			value: Value::None,
+			is_const,
+			doc: String::new(),
+		};
+
+		self.block(".endVariable", |_, line| {
+			match line.directive().as_str() {
+				".userflags" => member.user_flags = line.number(1, "user flags")?,
+				".initialvalue" => match line.tokens.get(1) {
+					Some(token) => member.value = value(token),
+					None => return Err(line.error(1, "a value")),
+				},
+				".docstring" => member.doc = line.string(1, "a doc string")?,
+				_ => return Err(line.error(0, "a part of a variable")),
+			}
+			Ok(())
+		})?;
+
+		Ok(member)
+	}
+
+	fn group(&mut self, object: &str, line: &Line) -> Result<PropertyGroup> {
+		if !line.is(".propertyGroup") {
+			return Err(line.error(0, ".propertyGroup"));
+		}
+
+		let mut group = PropertyGroup {
+			object: object.to_owned(),
+			name: line.name()?,
+			doc: String::new(),
+			user_flags: 0,
+			properties: vec![],
+		};
+
+		self.block(".endPropertyGroup", |_, line| {
+			match line.directive().as_str() {
+				".userflags" => group.user_flags = line.number(1, "user flags")?,
+				".docstring" => group.doc = line.string(1, "a doc string")?,
+				".property" => group.properties.push(line.word(1, "a property name")?),
+				_ => return Err(line.error(0, "a part of a property group")),
+			}
+			Ok(())
+		})?;
+
+		Ok(group)
+	}
+
+	fn property(&mut self, object: &str, line: &Line) -> Result<Property> {
+		if !line.is(".property") {
+			return Err(line.error(0, ".property"));
+		}
+
+		let name = line.word(1, "a property name")?;
+		let ty = line.word(2, "a type")?;
+		let is_auto = match line.tokens.get(3) {
+			None => false,
+			Some(Token::Word(word)) if word.eq_ignore_ascii_case("auto") => true,
+			_ => return Err(line.error(3, "auto")),
+		};
+
+		let (mut doc, mut user_flags) = (String::new(), 0);
+		let (mut variable, mut get, mut set) = (None, None, None);
+		self.block(".endProperty", |a, line| {
+			match line.directive().as_str() {
+				".userflags" => user_flags = line.number(1, "user flags")?,
+				".docstring" => doc = line.string(1, "a doc string")?,
+				".autovar" if is_auto => variable = Some(line.word(1, "a variable name")?),
+				".function" if !is_auto => {
+					let (slot, kind) =
+						match line.word(1, "get or set")?.to_ascii_lowercase().as_str() {
+							"get" => (&mut get, FunctionKind::Getter),
+							"set" => (&mut set, FunctionKind::Setter),
+							_ => return Err(line.error(1, "get or set")),
+						};
+					let (function, lines) = a.function(&line)?;
+					a.debug_function(object, "", &name, kind, lines);
+					*slot = Some(Box::new(function));
+				}
+				_ => return Err(line.error(0, "a part of a property")),
+			}
+			Ok(())
+		})?;
+
+		let kind = match is_auto {
+			true => PropertyKind::Auto(variable.ok_or(Error::UnexpectedEnd(".autoVar"))?),
+			false => PropertyKind::Full { get, set },
+		};
+		Ok(Property {
+			name,
+			ty,
+			doc,
+			user_flags,
+			kind,
+		})
+	}
+
+	/// Reads a function, with the source line of each instruction if they are all annotated.
+	fn function(&mut self, line: &Line) -> Result<(Function, Option<Vec<u16>>)> {
+		if !line.is(".function") {
+			return Err(line.error(0, ".function"));
+		}
+
+		let mut function = Function {
+			name: line.word(1, "a function name")?,
+			return_type: "None".to_owned(),
+			doc: String::new(),
+			user_flags: 0,
+			is_global: false,
+			is_native: false,
+			parameters: vec![],
+			locals: vec![],
+			instructions: vec![],
+		};
+		for i in 2..line.tokens.len() {
+			match line
+				.word(i, "static or native")?
+				.to_ascii_lowercase()
+				.as_str()
+			{
+				"static" => function.is_global = true,
+				"native" => function.is_native = true,
+				_ => return Err(line.error(i, "static or native")),
+			}
+		}
+
+		let mut lines = None;
+		self.block(".endFunction", |a, line| {
+			match line.directive().as_str() {
+				".userflags" => function.user_flags = line.number(1, "user flags")?,
+				".docstring" => function.doc = line.string(1, "a doc string")?,
+				".return" => function.return_type = line.word(1, "a type")?,
+				".paramtable" => a.block(".endParamTable", |_, line| {
+					if !line.is(".param") {
+						return Err(line.error(0, ".param"));
+					}
+					let parameter = (line.word(1, "a name")?, line.word(2, "a type")?);
+					function.parameters.push(parameter);
+					Ok(())
+				})?,
+				".localtable" => a.block(".endLocalTable", |_, line| {
+					if !line.is(".local") {
+						return Err(line.error(0, ".local"));
+					}
+					let local = (line.word(1, "a name")?, line.word(2, "a type")?);
+					function.locals.push(local);
+					Ok(())
+				})?,
+				".code" => {
+					let (instructions, code_lines) = a.code()?;
+					function.instructions = instructions;
+					lines = code_lines;
+				}
+				_ => return Err(line.error(0, "a part of a function")),
+			}
+			Ok(())
+		})?;
+
+		// Natives have no code to have lines.
+		if function.is_native {
+			lines = None;
+		}
+		Ok((function, lines))
+	}
+
+	fn code(&mut self) -> Result<(Vec<Instruction>, Option<Vec<u16>>)> {
+		let mut instructions = vec![];
+		let mut annotations = vec![];
+		let mut labels = HashMap::new();
+		// Index and source line of each jump, with the label it goes to.
+		let mut jumps = vec![];
+
+		let game = self.game;
+		self.block(".endCode", |_, line| {
+			let name = line.word(0, "an instruction or label")?;
+			if let Some(label) = name.strip_suffix(':').filter(|_| line.tokens.len() == 1) {
+				if labels
+					.insert(label.to_owned(), instructions.len())
+					.is_some()
+				{
+					return Err(Error::DuplicateLabel {
+						name: label.to_owned(),
+						line: line.number,
+					});
+				}
+				return Ok(());
+			}
+
+			let opcode = Opcode::from_name(&name).ok_or_else(|| Error::UnknownOpcode {
+				name: name.clone(),
+				line: line.number,
+			})?;
+			if !opcode.is_supported(game) {
+				return Err(Error::Unsupported {
+					opcode: opcode.name(),
+					game,
+					line: line.number,
+				});
+			}
+
+			let operands = &line.tokens[1..];
+			let fixed = opcode.fixed_args();
+			let valid = match opcode.is_variadic() {
+				true => operands.len() >= fixed,
+				false => operands.len() == fixed,
+			};
+			if !valid {
+				let at_least = if opcode.is_variadic() {
+					"at least "
+				} else {
+					""
+				};
+				return Err(Error::Operands {
+					opcode: opcode.name(),
+					expected: format!("{at_least}{fixed}"),
+					got: operands.len(),
+					line: line.number,
+				});
+			}
+
+			// Jumps end with the label they go to.
+			let mut args = operands.iter().map(value).collect::<Vec<_>>();
+			if let Opcode::Jmp | Opcode::JmpT | Opcode::JmpF = opcode {
+				let label = line.word(line.tokens.len() - 1, "a label")?;
+				jumps.push((instructions.len(), line.number, label));
+				args.pop();
+				args.push(Value::Int(0));
+			}
+
+			instructions.push(Instruction::new(opcode, args));
+			annotations.push((line.number, line.source_line));
+			Ok(())
+		})?;
+
+		for (index, line, label) in jumps {
+			let target = labels
+				.get(&label)
+				.ok_or(Error::UndefinedLabel { name: label, line })?;
+			let offset = *target as i32 - index as i32;
+			*instructions[index].args.last_mut().unwrap() = Value::Int(offset);
+		}
+
+		// Instructions either all have lines, or none do.
+		let lines = annotations
+			.iter()
+			.map(|&(number, line)| line.ok_or(Error::MissingLine(number)))
+			.collect::<Result<Vec<_>>>();
+		let lines = match lines {
+			Ok(lines) => Some(lines),
+			Err(_) if annotations.iter().all(|(_, line)| line.is_none()) => None,
+			Err(why) => return Err(why),
+		};
+
+		Ok((instructions, lines))
+	}
+
+	fn debug_function(
+		&mut self,
+		object: &str,
+		state: &str,
+		function: &str,
+		kind: FunctionKind,
+		lines: Option<Vec<u16>>,
+	) {
+		if let Some(lines) = lines {
+			self.functions.push(DebugFunction {
+				object: object.to_owned(),
+				state: state.to_owned(),
+				function: function.to_owned(),
+				kind,
+				lines,
+			});
+		}
+	}
+}
+
+/// Assembles a listing into a script for `game`, with debug info if it has a `.modifyTime`.
+pub fn assemble(source: &str, game: Game) -> Result<Pex> {
+	let mut assembler = Assembler {
+		lines: tokenize(source)?.into_iter(),
+		game,
+		functions: vec![],
+		groups: vec![],
+	};
+
+	let mut pex = Pex {
+		header: Header {
+			game,
+			compilation_time: 0,
+			source: String::new(),
+			user: String::new(),
+			machine: String::new(),
+		},
+		debug_info: None,
+		user_flags: vec![],
+		objects: vec![],
+	};
+	let mut modification_time = None;
+
+	while let Some(line) = assembler.lines.next() {
+		let header = &mut pex.header;
+		match line.directive().as_str() {
+			".info" => assembler.block(".endInfo", |_, line| {
+				match line.directive().as_str() {
+					".source" => header.source = line.string(1, "a path")?,
+					".modifytime" => modification_time = Some(line.number(1, "a time")?),
+					".compiletime" => header.compilation_time = line.number(1, "a time")?,
+					".user" => header.user = line.string(1, "a user name")?,
+					".computer" => header.machine = line.string(1, "a computer name")?,
+					_ => return Err(line.error(0, "a part of the info")),
+				}
+				Ok(())
+			})?,
+			".userflagsref" => assembler.block(".endUserFlagsRef", |_, line| {
+				if !line.is(".flag") {
+					return Err(line.error(0, ".flag"));
+				}
+				pex.user_flags.push(UserFlag {
+					name: line.word(1, "a flag name")?,
+					bit: line.number(2, "a bit index")?,
+				});
+				Ok(())
+			})?,
+			".objecttable" => assembler.block(".endObjectTable", |a, line| {
+				if !line.is(".object") {
+					return Err(line.error(0, ".object"));
+				}
+				pex.objects.push(a.object(&line)?);
+				Ok(())
+			})?,
+			_ => return Err(line.error(0, ".info, .userFlagsRef or .objectTable")),
+		}
+	}
+
+	// Members of structs are listed in the order they were declared.
+	let struct_orders = pex
+		.objects
+		.iter()
+		.flat_map(|object| {
+			object.structs.iter().map(|r#struct| StructOrder {
+				object: object.name.clone(),
+				name: r#struct.name.clone(),
+				members: r#struct.members.iter().map(|m| m.name.clone()).collect(),
+			})
+		})
+		.collect();
+
+	pex.debug_info = modification_time.map(|modification_time| DebugInfo {
+		modification_time,
+		functions: assembler.functions,
+		property_groups: assembler.groups,
+		struct_orders,
+	});
+	Ok(pex)
+}
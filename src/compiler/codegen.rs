@@ -0,0 +1,1034 @@
+//! Lowers the body of a function to Papyrus instructions.
+
+use super::conversion::constant;
+use super::conversion::{Conversion, Hierarchy, Lowering};
+use super::passes::calls::{state_function, Calls};
+use super::passes::context::is_global;
+use super::passes::resolve::{Binding, Resolution};
+use super::passes::typecheck::Typing;
+use super::pex::{Instruction, Opcode, Value};
+use super::types::Ty;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::parser::{ast::*, Rule};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("cannot generate code for a value whose type is unknown")]
+	Untyped,
+
+	#[error("cannot call a function whose declaration isn't loaded")]
+	Undeclared,
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Untyped => "E1501",
+			Self::Undeclared => "E1502",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Position in the code that jumps can target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label(pub usize);
+
+impl fmt::Display for Label {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "label{}", self.0)
+	}
+}
+
+/// Instruction whose jumps target labels, rather than relative offsets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ir {
+	/// Any instruction other than a jump.
+	Op(Instruction),
+	Jump(Label),
+	JumpTrue(Value, Label),
+	JumpFalse(Value, Label),
+	Label(Label),
+}
+
+impl fmt::Display for Ir {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Op(instruction) => write!(f, "{instruction}"),
+			Self::Jump(label) => write!(f, "jmp {label}"),
+			Self::JumpTrue(value, label) => write!(f, "jmpt {value} {label}"),
+			Self::JumpFalse(value, label) => write!(f, "jmpf {value} {label}"),
+			Self::Label(label) => write!(f, "{label}:"),
+		}
+	}
+}
+
+/// Code generated for a single function.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Code {
+	/// Name and type of each local, including temporaries.
+	pub locals: Vec<(String, String)>,
+	pub ir: Vec<Ir>,
+	/// Source line of each entry of `ir`.
+	pub lines: Vec<usize>,
+}
+
+impl Code {
+	/// Instructions with labels resolved to relative jumps.
+	pub fn instructions(&self) -> Vec<Instruction> {
+		let mut positions = HashMap::new();
+		let mut position: usize = 0;
+		for ir in &self.ir {
+			match ir {
+				Ir::Label(label) => {
+					positions.insert(*label, position);
+				}
+				_ => position += 1,
+			}
+		}
+
+		let offset = |from: usize, to: &Label| Value::Int(positions[to] as i32 - from as i32);
+
+		self.ir
+			.iter()
+			.filter(|ir| !matches!(ir, Ir::Label(_)))
+			.enumerate()
+			.map(|(i, ir)| match ir {
+				Ir::Op(instruction) => instruction.clone(),
+				Ir::Jump(label) => Instruction::new(Opcode::Jmp, vec![offset(i, label)]),
+				Ir::JumpTrue(value, label) => {
+					Instruction::new(Opcode::JmpT, vec![value.clone(), offset(i, label)])
+				}
+				Ir::JumpFalse(value, label) => {
+					Instruction::new(Opcode::JmpF, vec![value.clone(), offset(i, label)])
+				}
+				Ir::Label(_) => unreachable!(),
+			})
+			.collect()
+	}
+
+	/// Source line of each of the [instructions](Self::instructions).
+	pub fn instruction_lines(&self) -> Vec<usize> {
+		self.ir
+			.iter()
+			.zip(&self.lines)
+			.filter(|(ir, _)| !matches!(ir, Ir::Label(_)))
+			.map(|(_, line)| *line)
+			.collect()
+	}
+}
+
+/// Local that receives the results of calls which are thrown away.
+pub const NONE_VAR: &str = "::NoneVar";
+
+fn ident(name: &str) -> Value {
+	Value::Ident(name.to_owned())
+}
+
+fn self_ref() -> Value {
+	ident("self")
+}
+
+/// Operand an instruction writes its result to.
+fn destination(opcode: Opcode) -> Option<usize> {
+	use Opcode::*;
+
+	match opcode {
+		CallMethod | CallStatic | PropGet => Some(2),
+		CallParent | ArrayFindElement | ArrayRFindElement | ArrayFindStruct | ArrayRFindStruct => {
+			Some(1)
+		}
+		Nop | Jmp | JmpT | JmpF | Return | PropSet | ArraySetElement | StructSet | ArrayAdd
+		| ArrayInsert | ArrayRemoveLast | ArrayRemove | ArrayClear => None,
+		_ => Some(0),
+	}
+}
+
+const TEMP_PREFIX: &str = "::temp";
+
+/// Opcode of a builtin method of arrays.
+pub(crate) fn array_opcode(method: &str) -> Option<Opcode> {
+	Some(match method.to_ascii_lowercase().as_str() {
+		"find" => Opcode::ArrayFindElement,
+		"rfind" => Opcode::ArrayRFindElement,
+		"findstruct" => Opcode::ArrayFindStruct,
+		"rfindstruct" => Opcode::ArrayRFindStruct,
+		"add" => Opcode::ArrayAdd,
+		"insert" => Opcode::ArrayInsert,
+		"remove" => Opcode::ArrayRemove,
+		"removelast" => Opcode::ArrayRemoveLast,
+		"clear" => Opcode::ArrayClear,
+		_ => return None,
+	})
+}
+
+/// Hands out `::tempN` locals for intermediate results, reusing those whose value has been used.
+/// Numbers are handed out lowest first, so the same code always gets the same names.
+#[derive(Debug, Default)]
+struct Temps {
+	count: usize,
+	/// Numbers of the temporaries not holding a value, by lowercased type.
+	free: HashMap<String, BTreeSet<usize>>,
+	/// Lowercased type of each temporary, by number.
+	types: Vec<String>,
+}
+
+impl Temps {
+	/// Name of a free temporary of the given type, and whether it had to be created.
+	fn acquire(&mut self, ty: &str) -> (String, bool) {
+		let key = ty.to_ascii_lowercase();
+		let reused = self.free.get_mut(&key).and_then(|free| free.pop_first());
+
+		let (number, created) = match reused {
+			Some(number) => (number, false),
+			None => {
+				self.count += 1;
+				self.types.push(key);
+				(self.count - 1, true)
+			}
+		};
+
+		(format!("{TEMP_PREFIX}{number}"), created)
+	}
+
+	/// Makes `value` available again if it is a temporary.
+	fn release(&mut self, value: &Value) {
+		let Value::Ident(name) = value else {
+			return;
+		};
+
+		let Some(number) = name
+			.strip_prefix(TEMP_PREFIX)
+			.and_then(|n| n.parse::<usize>().ok())
+		else {
+			return;
+		};
+
+		if let Some(ty) = self.types.get(number) {
+			self.free.entry(ty.clone()).or_default().insert(number);
+		}
+	}
+}
+
+struct Generator<'a, 'r> {
+	ast: &'a Ast,
+	resolution: &'r Resolution<'a>,
+	hierarchy: &'r dyn Hierarchy,
+	calls: &'r Calls<'a>,
+	typing: &'r Typing,
+	diagnostics: &'r mut Diagnostics,
+
+	code: Code,
+	labels: usize,
+	temps: Temps,
+	/// Statement being generated.
+	span: Span,
+	/// Names given to locals, which are renamed when they clash with one declared in another block.
+	names: HashMap<*const Statement, String>,
+	return_type: Ty,
+}
+
+impl<'a> Generator<'a, '_> {
+	/// Emits an instruction, which uses up the temporaries it reads.
+	fn op(&mut self, opcode: Opcode, args: Vec<Value>) {
+		let destination = destination(opcode).and_then(|i| args.get(i));
+		for arg in &args {
+			if Some(arg) != destination {
+				self.temps.release(arg);
+			}
+		}
+
+		self.push(Ir::Op(Instruction::new(opcode, args)));
+	}
+
+	fn push(&mut self, ir: Ir) {
+		self.code.ir.push(ir);
+		self.code.lines.push(self.span.line);
+	}
+
+	/// Emits a conditional jump on a value that isn't needed afterwards.
+	fn branch(&mut self, jump: fn(Value, Label) -> Ir, cond: Value, label: Label) {
+		self.temps.release(&cond);
+		self.push(jump(cond, label));
+	}
+
+	fn label(&mut self) -> Label {
+		self.labels += 1;
+		Label(self.labels - 1)
+	}
+
+	fn place(&mut self, label: Label) {
+		self.push(Ir::Label(label));
+	}
+
+	fn local(&mut self, name: String, ty: &Ty) {
+		if !self.code.locals.iter().any(|(n, _)| n == &name) {
+			self.code.locals.push((name, ty.to_string()));
+		}
+	}
+
+	fn ty(&self, expr: &Expression) -> Option<Ty> {
+		self.typing.get(expr).cloned()
+	}
+
+	/// Resolves a type as written in the source, taking structs declared in this script into account.
+	fn declared(&self, ty: &str) -> Ty {
+		let structs = &self.resolution.symbols.structs;
+		let is_struct = |name: &str| structs.contains_key(&name.to_ascii_lowercase());
+
+		match Ty::parse(ty) {
+			Ty::Object(name) if is_struct(&name) => Ty::Struct(name),
+			Ty::Array(inner) => match *inner {
+				Ty::Object(name) if is_struct(&name) => Ty::Array(Box::new(Ty::Struct(name))),
+				inner => Ty::Array(Box::new(inner)),
+			},
+			ty => ty,
+		}
+	}
+
+	/// Local for an intermediate result, which is free again once an instruction reads it.
+	/// Every value held in one has a type, as [compile](super::compile) rejects scripts with values it couldn't infer.
+	/// Reports a value whose type is unknown, which analysis should have caught.
+	fn untyped(&mut self) {
+		self.report(Error::Untyped);
+	}
+
+	fn report(&mut self, error: Error) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(self.span));
+	}
+
+	fn temp(&mut self, ty: Option<&Ty>) -> Value {
+		let ty = ty.unwrap_or_else(|| {
+			self.untyped();
+			&Ty::None
+		});
+		let (name, created) = self.temps.acquire(&ty.to_string());
+		if created {
+			self.local(name.clone(), ty);
+		}
+		Value::Ident(name)
+	}
+
+	fn none_var(&mut self) -> Value {
+		self.local(NONE_VAR.to_owned(), &Ty::None);
+		ident(NONE_VAR)
+	}
+
+	/// Converts `value` if it has to be cast to be used as a `to`.
+	fn coerce(&mut self, value: Value, from: Option<&Ty>, to: &Ty) -> Value {
+		let Some(from) = from else {
+			return value;
+		};
+
+		if Conversion::between(from, to, self.hierarchy).lowering() != Some(Lowering::Cast) {
+			return value;
+		}
+
+		// Literals are converted right away, the same way as the `cast` opcode would.
+		let literal = match (&value, to) {
+			(Value::Int(i), Ty::Float) => Some(Value::Float(*i as f32)),
+
+			(Value::None, Ty::Bool) => Some(Value::Bool(false)),
+			(Value::Int(i), Ty::Bool) => Some(Value::Bool(*i != 0)),
+			(Value::Float(f), Ty::Bool) => Some(Value::Bool(*f != 0.0)),
+			(Value::String(s), Ty::Bool) => Some(Value::Bool(!s.is_empty())),
+
+			(Value::None, Ty::String) => Some(Value::String(String::from("None"))),
+			(Value::Int(i), Ty::String) => Some(Value::String(i.to_string())),
+			(Value::Float(f), Ty::String) => Some(Value::String(format!("{f:.6}"))),
+			(Value::Bool(b), Ty::String) => {
+				let text = if *b { "True" } else { "False" };
+				Some(Value::String(text.to_owned()))
+			}
+			_ => None,
+		};
+		if let Some(literal) = literal {
+			return literal;
+		}
+
+		let temp = self.temp(Some(to));
+		self.op(Opcode::Cast, vec![temp.clone(), value]);
+		temp
+	}
+
+	fn operand(&mut self, expr: &'a Expression, to: &Ty) -> Value {
+		let value = self.expression(expr);
+		let from = self.ty(expr);
+		self.coerce(value, from.as_ref(), to)
+	}
+
+	/// Value of `expr` as a `to`, if what it is written to has a type.
+	fn converted(&mut self, expr: &'a Expression, to: Option<&Ty>) -> Value {
+		match to {
+			Some(to) => self.operand(expr, to),
+			None => self.expression(expr),
+		}
+	}
+
+	/// Name of a variable in the code, for bindings that are variables.
+	fn variable(&self, binding: Binding<'a>, name: &str) -> Option<String> {
+		match binding {
+			Binding::Local(stmt) => self.names.get(&(stmt as *const _)).cloned(),
+			Binding::Parameter(param) => Some(param.1.clone()),
+			Binding::Variable(stmt) => stmt.name().map(str::to_owned),
+			Binding::Script(_) if name.eq_ignore_ascii_case("self") => Some("self".to_owned()),
+			Binding::Script(_) | Binding::Import(_) => Some(name.to_owned()),
+			_ => None,
+		}
+	}
+
+	/// Reads a variable or property by name.
+	fn load(&mut self, binding: Option<Binding<'a>>, name: &str, ty: Option<&Ty>) -> Value {
+		if let Some(variable) = binding.and_then(|b| self.variable(b, name)) {
+			return Value::Ident(variable);
+		}
+
+		// Anything else is a property, possibly inherited.
+		let temp = self.temp(ty);
+		self.op(Opcode::PropGet, vec![ident(name), self_ref(), temp.clone()]);
+		temp
+	}
+
+	/// Writes a variable or property by name.
+	fn store(&mut self, binding: Option<Binding<'a>>, name: &str, value: Value) {
+		match binding.and_then(|b| self.variable(b, name)) {
+			Some(variable) => self.op(Opcode::Assign, vec![ident(&variable), value]),
+			None => self.op(Opcode::PropSet, vec![ident(name), self_ref(), value]),
+		}
+	}
+
+	fn arithmetic(
+		&mut self,
+		expr: &'a Expression,
+		lhs: &'a Expression,
+		rhs: &'a Expression,
+	) -> Value {
+		let ty = self.ty(expr);
+		let (int, float) = match expr {
+			Expression::Addition(..) => (Opcode::IAdd, Opcode::FAdd),
+			Expression::Subtraction(..) => (Opcode::ISub, Opcode::FSub),
+			Expression::Multiplication(..) => (Opcode::IMul, Opcode::FMul),
+			Expression::Division(..) => (Opcode::IDiv, Opcode::FDiv),
+			_ => (Opcode::IMod, Opcode::IMod),
+		};
+
+		let (opcode, operand) = match ty {
+			Some(Ty::String) => (Opcode::StrCat, Ty::String),
+			Some(Ty::Int) => (int, Ty::Int),
+			Some(Ty::Float) => (float, Ty::Float),
+			None => return self.temp(None),
+			Some(ty) => unreachable!("arithmetic on {ty}"),
+		};
+
+		let lhs = self.operand(lhs, &operand);
+		let rhs = self.operand(rhs, &operand);
+		let temp = self.temp(ty.as_ref());
+		self.op(opcode, vec![temp.clone(), lhs, rhs]);
+		temp
+	}
+
+	fn comparison(
+		&mut self,
+		expr: &'a Expression,
+		lhs: &'a Expression,
+		rhs: &'a Expression,
+	) -> Value {
+		let (opcode, negate) = match expr {
+			Expression::Equal(..) => (Opcode::CmpEq, false),
+			Expression::NotEqual(..) => (Opcode::CmpEq, true),
+			Expression::LessThan(..) => (Opcode::CmpLt, false),
+			Expression::LessThanOrEqual(..) => (Opcode::CmpLe, false),
+			Expression::GreaterThan(..) => (Opcode::CmpGt, false),
+			_ => (Opcode::CmpGe, false),
+		};
+
+		// Mixed numbers are compared as floats.
+		let (lhs_ty, rhs_ty) = (self.ty(lhs), self.ty(rhs));
+		let mut lhs = self.expression(lhs);
+		let mut rhs = self.expression(rhs);
+		if let (Some(l), Some(r)) = (&lhs_ty, &rhs_ty) {
+			if l.is_numeric() && r.is_numeric() && l != r {
+				lhs = self.coerce(lhs, lhs_ty.as_ref(), &Ty::Float);
+				rhs = self.coerce(rhs, rhs_ty.as_ref(), &Ty::Float);
+			}
+		}
+
+		let temp = self.temp(Some(&Ty::Bool));
+		self.op(opcode, vec![temp.clone(), lhs, rhs]);
+		if negate {
+			self.op(Opcode::Not, vec![temp.clone(), temp.clone()]);
+		}
+		temp
+	}
+
+	/// `&&` and `||`, which skip their right side once the result is known.
+	fn logical(&mut self, and: bool, lhs: &'a Expression, rhs: &'a Expression) -> Value {
+		let temp = self.temp(Some(&Ty::Bool));
+		let end = self.label();
+
+		let value = self.operand(lhs, &Ty::Bool);
+		self.op(Opcode::Assign, vec![temp.clone(), value]);
+		self.push(match and {
+			true => Ir::JumpFalse(temp.clone(), end),
+			false => Ir::JumpTrue(temp.clone(), end),
+		});
+
+		let value = self.operand(rhs, &Ty::Bool);
+		self.op(Opcode::Assign, vec![temp.clone(), value]);
+		self.place(end);
+		temp
+	}
+
+	fn member(&mut self, expr: &'a Expression, base: &'a Expression, member: &str) -> Value {
+		let ty = self.ty(expr);
+		let base_ty = self.ty(base);
+		let object = self.expression(base);
+		let temp = self.temp(ty.as_ref());
+
+		match base_ty {
+			Some(Ty::Array(_)) if member.eq_ignore_ascii_case("Length") => {
+				self.op(Opcode::ArrayLength, vec![temp.clone(), object]);
+			}
+			Some(Ty::Struct(_)) => {
+				self.op(Opcode::StructGet, vec![temp.clone(), object, ident(member)]);
+			}
+			_ => self.op(Opcode::PropGet, vec![ident(member), object, temp.clone()]),
+		}
+		temp
+	}
+
+	/// Values passed to a call, in the order of the parameters when the function is known.
+	fn arguments(&mut self, call: &'a Expression, args: &'a [Argument]) -> Vec<Value> {
+		let Some(matched) = self.calls.get(call) else {
+			// Only the state functions can be called without a declaration to match arguments with.
+			if let Expression::Call(target, _) = call {
+				if state_function(target).is_none() {
+					self.report(Error::Undeclared);
+				}
+			}
+			return args
+				.iter()
+				.map(|arg| self.expression(arg.value()))
+				.collect();
+		};
+
+		let parameters = match matched.function {
+			Statement::Function { parameters, .. }
+			| Statement::NativeFunction { parameters, .. }
+			| Statement::Event { parameters, .. } => parameters,
+			_ => return vec![],
+		};
+
+		// Parameters of other scripts can't name the structs of this one.
+		let local = matched.script.is_none();
+		let arguments = matched.arguments.clone();
+		parameters
+			.iter()
+			.zip(arguments)
+			.map(|(param, value)| match value {
+				Some(value) => {
+					let to = match local {
+						true => self.declared(&param.0),
+						false => Ty::parse(&param.0),
+					};
+					self.operand(value.expression(), &to)
+				}
+				None => Value::None,
+			})
+			.collect()
+	}
+
+	/// Where the result of a call goes.
+	fn result(&mut self, call: &Expression, discard: bool) -> Value {
+		let ty = self.ty(call);
+		match (discard, &ty) {
+			(true, _) | (_, Some(Ty::None)) => self.none_var(),
+			_ => self.temp(ty.as_ref()),
+		}
+	}
+
+	/// Builtin methods of arrays, returning [None] for anything else.
+	fn array_method(
+		&mut self,
+		call: &'a Expression,
+		array: Value,
+		method: &str,
+		discard: bool,
+	) -> Option<Value> {
+		let Expression::Call(_, args) = call else {
+			return None;
+		};
+		let opcode = array_opcode(method)?;
+
+		let mut args = args
+			.iter()
+			.map(|arg| self.expression(arg.value()))
+			.collect::<Vec<_>>();
+		let result = &self.result(call, discard);
+		let mut arg = |i: usize, default: Value| {
+			if args.len() > i {
+				std::mem::replace(&mut args[i], Value::None)
+			} else {
+				default
+			}
+		};
+
+		let operands = match opcode {
+			Opcode::ArrayFindElement => vec![
+				array,
+				result.clone(),
+				arg(0, Value::None),
+				arg(1, Value::Int(0)),
+			],
+			Opcode::ArrayRFindElement => vec![
+				array,
+				result.clone(),
+				arg(0, Value::None),
+				arg(1, Value::Int(-1)),
+			],
+			Opcode::ArrayFindStruct => vec![
+				array,
+				result.clone(),
+				arg(0, Value::None),
+				arg(1, Value::None),
+				arg(2, Value::Int(0)),
+			],
+			Opcode::ArrayRFindStruct => vec![
+				array,
+				result.clone(),
+				arg(0, Value::None),
+				arg(1, Value::None),
+				arg(2, Value::Int(-1)),
+			],
+			Opcode::ArrayAdd => vec![array, arg(0, Value::None), arg(1, Value::Int(1))],
+			Opcode::ArrayInsert => vec![array, arg(0, Value::None), arg(1, Value::Int(0))],
+			Opcode::ArrayRemove => vec![array, arg(0, Value::None), arg(1, Value::Int(1))],
+			Opcode::ArrayRemoveLast | Opcode::ArrayClear => vec![array],
+			opcode => unreachable!("{opcode:?} is not an array method"),
+		};
+
+		self.op(opcode, operands);
+		Some(result.clone())
+	}
+
+	fn call(&mut self, expr: &'a Expression, discard: bool) -> Value {
+		let Expression::Call(target, args) = expr else {
+			unreachable!()
+		};
+
+		let (opcode, mut operands) = match target.as_ref() {
+			// Global functions are called on the script declaring them, be it this one, a parent or an import.
+			Expression::Ident(name) => match self.calls.get(expr) {
+				Some(call) if is_global(call.function) => {
+					let script = call.script.unwrap_or(&self.ast.script_info.script_name);
+					(Opcode::CallStatic, vec![ident(script), ident(name)])
+				}
+				_ => (Opcode::CallMethod, vec![ident(name), self_ref()]),
+			},
+
+			Expression::DotIndex(base, name) => match base.as_ref() {
+				Expression::Parent => (Opcode::CallParent, vec![ident(name)]),
+
+				// Calls on a script name are calls to its global functions.
+				Expression::Ident(script)
+					if matches!(
+						self.resolution.get(base),
+						Some(Binding::Script(_) | Binding::Import(_))
+					) && !self.typing.is_inherited(base) =>
+				{
+					(Opcode::CallStatic, vec![ident(script), ident(name)])
+				}
+
+				base => {
+					let object = self.expression(base);
+					if let Some(Ty::Array(_)) = self.ty(base) {
+						if let Some(value) = self.array_method(expr, object.clone(), name, discard)
+						{
+							return value;
+						}
+					}
+					(Opcode::CallMethod, vec![ident(name), object])
+				}
+			},
+
+			target => {
+				let object = self.expression(target);
+				(Opcode::CallMethod, vec![Value::None, object])
+			}
+		};
+
+		let arguments = self.arguments(expr, args);
+		let result = self.result(expr, discard);
+		operands.push(result.clone());
+		operands.extend(arguments);
+		self.op(opcode, operands);
+		result
+	}
+
+	fn expression(&mut self, expr: &'a Expression) -> Value {
+		if let Some(value) = constant(expr) {
+			return value;
+		}
+
+		match expr {
+			Expression::Ident(name) => {
+				// Inherited properties were taken for scripts by resolve.
+				let binding = self
+					.resolution
+					.get(expr)
+					.filter(|_| !self.typing.is_inherited(expr));
+				let ty = self.ty(expr);
+				self.load(binding, name, ty.as_ref())
+			}
+
+			Expression::SelfRef | Expression::Parent => self_ref(),
+
+			Expression::Addition(lhs, rhs)
+			| Expression::Subtraction(lhs, rhs)
+			| Expression::Multiplication(lhs, rhs)
+			| Expression::Division(lhs, rhs)
+			| Expression::Modulo(lhs, rhs) => self.arithmetic(expr, lhs, rhs),
+
+			Expression::Equal(lhs, rhs)
+			| Expression::NotEqual(lhs, rhs)
+			| Expression::LessThan(lhs, rhs)
+			| Expression::LessThanOrEqual(lhs, rhs)
+			| Expression::GreaterThan(lhs, rhs)
+			| Expression::GreaterThanOrEqual(lhs, rhs) => self.comparison(expr, lhs, rhs),
+
+			Expression::And(lhs, rhs) => self.logical(true, lhs, rhs),
+			Expression::Or(lhs, rhs) => self.logical(false, lhs, rhs),
+
+			Expression::Not(inner) => {
+				let value = self.expression(inner);
+				let temp = self.temp(Some(&Ty::Bool));
+				self.op(Opcode::Not, vec![temp.clone(), value]);
+				temp
+			}
+
+			Expression::Negate(inner) => {
+				let ty = self.ty(inner);
+				let opcode = match ty {
+					Some(Ty::Int) => Opcode::INeg,
+					_ => Opcode::FNeg,
+				};
+				let value = self.expression(inner);
+				let temp = self.temp(ty.as_ref());
+				self.op(opcode, vec![temp.clone(), value]);
+				temp
+			}
+
+			Expression::Cast(inner, ty) => {
+				let to = self.declared(ty);
+				let from = self.ty(inner);
+				let value = self.expression(inner);
+
+				match from.map(|from| Conversion::between(&from, &to, self.hierarchy).lowering()) {
+					Some(Some(Lowering::Assign)) => value,
+					_ => {
+						let temp = self.temp(Some(&to));
+						self.op(Opcode::Cast, vec![temp.clone(), value]);
+						temp
+					}
+				}
+			}
+
+			Expression::Is(inner, ty) => {
+				let value = self.expression(inner);
+				let temp = self.temp(Some(&Ty::Bool));
+				self.op(Opcode::Is, vec![temp.clone(), value, ident(ty)]);
+				temp
+			}
+
+			Expression::DotIndex(base, member) => self.member(expr, base, member),
+
+			Expression::BracketIndex(array, index) => {
+				let ty = self.ty(expr);
+				let array = self.expression(array);
+				let index = self.expression(index);
+				let temp = self.temp(ty.as_ref());
+				self.op(Opcode::ArrayGetElement, vec![temp.clone(), array, index]);
+				temp
+			}
+
+			Expression::Call(..) => self.call(expr, false),
+
+			Expression::Array(_, size) => {
+				let ty = self.ty(expr);
+				let size = self.expression(size);
+				let temp = self.temp(ty.as_ref());
+				self.op(Opcode::ArrayCreate, vec![temp.clone(), size]);
+				temp
+			}
+
+			Expression::Struct(_) => {
+				let ty = self.ty(expr);
+				let temp = self.temp(ty.as_ref());
+				self.op(Opcode::StructCreate, vec![temp.clone()]);
+				temp
+			}
+
+			Expression::Bool(_)
+			| Expression::String(_)
+			| Expression::Integer(_)
+			| Expression::Float(_)
+			| Expression::None => unreachable!("literals are constants"),
+		}
+	}
+
+	/// Declares a local, renaming it if a local with the same name was declared in another block.
+	fn declare(&mut self, stmt: &'a Statement, name: &str, ty: &Ty) -> String {
+		let mut unique = name.to_owned();
+		let mut n = 0;
+		while self
+			.code
+			.locals
+			.iter()
+			.any(|(local, _)| local.eq_ignore_ascii_case(&unique))
+		{
+			n += 1;
+			unique = format!("::{name}_{n}");
+		}
+
+		self.local(unique.clone(), ty);
+		self.names.insert(stmt, unique.clone());
+		unique
+	}
+
+	fn assignment(
+		&mut self,
+		stmt: &'a Statement,
+		name: &str,
+		indexes: &'a [Index],
+		value: &'a Expression,
+	) {
+		let binding = self.resolution.target(stmt);
+		let mut ty = self.typing.target(stmt).cloned();
+
+		let Some((last, path)) = indexes.split_last() else {
+			let value = self.converted(value, ty.as_ref());
+			return self.store(binding, name, value);
+		};
+
+		let mut object = self.load(binding, name, ty.as_ref());
+		for index in path {
+			let (opcode, key) = match (index, &ty) {
+				(Index::Bracket(index), _) => (Opcode::ArrayGetElement, self.expression(index)),
+				(Index::Dot(member), Some(Ty::Struct(_))) => (Opcode::StructGet, ident(member)),
+				(Index::Dot(member), _) => (Opcode::PropGet, ident(member)),
+			};
+
+			ty = self.typing.index(index).cloned();
+			let temp = self.temp(ty.as_ref());
+			match opcode {
+				Opcode::PropGet => self.op(opcode, vec![key, object, temp.clone()]),
+				_ => self.op(opcode, vec![temp.clone(), object, key]),
+			}
+			object = temp;
+		}
+
+		let to = self.typing.index(last).cloned();
+		match (last, ty) {
+			(Index::Bracket(index), _) => {
+				let index = self.expression(index);
+				let value = self.converted(value, to.as_ref());
+				self.op(Opcode::ArraySetElement, vec![object, index, value]);
+			}
+			(Index::Dot(member), Some(Ty::Struct(_))) => {
+				let value = self.converted(value, to.as_ref());
+				self.op(Opcode::StructSet, vec![object, ident(member), value]);
+			}
+			(Index::Dot(member), _) => {
+				let value = self.converted(value, to.as_ref());
+				self.op(Opcode::PropSet, vec![ident(member), object, value]);
+			}
+		}
+	}
+
+	fn compound_assignment(
+		&mut self,
+		stmt: &'a Statement,
+		name: &str,
+		op: Rule,
+		value: &'a Expression,
+	) {
+		let binding = self.resolution.target(stmt);
+		let ty = self.typing.target(stmt).cloned();
+
+		let (opcode, operand) = match (op, &ty) {
+			(Rule::op_add, Some(Ty::String)) => (Opcode::StrCat, Ty::String),
+			(Rule::op_add, Some(Ty::Int)) => (Opcode::IAdd, Ty::Int),
+			(Rule::op_sub, Some(Ty::Int)) => (Opcode::ISub, Ty::Int),
+			(Rule::op_mul, Some(Ty::Int)) => (Opcode::IMul, Ty::Int),
+			(Rule::op_div, Some(Ty::Int)) => (Opcode::IDiv, Ty::Int),
+			(Rule::op_add, Some(Ty::Float)) => (Opcode::FAdd, Ty::Float),
+			(Rule::op_sub, Some(Ty::Float)) => (Opcode::FSub, Ty::Float),
+			(Rule::op_mul, Some(Ty::Float)) => (Opcode::FMul, Ty::Float),
+			(Rule::op_div, Some(Ty::Float)) => (Opcode::FDiv, Ty::Float),
+			(Rule::op_mod, Some(Ty::Int)) => (Opcode::IMod, Ty::Int),
+			(_, None) => return self.untyped(),
+			(op, Some(ty)) => unreachable!("{op:?} assignment to {ty}"),
+		};
+
+		let current = self.load(binding, name, ty.as_ref());
+		let value = self.operand(value, &operand);
+		self.op(opcode, vec![current.clone(), current.clone(), value]);
+
+		// Properties are read into a temporary, so the result has to be written back.
+		if binding.and_then(|b| self.variable(b, name)).is_none() {
+			self.store(binding, name, current);
+		}
+	}
+
+	/// Generates each statement of a block, with the lines of the enclosing statement restored after.
+	fn body(&mut self, body: &'a [Statement]) {
+		let outer = self.span;
+		for stmt in body {
+			self.span = stmt.span();
+			self.statement(stmt);
+		}
+		self.span = outer;
+	}
+
+	fn statement(&mut self, stmt: &'a Statement) {
+		match stmt {
+			Statement::If {
+				cond,
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				let end = self.label();
+				let branches =
+					std::iter::once((cond, body)).chain(elifs.iter().map(|(c, b)| (c, b)));
+
+				for (cond, body) in branches {
+					let next = self.label();
+					let cond = self.expression(cond);
+					self.branch(Ir::JumpFalse, cond, next);
+					self.body(body);
+					self.push(Ir::Jump(end));
+					self.place(next);
+				}
+
+				if let Some(body) = else_block {
+					self.body(body);
+				}
+				self.place(end);
+			}
+
+			Statement::While { cond, body, .. } => {
+				let start = self.label();
+				let end = self.label();
+
+				self.place(start);
+				let cond = self.expression(cond);
+				self.branch(Ir::JumpFalse, cond, end);
+				self.body(body);
+				self.push(Ir::Jump(start));
+				self.place(end);
+			}
+
+			Statement::Return { value, .. } => {
+				let value = match value {
+					Some(value) => {
+						let to = self.return_type.clone();
+						self.operand(value, &to)
+					}
+					None => Value::None,
+				};
+				self.op(Opcode::Return, vec![value]);
+			}
+
+			Statement::Declaration { ty, name, .. } => {
+				let ty = self.declared(ty);
+				self.declare(stmt, name, &ty);
+			}
+
+			Statement::Definition {
+				ty, name, value, ..
+			} => {
+				let ty = self.declared(ty);
+				let name = self.declare(stmt, name, &ty);
+				let value = self.operand(value, &ty);
+				self.op(Opcode::Assign, vec![ident(&name), value]);
+			}
+
+			Statement::Assignment {
+				name,
+				indexes,
+				value,
+				..
+			} => self.assignment(stmt, name, indexes, value),
+
+			Statement::CompoundAssignment {
+				name, op, value, ..
+			} => self.compound_assignment(stmt, name, *op, value),
+
+			Statement::Expression { expr, .. } => match expr {
+				Expression::Call(..) => {
+					self.call(expr, true);
+				}
+				expr => {
+					let value = self.expression(expr);
+					self.temps.release(&value);
+				}
+			},
+
+			// Nothing else can appear in a function body.
+			_ => (),
+		}
+	}
+}
+
+/// Generates the code of a [Statement::Function] or [Statement::Event] of a script that has passed validation.
+/// Values that still can't be compiled are reported to `diagnostics`.
+pub fn generate<'a>(
+	ast: &'a Ast,
+	function: &'a Statement,
+	resolution: &Resolution<'a>,
+	hierarchy: &dyn Hierarchy,
+	calls: &Calls<'a>,
+	typing: &Typing,
+	diagnostics: &mut Diagnostics,
+) -> Code {
+	let mut generator = Generator {
+		ast,
+		resolution,
+		hierarchy,
+		calls,
+		typing,
+		diagnostics,
+		code: Code::default(),
+		labels: 0,
+		temps: Temps::default(),
+		names: HashMap::new(),
+		return_type: Ty::None,
+		span: function.span(),
+	};
+
+	match function {
+		Statement::Function {
+			return_type, body, ..
+		} => {
+			generator.return_type = return_type
+				.as_deref()
+				.map_or(Ty::None, |ty| generator.declared(ty));
+			generator.body(body);
+		}
+		Statement::Event { body, .. } => generator.body(body),
+		_ => (),
+	}
+
+	generator.code
+}
@@ -0,0 +1,185 @@
+//! Rules for converting between Papyrus types.
+//! https://www.creationkit.com/fallout4/index.php?title=Cast_Reference
+
+use super::pex::Value;
+use super::types::Ty;
+use crate::parser::ast::{Expression, Statement};
+
+/// How a conversion is compiled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lowering {
+	/// Value is moved as-is with `assign`.
+	Assign,
+	/// Value is converted with the `cast` opcode.
+	Cast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conversion {
+	/// Happens automatically, for assignments, arguments, returns and operators.
+	Implicit(Lowering),
+	/// Needs an explicit `as`.
+	Explicit(Lowering),
+	Illegal,
+}
+
+/// Knowledge of which scripts extend which, and of what they declare.
+pub trait Hierarchy {
+	/// Whether `child` is `parent` or extends it, or [None] if that can't be known.
+	fn extends(&self, child: &str, parent: &str) -> Option<bool>;
+
+	/// Type of the property `name` of `script` or a script it extends.
+	/// `Some(None)` if there is no such property, or [None] if that can't be known.
+	fn property(&self, _script: &str, _name: &str) -> Option<Option<Ty>> {
+		None
+	}
+
+	/// Function or event `name` of `script` or a script it extends, along with the name of the script declaring it,
+	/// following the same rules as [Hierarchy::property].
+	fn function(&self, _script: &str, _name: &str) -> Option<Option<(&str, &Statement)>> {
+		None
+	}
+}
+
+/// No knowledge of other scripts, every object conversion is assumed to be an upcast.
+impl Hierarchy for () {
+	fn extends(&self, _child: &str, _parent: &str) -> Option<bool> {
+		None
+	}
+}
+
+impl Conversion {
+	pub fn between(from: &Ty, to: &Ty, hierarchy: &dyn Hierarchy) -> Self {
+		use Lowering::*;
+
+		match (from, to) {
+			(from, to) if from == to => Self::Implicit(Assign),
+
+			(_, Ty::None) => Self::Illegal,
+			(Ty::None, to) if to.is_nullable() => Self::Implicit(Assign),
+			(Ty::None, Ty::String | Ty::Bool) => Self::Implicit(Cast),
+			(Ty::None, _) => Self::Illegal,
+
+			// Var can't hold arrays.
+			(Ty::Array(_), Ty::Var) | (Ty::Var, Ty::Array(_)) => Self::Illegal,
+			(_, Ty::Var) => Self::Implicit(Cast),
+			(Ty::Var, _) => Self::Explicit(Cast),
+
+			(_, Ty::String | Ty::Bool) => Self::Implicit(Cast),
+
+			(Ty::Int, Ty::Float) => Self::Implicit(Cast),
+			(Ty::Float | Ty::String | Ty::Bool, Ty::Int | Ty::Float) => Self::Explicit(Cast),
+
+			(Ty::Object(from), Ty::Object(to)) => Self::object(from, to, hierarchy),
+
+			// Only arrays of objects can be cast, following the same rules as their elements.
+			(Ty::Array(from), Ty::Array(to)) => match (from.as_ref(), to.as_ref()) {
+				(Ty::Object(from), Ty::Object(to)) => Self::object(from, to, hierarchy),
+				_ => Self::Illegal,
+			},
+
+			_ => Self::Illegal,
+		}
+	}
+
+	fn object(from: &str, to: &str, hierarchy: &dyn Hierarchy) -> Self {
+		match hierarchy.extends(from, to) {
+			Some(true) | None => Self::Implicit(Lowering::Cast),
+			Some(false) if hierarchy.extends(to, from) == Some(true) => {
+				Self::Explicit(Lowering::Cast)
+			}
+			Some(false) => Self::Illegal,
+		}
+	}
+
+	pub fn is_implicit(&self) -> bool {
+		matches!(self, Self::Implicit(_))
+	}
+
+	pub fn is_legal(&self) -> bool {
+		!matches!(self, Self::Illegal)
+	}
+
+	pub fn lowering(&self) -> Option<Lowering> {
+		match self {
+			Self::Implicit(lowering) | Self::Explicit(lowering) => Some(*lowering),
+			Self::Illegal => None,
+		}
+	}
+}
+
+/// Converts a literal at compile time, if the conversion is legal and the result is known.
+pub(crate) fn fold(value: &Expression, to: &Ty) -> Option<Expression> {
+	let folded = match (value, to) {
+		(Expression::Integer(i), Ty::Int) => Expression::Integer(*i),
+		(Expression::Integer(i), Ty::Float) => Expression::Float(*i as f64),
+		(Expression::Integer(i), Ty::Bool) => Expression::Bool(*i != 0),
+		(Expression::Integer(i), Ty::String) => Expression::String(format!("\"{i}\"")),
+
+		(Expression::Float(f), Ty::Int) => {
+			Expression::Integer(i32::try_from(f.trunc() as i64).ok()?.into())
+		}
+		(Expression::Float(f), Ty::Float) => Expression::Float(*f),
+		(Expression::Float(f), Ty::Bool) => Expression::Bool(*f != 0.0),
+		(Expression::Float(f), Ty::String) => Expression::String(format!("\"{f:.6}\"")),
+
+		(Expression::Bool(b), Ty::Int) => Expression::Integer(*b as i64),
+		(Expression::Bool(b), Ty::Float) => Expression::Float(*b as i64 as f64),
+		(Expression::Bool(b), Ty::Bool) => Expression::Bool(*b),
+		(Expression::Bool(b), Ty::String) => {
+			Expression::String(String::from(if *b { "\"True\"" } else { "\"False\"" }))
+		}
+
+		(Expression::String(s), Ty::String) => Expression::String(s.clone()),
+		(Expression::String(s), Ty::Bool) => Expression::Bool(s.len() > 2),
+
+		(Expression::None, Ty::Bool) => Expression::Bool(false),
+		(Expression::None, Ty::String) => Expression::String(String::from("\"None\"")),
+
+		_ => return None,
+	};
+
+	Some(folded)
+}
+
+/// Contents of a string literal, without its quotes and escapes.
+fn string(literal: &str) -> String {
+	let inner = literal
+		.strip_prefix('"')
+		.and_then(|s| s.strip_suffix('"'))
+		.unwrap_or(literal);
+
+	let mut out = String::with_capacity(inner.len());
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			out.push(c);
+			continue;
+		}
+
+		match chars.next() {
+			Some('n') => out.push('\n'),
+			Some('t') => out.push('\t'),
+			Some(c) => out.push(c),
+			None => out.push('\\'),
+		}
+	}
+	out
+}
+
+/// Value of a literal, or [None] if `expr` isn't known at compile time.
+pub(crate) fn constant(expr: &Expression) -> Option<Value> {
+	Some(match expr {
+		Expression::None => Value::None,
+		Expression::Bool(value) => Value::Bool(*value),
+		Expression::Integer(value) => Value::Int(i32::try_from(*value).ok()?),
+		Expression::Float(value) => Value::Float(*value as f32),
+		Expression::String(value) => Value::String(string(value)),
+		Expression::Negate(inner) => match inner.as_ref() {
+			Expression::Integer(value) => Value::Int(i32::try_from(-*value).ok()?),
+			Expression::Float(value) => Value::Float(-*value as f32),
+			_ => return None,
+		},
+		_ => return None,
+	})
+}
@@ -0,0 +1,404 @@
+//! Writes [Pex] to the binary format the game loads.
+
+use super::game::Game;
+use super::pex::*;
+use crate::diagnostics::Diagnostic;
+use indexmap::IndexSet;
+use std::collections::HashMap;
+use thiserror::Error;
+
+const MAGIC: u32 = 0xFA57C0DE;
+
+/// Something that the PEX format has no room for, as its sizes and indexes are 16 bits.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+	#[error("A string of {0} bytes is longer than the {max} a PEX can hold", max = u16::MAX)]
+	TooLong(usize),
+
+	#[error("{0} entries are more than the {max} a table of a PEX can hold", max = u16::MAX)]
+	TooMany(usize),
+
+	#[error("{0} different strings are more than the {max} the string table of a PEX can hold", max = u16::MAX)]
+	TooManyStrings(usize),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::TooLong(_) => "E1401",
+			Self::TooMany(_) => "E1402",
+			Self::TooManyStrings(_) => "E1403",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+impl Game {
+	/// Major and minor version of the PEX format.
+	fn pex_version(&self) -> (u8, u8) {
+		match self {
+			Self::Skyrim => (3, 2),
+			Self::Fallout4 => (3, 9),
+		}
+	}
+
+	fn pex_id(&self) -> u16 {
+		match self {
+			Self::Skyrim => 1,
+			Self::Fallout4 => 2,
+		}
+	}
+}
+
+/// Strings of a file, each stored once and in the order they are first written.
+/// Identifiers are matched regardless of case, like they are in Papyrus,
+/// whereas string literals and documentation keep theirs.
+#[derive(Debug, Default)]
+struct StringTable {
+	strings: IndexSet<String>,
+	/// Index of each identifier, by its lowercased form.
+	identifiers: HashMap<String, usize>,
+}
+
+impl StringTable {
+	fn identifier(&mut self, name: &str) -> usize {
+		let key = name.to_lowercase();
+		if let Some(&index) = self.identifiers.get(&key) {
+			return index;
+		}
+
+		let index = self.literal(name);
+		self.identifiers.insert(key, index);
+		index
+	}
+
+	fn literal(&mut self, value: &str) -> usize {
+		self.strings.insert_full(value.to_owned()).0
+	}
+}
+
+struct Writer {
+	out: Vec<u8>,
+	game: Game,
+	strings: StringTable,
+	/// First size or index that didn't fit, which fails the whole file.
+	error: Option<Error>,
+}
+
+macro_rules! number {
+	($($name:ident: $ty:ty),*) => {
+		$(fn $name(&mut self, value: $ty) {
+			if self.game == Game::Skyrim {
+				self.out.extend(value.to_be_bytes());
+			} else {
+				self.out.extend(value.to_le_bytes());
+			}
+		})*
+	};
+}
+
+impl Writer {
+	fn new(game: Game) -> Self {
+		Self {
+			out: vec![],
+			game,
+			strings: StringTable::default(),
+			error: None,
+		}
+	}
+
+	number!(u16: u16, u32: u32, u64: u64, i32: i32, f32: f32);
+
+	fn u8(&mut self, value: u8) {
+		self.out.push(value);
+	}
+
+	fn is_fallout4(&self) -> bool {
+		self.game == Game::Fallout4
+	}
+
+	/// Writes a size or index, recording `error` if it doesn't fit in 16 bits.
+	fn checked(&mut self, value: usize, error: fn(usize) -> Error) {
+		let value = u16::try_from(value).unwrap_or_else(|_| {
+			self.error.get_or_insert(error(value));
+			u16::MAX
+		});
+		self.u16(value);
+	}
+
+	fn len(&mut self, len: usize) {
+		self.checked(len, Error::TooMany);
+	}
+
+	/// String stored inline, rather than in the string table.
+	fn wstring(&mut self, value: &str) {
+		self.checked(value.len(), Error::TooLong);
+		self.out.extend(value.as_bytes());
+	}
+
+	/// Index of an identifier in the string table.
+	fn string(&mut self, name: &str) {
+		let index = self.strings.identifier(name);
+		self.checked(index, |index| Error::TooManyStrings(index + 1));
+	}
+
+	/// Index of a string literal or documentation in the string table.
+	fn literal(&mut self, value: &str) {
+		let index = self.strings.literal(value);
+		self.checked(index, |index| Error::TooManyStrings(index + 1));
+	}
+
+	/// Runs `write` into a fresh buffer and returns what it wrote, for data prefixed by its size.
+	fn nested(&mut self, write: impl FnOnce(&mut Self)) -> Vec<u8> {
+		let outer = std::mem::take(&mut self.out);
+		write(self);
+		std::mem::replace(&mut self.out, outer)
+	}
+
+	fn value(&mut self, value: &Value) {
+		match value {
+			Value::None => self.u8(0),
+			Value::Ident(name) => {
+				self.u8(1);
+				self.string(name);
+			}
+			Value::String(value) => {
+				self.u8(2);
+				self.literal(value);
+			}
+			Value::Int(value) => {
+				self.u8(3);
+				self.i32(*value);
+			}
+			Value::Float(value) => {
+				self.u8(4);
+				self.f32(*value);
+			}
+			Value::Bool(value) => {
+				self.u8(5);
+				self.u8(*value as u8);
+			}
+		}
+	}
+
+	fn debug_info(&mut self, debug_info: Option<&DebugInfo>) {
+		let Some(debug_info) = debug_info else {
+			self.u8(0);
+			return;
+		};
+
+		self.u8(1);
+		self.u64(debug_info.modification_time);
+		self.len(debug_info.functions.len());
+		for function in &debug_info.functions {
+			self.string(&function.object);
+			self.string(&function.state);
+			self.string(&function.function);
+			self.u8(function.kind as u8);
+			self.len(function.lines.len());
+			for &line in &function.lines {
+				self.u16(line);
+			}
+		}
+
+		if !self.is_fallout4() {
+			return;
+		}
+
+		self.len(debug_info.property_groups.len());
+		for group in &debug_info.property_groups {
+			self.string(&group.object);
+			self.string(&group.name);
+			self.literal(&group.doc);
+			self.u32(group.user_flags);
+			self.names(&group.properties);
+		}
+
+		self.len(debug_info.struct_orders.len());
+		for order in &debug_info.struct_orders {
+			self.string(&order.object);
+			self.string(&order.name);
+			self.names(&order.members);
+		}
+	}
+
+	fn names(&mut self, names: &[String]) {
+		self.len(names.len());
+		for name in names {
+			self.string(name);
+		}
+	}
+
+	fn object(&mut self, object: &Object) {
+		self.string(&object.name);
+
+		let data = self.nested(|w| {
+			w.string(&object.parent);
+			w.literal(&object.doc);
+			if w.is_fallout4() {
+				w.u8(object.is_const as u8);
+			}
+			w.u32(object.user_flags);
+			w.string(&object.auto_state);
+
+			if w.is_fallout4() {
+				w.len(object.structs.len());
+				for r#struct in &object.structs {
+					w.r#struct(r#struct);
+				}
+			}
+
+			w.len(object.variables.len());
+			for variable in &object.variables {
+				w.string(&variable.name);
+				w.string(&variable.ty);
+				w.u32(variable.user_flags);
+				w.value(&variable.value);
+				if w.is_fallout4() {
+					w.u8(variable.is_const as u8);
+				}
+			}
+
+			w.len(object.properties.len());
+			for property in &object.properties {
+				w.property(property);
+			}
+
+			w.len(object.states.len());
+			for state in &object.states {
+				w.string(&state.name);
+				w.len(state.functions.len());
+				for function in &state.functions {
+					w.string(&function.name);
+					w.function(function);
+				}
+			}
+		});
+
+		// The size includes itself.
+		self.u32(data.len() as u32 + 4);
+		self.out.extend(data);
+	}
+
+	fn r#struct(&mut self, r#struct: &Struct) {
+		self.string(&r#struct.name);
+		self.len(r#struct.members.len());
+		for member in &r#struct.members {
+			self.string(&member.name);
+			self.string(&member.ty);
+			self.u32(member.user_flags);
+			self.value(&member.value);
+			self.u8(member.is_const as u8);
+			self.literal(&member.doc);
+		}
+	}
+
+	fn property(&mut self, property: &Property) {
+		self.string(&property.name);
+		self.string(&property.ty);
+		self.literal(&property.doc);
+		self.u32(property.user_flags);
+		self.u8(property.flags());
+
+		match &property.kind {
+			PropertyKind::Auto(variable) => self.string(variable),
+			PropertyKind::Full { get, set } => {
+				for function in get.iter().chain(set) {
+					self.function(function);
+				}
+			}
+		}
+	}
+
+	fn function(&mut self, function: &Function) {
+		self.string(&function.return_type);
+		self.literal(&function.doc);
+		self.u32(function.user_flags);
+		self.u8(function.flags());
+
+		for variables in [&function.parameters, &function.locals] {
+			self.len(variables.len());
+			for (name, ty) in variables {
+				self.string(name);
+				self.string(ty);
+			}
+		}
+
+		self.len(function.instructions.len());
+		for instruction in &function.instructions {
+			self.instruction(instruction);
+		}
+	}
+
+	fn instruction(&mut self, instruction: &Instruction) {
+		let opcode = instruction.opcode;
+		self.u8(opcode.code());
+
+		let (fixed, variadic) = instruction
+			.args
+			.split_at(opcode.fixed_args().min(instruction.args.len()));
+		for arg in fixed {
+			self.value(arg);
+		}
+
+		if opcode.is_variadic() {
+			self.value(&Value::Int(variadic.len() as i32));
+			for arg in variadic {
+				self.value(arg);
+			}
+		}
+	}
+}
+
+impl Pex {
+	/// Encodes the script in the layout and byte order of [Header::game].
+	/// The same script always encodes to the same bytes.
+	/// Fails if a string or table is too large for the format.
+	pub fn encode(&self) -> Result<Vec<u8>, Error> {
+		let game = self.header.game;
+
+		// Everything after the string table is written first, so that it knows every string.
+		// Objects go before the debug info that comes ahead of them, to intern their strings first.
+		let mut body = Writer::new(game);
+		let objects = body.nested(|w| {
+			w.len(self.objects.len());
+			for object in &self.objects {
+				w.object(object);
+			}
+		});
+		body.debug_info(self.debug_info.as_ref());
+		body.len(self.user_flags.len());
+		for flag in &self.user_flags {
+			body.string(&flag.name);
+			body.u8(flag.bit);
+		}
+		body.out.extend(objects);
+
+		let mut out = Writer::new(game);
+		let (major, minor) = game.pex_version();
+		out.u32(MAGIC);
+		out.u8(major);
+		out.u8(minor);
+		out.u16(game.pex_id());
+		out.u64(self.header.compilation_time);
+		out.wstring(&self.header.source);
+		out.wstring(&self.header.user);
+		out.wstring(&self.header.machine);
+
+		out.checked(body.strings.strings.len(), Error::TooManyStrings);
+		for string in &body.strings.strings {
+			out.wstring(string);
+		}
+
+		if let Some(error) = body.error.or(out.error) {
+			return Err(error);
+		}
+		out.out.extend(body.out);
+		Ok(out.out)
+	}
+}
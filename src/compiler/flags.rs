@@ -0,0 +1,213 @@
+//! User flags, as declared by the `.flg` files of the official toolchain.
+//!
+//! ```text
+//! Flag Hidden 0
+//! {
+//!     Script
+//!     Property
+//! }
+//! ```
+
+use super::game::Game;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+	#[error("Expected {expected}, but got {got:?} at line {line}")]
+	Expected {
+		expected: &'static str,
+		got: String,
+		line: usize,
+	},
+
+	#[error("Expected {0}, but got end of input")]
+	UnexpectedEnd(&'static str),
+
+	#[error("Flag {name} has bit {bit}, but there are only 32 at line {line}")]
+	InvalidBit { name: String, bit: u32, line: usize },
+
+	#[error("Flag {name} is declared twice at line {line}")]
+	Duplicate { name: String, line: usize },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Kind of declaration a flag can be put on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+	Script,
+	Property,
+	Variable,
+	Group,
+	/// Member of a struct.
+	StructVar,
+	Function,
+}
+
+impl Target {
+	fn parse(name: &str) -> Option<Self> {
+		Some(match name.to_ascii_lowercase().as_str() {
+			"script" => Self::Script,
+			"property" => Self::Property,
+			"variable" => Self::Variable,
+			"group" => Self::Group,
+			"structvar" => Self::StructVar,
+			"function" => Self::Function,
+			_ => return None,
+		})
+	}
+}
+
+impl fmt::Display for Target {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Script => write!(f, "scripts"),
+			Self::Property => write!(f, "properties"),
+			Self::Variable => write!(f, "variables"),
+			Self::Group => write!(f, "groups"),
+			Self::StructVar => write!(f, "struct members"),
+			Self::Function => write!(f, "functions"),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flag {
+	/// As declared, although it's written to the PEX lowercased.
+	pub name: String,
+	/// Index of the bit it sets in user flag fields.
+	pub bit: u8,
+	pub targets: Vec<Target>,
+}
+
+/// Every user flag scripts can use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Flags {
+	pub flags: Vec<Flag>,
+}
+
+/// Splits a flags file into words and braces, with the line each is on.
+fn tokens(source: &str) -> impl Iterator<Item = (usize, String)> + '_ {
+	source.lines().enumerate().flat_map(|(i, line)| {
+		let code = line.split(';').next().unwrap_or_default();
+		// Braces don't need to be separated from what's around them.
+		let code = code.replace('{', " { ").replace('}', " } ");
+		code.split_whitespace()
+			.map(|token| (i + 1, token.to_owned()))
+			.collect::<Vec<_>>()
+	})
+}
+
+impl Flags {
+	/// Parses the contents of a `.flg` file.
+	pub fn parse(source: &str) -> Result<Self> {
+		let mut tokens = tokens(source);
+		let mut next = |expected: &'static str| tokens.next().ok_or(Error::UnexpectedEnd(expected));
+		let expect = |(line, got): (usize, String), expected: &'static str| match got {
+			got if got.eq_ignore_ascii_case(expected) => Ok(()),
+			got => Err(Error::Expected {
+				expected,
+				got,
+				line,
+			}),
+		};
+
+		let mut flags = Self::default();
+		while let Ok(token) = next("Flag") {
+			expect(token, "Flag")?;
+			let (line, name) = next("a flag name")?;
+			let name = name.as_str();
+
+			let (_, bit) = next("a bit index")?;
+			let bit = bit.parse::<u32>().map_err(|_| Error::Expected {
+				expected: "a bit index",
+				got: bit.clone(),
+				line,
+			})?;
+			if bit >= 32 {
+				return Err(Error::InvalidBit {
+					name: name.to_owned(),
+					bit,
+					line,
+				});
+			}
+
+			if flags.get(name).is_some() {
+				return Err(Error::Duplicate {
+					name: name.to_owned(),
+					line,
+				});
+			}
+
+			expect(next("{")?, "{")?;
+			let mut targets = vec![];
+			loop {
+				let (line, target) = next("}")?;
+				if target == "}" {
+					break;
+				}
+
+				targets.push(Target::parse(&target).ok_or(Error::Expected {
+					expected: "Script, Property, Variable, Group, StructVar or Function",
+					got: target,
+					line,
+				})?);
+			}
+
+			flags.flags.push(Flag {
+				name: name.to_owned(),
+				bit: bit as u8,
+				targets,
+			});
+		}
+
+		Ok(flags)
+	}
+
+	/// Flags of the files that ship with the official compiler of `game`.
+	pub fn builtin(game: Game) -> Self {
+		let source = match game {
+			Game::Skyrim => include_str!("flags/TESV_Papyrus_Flags.flg"),
+			Game::Fallout4 => include_str!("flags/Institute_Papyrus_Flags.flg"),
+		};
+		Self::parse(source).expect("builtin flags files are valid")
+	}
+
+	/// Finds a flag, ignoring case like the rest of Papyrus.
+	pub fn get(&self, name: &str) -> Option<&Flag> {
+		self.flags
+			.iter()
+			.find(|flag| flag.name.eq_ignore_ascii_case(name))
+	}
+
+	/// Flags a name written on a declaration stands for.
+	/// `Collapsed` is short for both `CollapsedOnRef` and `CollapsedOnBase`, unless it's declared itself.
+	pub fn resolve(&self, name: &str) -> Vec<&Flag> {
+		match self.get(name) {
+			Some(flag) => vec![flag],
+			None if name.eq_ignore_ascii_case("collapsed") => ["CollapsedOnRef", "CollapsedOnBase"]
+				.into_iter()
+				.filter_map(|name| self.get(name))
+				.collect(),
+			None => vec![],
+		}
+	}
+
+	/// Bits set by those of `names` that can be put on `target`.
+	pub fn mask_for<'n>(&self, names: impl IntoIterator<Item = &'n String>, target: Target) -> u32 {
+		names
+			.into_iter()
+			.flat_map(|name| self.resolve(name))
+			.filter(|flag| flag.targets.contains(&target))
+			.fold(0, |mask, flag| mask | 1 << flag.bit)
+	}
+
+	/// Bits set by `names` on a declaration, skipping those that aren't declared.
+	pub fn mask<'n>(&self, names: impl IntoIterator<Item = &'n String>) -> u32 {
+		names
+			.into_iter()
+			.flat_map(|name| self.resolve(name))
+			.fold(0, |mask, flag| mask | 1 << flag.bit)
+	}
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+/// Which game a script is compiled for, as the language and bytecode differ slightly between them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Game {
+	Skyrim,
+	#[default]
+	Fallout4,
+}
+
+impl Game {
+	/// Whether the game supports `Struct` declarations and `new` structs.
+	pub fn has_structs(&self) -> bool {
+		matches!(self, Self::Fallout4)
+	}
+
+	/// Whether script names can have namespaces, as in `ScriptName Namespace:Name`.
+	pub fn has_namespaces(&self) -> bool {
+		matches!(self, Self::Fallout4)
+	}
+}
+
+impl fmt::Display for Game {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Skyrim => write!(f, "Skyrim"),
+			Self::Fallout4 => write!(f, "Fallout 4"),
+		}
+	}
+}
@@ -0,0 +1,499 @@
+//! Lowers the declarations of a script into the tables of a [Pex].
+
+use super::codegen::{generate, NONE_VAR};
+use super::conversion::{constant, Hierarchy};
+use super::flags::{Flags, Target};
+use super::game::Game;
+use super::options::{CompileOptions, Metadata};
+use super::passes::{calls::Calls, resolve::Resolution, typecheck::Typing};
+use super::pex::*;
+use super::types::Ty;
+use crate::diagnostics::Diagnostics;
+use crate::parser::ast::*;
+
+/// Name of a type as it is written to the PEX, with primitive types spelled consistently.
+fn type_name(ty: &str) -> String {
+	Ty::parse(ty).to_string()
+}
+
+fn return_type(ty: Option<&Type>) -> String {
+	ty.map_or_else(|| "None".to_owned(), |ty| type_name(ty))
+}
+
+/// Value a variable of type `ty` starts with, where Int literals initialize Floats.
+fn initial(ty: &str, value: Option<&Expression>) -> Value {
+	match value.and_then(constant) {
+		Some(Value::Int(value)) if Ty::parse(ty) == Ty::Float => Value::Float(value as f32),
+		Some(value) => value,
+		None => Value::None,
+	}
+}
+
+fn parameters(parameters: &[Parameter]) -> Vec<(String, String)> {
+	parameters
+		.iter()
+		.map(|Parameter(ty, name, _)| (name.clone(), type_name(ty)))
+		.collect()
+}
+
+/// Declaration of a function, without its code.
+pub(crate) fn function(stmt: &Statement) -> Option<Function> {
+	let (name, return_type, params, is_global, is_native) = match stmt {
+		Statement::Function {
+			return_type: ty,
+			name,
+			parameters,
+			is_global,
+			..
+		} => (
+			name,
+			return_type(ty.as_ref()),
+			parameters,
+			*is_global,
+			false,
+		),
+		Statement::NativeFunction {
+			return_type: ty,
+			name,
+			parameters,
+			is_global,
+			..
+		} => (name, return_type(ty.as_ref()), parameters, *is_global, true),
+		Statement::Event {
+			name, parameters, ..
+		} => (name, return_type(None), parameters, false, false),
+		_ => return None,
+	};
+
+	Some(Function {
+		name: name.clone(),
+		return_type,
+		doc: String::new(),
+		user_flags: 0,
+		is_global,
+		is_native,
+		parameters: parameters(params),
+		locals: vec![],
+		instructions: vec![],
+	})
+}
+
+/// Variable holding the current state, in scripts where the compiler generates [state_functions].
+const STATE_VAR: &str = "::State";
+
+/// `GetState` and `GotoState`, which Skyrim expects every script without a parent to define.
+fn state_functions() -> [Function; 2] {
+	let ident = |name: &str| Value::Ident(name.to_owned());
+	let none_var = vec![(NONE_VAR.to_owned(), "None".to_owned())];
+	let event = |name: &str| {
+		Instruction::new(
+			Opcode::CallMethod,
+			vec![ident(name), ident("self"), ident(NONE_VAR)],
+		)
+	};
+
+	let get_state = Function {
+		name: "GetState".to_owned(),
+		return_type: "String".to_owned(),
+		doc: "Function that returns the current state".to_owned(),
+		user_flags: 0,
+		is_global: false,
+		is_native: false,
+		parameters: vec![],
+		locals: vec![],
+		instructions: vec![Instruction::new(Opcode::Return, vec![ident(STATE_VAR)])],
+	};
+
+	let goto_state = Function {
+		name: "GotoState".to_owned(),
+		return_type: "None".to_owned(),
+		doc: "Function that switches this object to the specified state".to_owned(),
+		user_flags: 0,
+		is_global: false,
+		is_native: false,
+		parameters: vec![("newState".to_owned(), "String".to_owned())],
+		locals: none_var,
+		instructions: vec![
+			event("onEndState"),
+			Instruction::new(Opcode::Assign, vec![ident(STATE_VAR), ident("newState")]),
+			event("onBeginState"),
+		],
+	};
+
+	[get_state, goto_state]
+}
+
+/// Every flag the compiler knew about, named the way the official compiler writes them.
+fn user_flags(flags: &Flags) -> Vec<UserFlag> {
+	flags
+		.flags
+		.iter()
+		.map(|flag| UserFlag {
+			name: flag.name.to_ascii_lowercase(),
+			bit: flag.bit,
+		})
+		.collect()
+}
+
+/// Header of the PEX, as given by `metadata`.
+fn header(ast: &Ast, game: Game, metadata: &Metadata) -> Header {
+	Header {
+		game,
+		compilation_time: metadata.compilation_time.unwrap_or(0),
+		// Namespaces are folders in Fallout 4.
+		source: metadata
+			.source
+			.clone()
+			.unwrap_or_else(|| format!("{}.psc", ast.script_info.script_name.replace(':', "\\"))),
+		user: metadata.user.clone().unwrap_or_default(),
+		machine: metadata.machine.clone().unwrap_or_default(),
+	}
+}
+
+fn is_property(stmt: &Statement) -> bool {
+	matches!(
+		stmt,
+		Statement::PropertyFull { .. }
+			| Statement::PropertyAuto { .. }
+			| Statement::PropertyAutoConst { .. }
+	)
+}
+
+/// Properties of each group, with those outside of any group in an unnamed one that comes first.
+fn property_groups(ast: &Ast, flags: &Flags) -> Vec<PropertyGroup> {
+	let object = &ast.script_info.script_name;
+	let group = |name: &str, user_flags: u32, properties: &[Statement]| PropertyGroup {
+		object: object.clone(),
+		name: name.to_owned(),
+		doc: String::new(),
+		user_flags,
+		properties: properties
+			.iter()
+			.filter(|stmt| is_property(stmt))
+			.filter_map(|stmt| stmt.name().map(str::to_owned))
+			.collect(),
+	};
+
+	let named = ast.statements.iter().filter_map(|stmt| match stmt {
+		Statement::Group {
+			name,
+			properties,
+			flags: names,
+			..
+		} => Some(group(name, flags.mask(names), properties)),
+		_ => None,
+	});
+
+	std::iter::once(group("", 0, &ast.statements))
+		.filter(|group| !group.properties.is_empty())
+		.chain(named)
+		.collect()
+}
+
+fn struct_orders(ast: &Ast) -> Vec<StructOrder> {
+	ast.statements
+		.iter()
+		.filter_map(|stmt| match stmt {
+			Statement::Struct { name, fields, .. } => Some(StructOrder {
+				object: ast.script_info.script_name.clone(),
+				name: name.clone(),
+				members: fields
+					.iter()
+					.map(|Field(_, name, ..)| name.clone())
+					.collect(),
+			}),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Puts the debug info of functions in the order of the tables they are in, property accessors first.
+/// Assembly listings are read back in that order.
+fn sort_debug_functions(object: &Object, functions: &mut [DebugFunction]) {
+	let accessors = object.properties.iter().flat_map(|property| {
+		[FunctionKind::Getter, FunctionKind::Setter].map(|kind| ("", property.name.as_str(), kind))
+	});
+	let functions_of_states = object.states.iter().flat_map(|state| {
+		state.functions.iter().map(|function| {
+			(
+				state.name.as_str(),
+				function.name.as_str(),
+				FunctionKind::Normal,
+			)
+		})
+	});
+	let order = accessors.chain(functions_of_states).collect::<Vec<_>>();
+
+	functions.sort_by_key(|function| {
+		order.iter().position(|&(state, name, kind)| {
+			function.state == state && function.function == name && function.kind == kind
+		})
+	});
+}
+
+/// Builds the object of a script that has passed validation, with debug info if `options` ask for it.
+/// Fails with the code that still can't be compiled.
+pub(crate) fn lower<'a>(
+	ast: &'a Ast,
+	options: &CompileOptions,
+	flags: &Flags,
+	resolution: &Resolution<'a>,
+	hierarchy: &dyn Hierarchy,
+	calls: &Calls<'a>,
+	typing: &Typing,
+) -> Result<Pex, Diagnostics> {
+	let mut diagnostics = Diagnostics::new();
+	let game = options.game;
+	let info = &ast.script_info;
+	let mut lines = vec![];
+	let debug_function = |state: &str, function: &str, kind, lines: Vec<usize>| DebugFunction {
+		object: info.script_name.clone(),
+		state: state.to_owned(),
+		function: function.to_owned(),
+		kind,
+		lines: lines
+			.into_iter()
+			.map(|line| line.min(u16::MAX as usize) as u16)
+			.collect(),
+	};
+
+	// Accessors of properties are named after their property in the debug info.
+	let mut compile = |lines: &mut Vec<DebugFunction>,
+	                   stmt: &'a Statement,
+	                   state: &str,
+	                   accessor: Option<(&str, FunctionKind)>| {
+		let mut function = function(stmt)?;
+		function.user_flags = flags.mask(stmt.flags());
+		if !function.is_native {
+			let code = generate(
+				ast,
+				stmt,
+				resolution,
+				hierarchy,
+				calls,
+				typing,
+				&mut diagnostics,
+			);
+			function.instructions = code.instructions();
+			let (name, kind) = accessor.unwrap_or((&function.name, FunctionKind::Normal));
+			lines.push(debug_function(state, name, kind, code.instruction_lines()));
+			function.locals = code.locals;
+		}
+		Some(function)
+	};
+
+	let mut object = Object {
+		name: info.script_name.clone(),
+		parent: info.extended_type.clone().unwrap_or_default(),
+		doc: String::new(),
+		is_const: info.is_const,
+		user_flags: flags.mask(&info.flags),
+		auto_state: String::new(),
+		structs: vec![],
+		variables: vec![],
+		properties: vec![],
+		states: vec![],
+	};
+
+	let mut empty = State {
+		name: String::new(),
+		functions: vec![],
+	};
+
+	// Properties of groups are declared like any other.
+	let statements = ast.statements.iter().flat_map(|stmt| match stmt {
+		Statement::Group { properties, .. } => properties.iter().collect(),
+		stmt => vec![stmt],
+	});
+
+	for stmt in statements {
+		match stmt {
+			Statement::Declaration {
+				ty, name, is_const, ..
+			} => object.variables.push(Variable {
+				name: name.clone(),
+				ty: type_name(ty),
+				user_flags: flags.mask(stmt.flags()),
+				value: Value::None,
+				is_const: *is_const,
+			}),
+			Statement::Definition {
+				ty,
+				name,
+				value,
+				is_const,
+				..
+			} => object.variables.push(Variable {
+				name: name.clone(),
+				ty: type_name(ty),
+				user_flags: flags.mask(stmt.flags()),
+				value: initial(ty, Some(value)),
+				is_const: *is_const,
+			}),
+
+			// Auto properties read and write a hidden variable, which gets those flags that apply to variables.
+			Statement::PropertyAuto {
+				ty,
+				name,
+				value,
+				is_const,
+				flags: names,
+				..
+			} => {
+				let variable = format!("::{name}_var");
+				object.variables.push(Variable {
+					name: variable.clone(),
+					ty: type_name(ty),
+					user_flags: flags.mask_for(names, Target::Variable),
+					value: initial(ty, value.as_ref()),
+					is_const: *is_const,
+				});
+				object.properties.push(Property {
+					name: name.clone(),
+					ty: type_name(ty),
+					doc: String::new(),
+					user_flags: flags.mask_for(names, Target::Property),
+					kind: PropertyKind::Auto(variable),
+				});
+			}
+
+			// AutoReadOnly properties have a getter that returns their value, and nothing to set it with.
+			Statement::PropertyAutoConst {
+				ty,
+				name,
+				value,
+				flags: names,
+				span,
+			} => {
+				let get = Function {
+					name: String::new(),
+					return_type: type_name(ty),
+					doc: String::new(),
+					user_flags: 0,
+					is_global: false,
+					is_native: false,
+					parameters: vec![],
+					locals: vec![],
+					instructions: vec![Instruction::new(
+						Opcode::Return,
+						vec![initial(ty, Some(value))],
+					)],
+				};
+				lines.push(debug_function(
+					"",
+					name,
+					FunctionKind::Getter,
+					vec![span.line],
+				));
+
+				object.properties.push(Property {
+					name: name.clone(),
+					ty: type_name(ty),
+					doc: String::new(),
+					user_flags: flags.mask_for(names, Target::Property),
+					kind: PropertyKind::Full {
+						get: Some(Box::new(get)),
+						set: None,
+					},
+				});
+			}
+
+			Statement::PropertyFull {
+				ty,
+				name,
+				functions,
+				flags: names,
+				..
+			} => {
+				let (mut get, mut set) = (None, None);
+				for accessor in std::iter::once(&functions.0).chain(&functions.1) {
+					let (slot, kind) = match accessor.name() {
+						Some(n) if n.eq_ignore_ascii_case("Get") => {
+							(&mut get, FunctionKind::Getter)
+						}
+						_ => (&mut set, FunctionKind::Setter),
+					};
+					*slot = compile(&mut lines, accessor, "", Some((name, kind))).map(Box::new);
+				}
+
+				object.properties.push(Property {
+					name: name.clone(),
+					ty: type_name(ty),
+					doc: String::new(),
+					user_flags: flags.mask_for(names, Target::Property),
+					kind: PropertyKind::Full { get, set },
+				});
+			}
+
+			Statement::Struct {
+				name,
+				fields,
+				flags: names,
+				..
+			} => object.structs.push(Struct {
+				name: name.clone(),
+				members: fields
+					.iter()
+					.map(|Field(ty, name, value, is_const, own)| StructMember {
+						name: name.clone(),
+						ty: type_name(ty),
+						user_flags: flags.mask(names.iter().chain(own)),
+						value: initial(ty, value.as_ref()),
+						is_const: *is_const,
+						doc: String::new(),
+					})
+					.collect(),
+			}),
+			Statement::State {
+				auto, name, body, ..
+			} => {
+				if *auto {
+					object.auto_state = name.clone();
+				}
+
+				object.states.push(State {
+					name: name.clone(),
+					functions: body
+						.iter()
+						.filter_map(|stmt| compile(&mut lines, stmt, name, None))
+						.collect(),
+				});
+			}
+			stmt => empty.functions.extend(compile(&mut lines, stmt, "", None)),
+		}
+	}
+
+	if game == Game::Skyrim && info.extended_type.is_none() {
+		object.variables.push(Variable {
+			name: STATE_VAR.to_owned(),
+			ty: "String".to_owned(),
+			user_flags: 0,
+			value: Value::None,
+			is_const: false,
+		});
+		empty.functions.extend(state_functions());
+	}
+
+	// The empty state always comes first, even when it has no functions.
+	object.states.insert(0, empty);
+	sort_debug_functions(&object, &mut lines);
+
+	let header = header(ast, game, &options.metadata);
+	let debug_info = options.debug_info.then(|| DebugInfo {
+		// Scripts are compiled from memory, so the time they were last changed isn't known.
+		modification_time: header.compilation_time,
+		functions: lines,
+		property_groups: property_groups(ast, flags),
+		struct_orders: struct_orders(ast),
+	});
+
+	if diagnostics.has_errors() {
+		return Err(diagnostics);
+	}
+	Ok(Pex {
+		header,
+		debug_info,
+		user_flags: user_flags(flags),
+		objects: vec![object],
+	})
+}
@@ -1,79 +1,171 @@
+use crate::diagnostics::{Diagnostic, Diagnostics};
 use crate::parser::ast::*;
-
-// mod encode;
+use crate::project::{relative_path, Project};
+use std::path::PathBuf;
+
+pub mod assembly;
+pub(crate) mod conversion;
+pub mod codegen;
+mod encode;
+pub mod flags;
+mod game;
+mod lower;
+pub mod options;
 mod passes;
-
-trait Pass<Userdata> {
-	fn statement(stmt: &Statement, userdata: &mut Userdata) {}
-	fn expression(expr: &Expression, userdata: &mut Userdata) {}
-
-	fn enter_scope(userdata: &mut Userdata) {}
-	fn exit_scope(userdata: &mut Userdata) {}
+pub mod pex;
+mod types;
+
+pub use conversion::{Conversion, Hierarchy, Lowering};
+pub use encode::Error as EncodeError;
+pub use flags::Flags;
+pub use game::Game;
+pub use options::{CompileOptions, CompileOutput, Metadata, OptLevel};
+pub use passes::calls::{match_arguments, resolve_calls, ArgumentValue, Call, Calls};
+pub use passes::context::check_context;
+pub use passes::dialect::{check_dialect, check_opcodes};
+pub use passes::effects::check_effects;
+pub use passes::flow::check_flow;
+pub use passes::inheritance::check_inheritance;
+pub use passes::initial_values::check_initial_values;
+pub use passes::placement::check_placement;
+pub use passes::resolve::{resolve, Binding, Resolution, Symbols};
+pub use passes::states::check_states;
+pub use passes::typecheck::{typecheck, Typing};
+pub use passes::user_flags::check_flags;
+pub use types::Ty;
+
+/// Runs every pass that only needs this one script, keeping what later stages need.
+fn analyze<'a>(
+	ast: &'a Ast,
+	game: Game,
+	flags: &Flags,
+	hierarchy: &'a dyn Hierarchy,
+	diagnostics: &mut Diagnostics,
+) -> (Resolution<'a>, Calls<'a>, Typing) {
+	check_placement(ast, diagnostics);
+	check_dialect(ast, game, diagnostics);
+	check_flags(ast, flags, diagnostics);
+	check_flow(ast, diagnostics);
+	check_effects(ast, diagnostics);
+	let resolution = resolve(ast, diagnostics);
+	check_context(ast, &resolution, diagnostics);
+	check_initial_values(ast, diagnostics);
+	let typing = typecheck(ast, &resolution, hierarchy, diagnostics);
+	let calls = resolve_calls(ast, &resolution, hierarchy, &typing, diagnostics);
+	check_opcodes(ast, game, &typing, diagnostics);
+
+	(resolution, calls, typing)
 }
 
-trait AstWalk<Userdata, P: Pass<Userdata>> {
-	fn walk(&self, userdata: &mut Userdata);
+/// Runs every pass that only needs this one script, reporting anything wrong with it.
+/// Checks against parent scripts are done by [check_inheritance] and [check_states].
+/// User flags are checked against the [builtin](Flags::builtin) ones of `game`.
+pub fn validate(ast: &Ast, game: Game) -> Diagnostics {
+	let mut diagnostics = Diagnostics::new();
+	analyze(ast, game, &Flags::builtin(game), &(), &mut diagnostics);
+	diagnostics
 }
 
-impl<Userdata, P: Pass<Userdata>, T: AstWalk<Userdata, P>> AstWalk<Userdata, P> for Vec<T> {
-	fn walk(&self, userdata: &mut Userdata) {
-		P::enter_scope(userdata);
-		self.iter().for_each(|x| x.walk(userdata));
-		P::exit_scope(userdata);
+/// Checks `ast` against the scripts it extends, which are loaded only if there are import directories.
+/// Returns the project, along with the files that were loaded.
+fn check_project(
+	ast: &Ast,
+	imports: &[PathBuf],
+	diagnostics: &mut Diagnostics,
+) -> (Project, Vec<PathBuf>) {
+	let name = &ast.script_info.script_name;
+	let mut project = Project::new(imports);
+	if let Err(why) = project.insert(relative_path(name), ast.clone()) {
+		diagnostics.push(options::Error::from(why).into());
+		return (project, vec![]);
 	}
-}
-
-impl<Userdata, P: Pass<Userdata>> AstWalk<Userdata, P> for Statement {
-	fn walk(&self, userdata: &mut Userdata) {
-		match self {
-			Statement::If {
-				body,
-				elifs,
-				else_block,
-				..
-			} => {
-				AstWalk::<Userdata, P>::walk(body, userdata);
-
-				for elif in elifs {
-					P::enter_scope(userdata);
-					AstWalk::<Userdata, P>::walk(&elif.1, userdata);
-					P::exit_scope(userdata);
-				}
-
-				if let Some(body) = else_block {
-					AstWalk::<Userdata, P>::walk(body, userdata);
-				}
-			}
-
-			Statement::Function { body, .. } => AstWalk::<Userdata, P>::walk(body, userdata),
-			Statement::While { body, .. } => AstWalk::<Userdata, P>::walk(body, userdata),
-			Statement::Event { body, .. } => AstWalk::<Userdata, P>::walk(body, userdata),
-			Statement::Group { properties, .. } => AstWalk::<Userdata, P>::walk(properties, userdata),
 
-			Statement::PropertyFull { functions, .. } => {
-				AstWalk::<Userdata, P>::walk(functions.0.as_ref(), userdata);
-
-				if let Some(body) = functions.1.as_ref() {
-					AstWalk::<Userdata, P>::walk(body.as_ref(), userdata);
-				}
-			}
-
-			_ => (),
-		};
-
-		P::statement(self, userdata);
+	if !imports.is_empty() {
+		if let Err(why) = project.load(name) {
+			diagnostics.push(options::Error::from(why).into());
+		}
 	}
+	check_inheritance(&project, name, diagnostics);
+	check_states(&project, name, diagnostics);
+
+	// The script itself was inserted first.
+	let dependencies = project
+		.scripts()
+		.skip(1)
+		.map(|script| script.path.clone())
+		.collect();
+	(project, dependencies)
 }
 
-pub fn compile(ast: &Ast) -> Vec<u8> {
-	use indexmap::IndexSet;
-	use passes::{validate::{Validate, State as ValidationState}, string_table::StringTable};
-
-	let mut state = ValidationState::default();
-	AstWalk::<_, Validate>::walk(&ast.statements, &mut state);
+/// Compiles a script to PEX, failing with every diagnostic if there were any errors.
+pub fn compile(ast: &Ast, options: &CompileOptions) -> Result<CompileOutput, Diagnostics> {
+	let mut diagnostics = Diagnostics::new();
+	let flags = options.flags().unwrap_or_else(|why| {
+		diagnostics.push(Diagnostic::from(why));
+		Flags::builtin(options.game)
+	});
+
+	let (project, dependencies) = check_project(ast, &options.imports, &mut diagnostics);
+
+	let (resolution, calls, typing) =
+		analyze(ast, options.game, &flags, &project, &mut diagnostics);
+	// Values that can't be typed are only reported when there's nothing more fundamental wrong.
+	if !diagnostics.has_errors() {
+		diagnostics.extend(typing.unknown().iter().cloned());
+	}
+	if diagnostics.has_errors() {
+		return Err(diagnostics);
+	}
 
-	let mut strings = IndexSet::new();
-	AstWalk::<_, StringTable>::walk(&ast.statements, &mut strings);
+	let lowered = match options.optimization {
+		OptLevel::None => {
+			lower::lower(ast, options, &flags, &resolution, &project, &calls, &typing)
+		}
+		// Lowering needs the analysis of the tree it lowers, so the optimized one is analyzed again.
+		OptLevel::Full => {
+			let mut ast = ast.clone();
+			crate::optimize(&mut ast);
+			let mut reanalysis = Diagnostics::new();
+			let (resolution, calls, typing) =
+				analyze(&ast, options.game, &flags, &project, &mut reanalysis);
+			// Warnings were already given for the tree as written, but the optimizer may have broken it.
+			diagnostics.extend(reanalysis.errors().cloned());
+			if diagnostics.has_errors() {
+				return Err(diagnostics);
+			}
 
-	vec![]
-}
\ No newline at end of file
+			lower::lower(
+				&ast,
+				options,
+				&flags,
+				&resolution,
+				&project,
+				&calls,
+				&typing,
+			)
+		}
+	};
+	let pex = match lowered {
+		Ok(pex) => pex,
+		Err(errors) => {
+			diagnostics.extend(errors);
+			return Err(diagnostics);
+		}
+	};
+
+	let bytes = match pex.encode() {
+		Ok(bytes) => bytes,
+		Err(why) => {
+			diagnostics.push(Diagnostic::from(why));
+			return Err(diagnostics);
+		}
+	};
+
+	Ok(CompileOutput {
+		path: options.output_path(&ast.script_info.script_name),
+		bytes,
+		assembly: options.assembly.then(|| assembly::emit(&pex)),
+		dependencies,
+		diagnostics,
+	})
+}
@@ -0,0 +1,151 @@
+//! Settings of a compilation and what it produces.
+
+use super::flags::{self, Flags};
+use super::game::Game;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Failed to read flags file {}: {source}", path.display())]
+	Io {
+		path: PathBuf,
+		source: std::io::Error,
+	},
+
+	#[error("Invalid flags file {}: {source}", path.display())]
+	Flags { path: PathBuf, source: flags::Error },
+
+	#[error("{0}")]
+	Project(#[from] crate::project::Error),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Io { .. } => "E1201",
+			Self::Flags { .. } => "E1202",
+			Self::Project(_) => "E1203",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// How much the optimizer may rewrite a script before it is compiled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OptLevel {
+	/// Compiles the code as written.
+	#[default]
+	None,
+	/// Folds constant expressions.
+	Full,
+}
+
+/// What the PEX header records about a build. Whatever is left out is written as 0 or blank.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+	/// Seconds since the Unix epoch.
+	pub compilation_time: Option<u64>,
+	/// Path of the source file, otherwise derived from the script name.
+	pub source: Option<String>,
+	pub user: Option<String>,
+	pub machine: Option<String>,
+}
+
+impl Metadata {
+	/// Takes the time, user and machine from the environment, like the official compiler.
+	/// Setting `SOURCE_DATE_EPOCH` fixes the time, and leaves out the user and machine as they vary between builds.
+	pub fn from_env() -> Self {
+		let epoch = std::env::var("SOURCE_DATE_EPOCH")
+			.ok()
+			.and_then(|epoch| epoch.trim().parse::<u64>().ok());
+		let env = |names: [&str; 2]| match epoch {
+			Some(_) => None,
+			None => names.into_iter().find_map(|name| std::env::var(name).ok()),
+		};
+
+		Self {
+			compilation_time: Some(epoch.unwrap_or_else(|| {
+				SystemTime::now()
+					.duration_since(UNIX_EPOCH)
+					.map_or(0, |time| time.as_secs())
+			})),
+			source: None,
+			user: env(["USER", "USERNAME"]),
+			machine: env(["HOSTNAME", "COMPUTERNAME"]),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+	pub game: Game,
+	/// Whether to write debug info, which gives line numbers to stack traces.
+	pub debug_info: bool,
+	pub optimization: OptLevel,
+	/// `.flg` file declaring user flags, instead of the [builtin](Flags::builtin) ones of the game.
+	pub flags_file: Option<PathBuf>,
+	/// Directories parent and referenced scripts are loaded from, where later directories take priority.
+	/// Without any, the script is compiled on its own.
+	pub imports: Vec<PathBuf>,
+	/// Whether to also produce an assembly listing.
+	pub assembly: bool,
+	/// Directory compiled scripts go in, under the folders of their namespace.
+	pub output_dir: PathBuf,
+	/// What goes in the header, which [CompileOptions::new] takes from the environment.
+	pub metadata: Metadata,
+}
+
+impl CompileOptions {
+	pub fn new(game: Game) -> Self {
+		Self {
+			game,
+			metadata: Metadata::from_env(),
+			..Default::default()
+		}
+	}
+
+	/// Where the compiled script named `name` is written, `Namespace:Name` becoming `Namespace/Name.pex`.
+	pub fn output_path(&self, name: &str) -> PathBuf {
+		let mut path = self.output_dir.clone();
+		path.extend(name.split(':'));
+		path.set_extension("pex");
+		path
+	}
+
+	/// Loads the flags file, if there is one.
+	pub(crate) fn flags(&self) -> Result<Flags, Error> {
+		let Some(path) = &self.flags_file else {
+			return Ok(Flags::builtin(self.game));
+		};
+
+		let source = std::fs::read_to_string(path).map_err(|source| Error::Io {
+			path: path.clone(),
+			source,
+		})?;
+		Flags::parse(&source).map_err(|source| Error::Flags {
+			path: path.clone(),
+			source,
+		})
+	}
+}
+
+#[derive(Debug)]
+pub struct CompileOutput {
+	/// Path from [CompileOptions::output_path].
+	pub path: PathBuf,
+	pub bytes: Vec<u8>,
+	/// Listing of the bytecode, if [CompileOptions::assembly] is set.
+	pub assembly: Option<String>,
+	/// Source files of every script loaded from the import directories, which the output is stale without.
+	pub dependencies: Vec<PathBuf>,
+	/// Warnings, as errors prevent any output.
+	pub diagnostics: Diagnostics,
+}
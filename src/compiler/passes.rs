@@ -1,4 +1,14 @@
 use super::*;
 
-pub(crate) mod validate;
-pub(crate) mod string_table;
\ No newline at end of file
+pub(crate) mod calls;
+pub(crate) mod context;
+pub(crate) mod dialect;
+pub(crate) mod effects;
+pub(crate) mod flow;
+pub(crate) mod inheritance;
+pub(crate) mod initial_values;
+pub(crate) mod placement;
+pub(crate) mod resolve;
+pub(crate) mod states;
+pub(crate) mod typecheck;
+pub(crate) mod user_flags;
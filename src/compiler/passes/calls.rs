@@ -0,0 +1,293 @@
+use super::context::is_global;
+use super::resolve::{Binding, Resolution};
+use super::typecheck::Typing;
+use super::*;
+use crate::compiler::{Hierarchy, Ty};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{function} takes {expected} arguments, but {got} were given")]
+	TooMany {
+		function: String,
+		expected: usize,
+		got: usize,
+	},
+
+	#[error("{function} is missing a value for parameter {name}")]
+	Missing { function: String, name: String },
+
+	#[error("{function} has no parameter {name}")]
+	NoParameter { function: String, name: String },
+
+	#[error("positional argument given after named arguments in call to {0}")]
+	PositionalAfterNamed(String),
+
+	#[error("parameter {name} of {function} is given multiple times")]
+	Duplicate { function: String, name: String },
+
+	#[error("{name} could be a global function of any of {}", .scripts.join(", "))]
+	Ambiguous { name: String, scripts: Vec<String> },
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::TooMany { .. } => "E0901",
+			Self::Missing { .. } => "E0902",
+			Self::NoParameter { .. } => "E0903",
+			Self::PositionalAfterNamed(_) => "E0904",
+			Self::Duplicate { .. } => "E0905",
+			Self::Ambiguous { .. } => "E0906",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Value passed for a single parameter.
+#[derive(Debug, Clone, Copy)]
+pub enum ArgumentValue<'a> {
+	/// Given at the call site, either by position or by name.
+	Given(&'a Expression),
+	/// Left out, so the default value of the parameter is used.
+	Default(&'a Expression),
+}
+
+impl<'a> ArgumentValue<'a> {
+	pub fn expression(&self) -> &'a Expression {
+		match self {
+			Self::Given(expr) | Self::Default(expr) => expr,
+		}
+	}
+}
+
+/// Arguments of a call, in the order of the parameters of the function being called.
+#[derive(Debug, Clone)]
+pub struct Call<'a> {
+	/// [Statement::Function], [Statement::NativeFunction] or [Statement::Event] being called.
+	pub function: &'a Statement,
+	/// Script declaring [Call::function], if it isn't this one.
+	pub script: Option<&'a str>,
+	/// One entry per parameter, [None] where no value was given and there is no default.
+	pub arguments: Vec<Option<ArgumentValue<'a>>>,
+}
+
+fn signature(function: &Statement) -> Option<(&String, &[Parameter])> {
+	match function {
+		Statement::Function {
+			name, parameters, ..
+		}
+		| Statement::NativeFunction {
+			name, parameters, ..
+		}
+		| Statement::Event {
+			name, parameters, ..
+		} => Some((name, parameters)),
+		_ => None,
+	}
+}
+
+/// Maps the arguments of a call onto the parameters of `function`, filling in defaults where they are left out.
+/// Returns [None] if `function` isn't something that can be called.
+pub fn match_arguments<'a>(
+	function: &'a Statement,
+	args: &'a [Argument],
+	errors: &mut Vec<Error>,
+) -> Option<Call<'a>> {
+	let (name, parameters) = signature(function)?;
+	let mut given: Vec<Option<&'a Expression>> = vec![None; parameters.len()];
+	let mut named = false;
+
+	for (i, arg) in args.iter().enumerate() {
+		let index = match arg {
+			Argument::Anonymous(_) if named => {
+				errors.push(Error::PositionalAfterNamed(name.clone()));
+				continue;
+			}
+			Argument::Anonymous(_) if i >= parameters.len() => {
+				errors.push(Error::TooMany {
+					function: name.clone(),
+					expected: parameters.len(),
+					got: args.len(),
+				});
+				break;
+			}
+			Argument::Anonymous(_) => i,
+			Argument::Named(arg, _) => {
+				named = true;
+				match parameters
+					.iter()
+					.position(|p| p.1.eq_ignore_ascii_case(arg))
+				{
+					Some(index) => index,
+					None => {
+						errors.push(Error::NoParameter {
+							function: name.clone(),
+							name: arg.clone(),
+						});
+						continue;
+					}
+				}
+			}
+		};
+
+		if given[index].replace(arg.value()).is_some() {
+			errors.push(Error::Duplicate {
+				function: name.clone(),
+				name: parameters[index].1.clone(),
+			});
+		}
+	}
+
+	let arguments = parameters
+		.iter()
+		.zip(given)
+		.map(|(param, given)| match (given, &param.2) {
+			(Some(value), _) => Some(ArgumentValue::Given(value)),
+			(None, Some(default)) => Some(ArgumentValue::Default(default)),
+			(None, None) => {
+				errors.push(Error::Missing {
+					function: name.clone(),
+					name: param.1.clone(),
+				});
+				None
+			}
+		})
+		.collect();
+
+	Some(Call {
+		function,
+		script: None,
+		arguments,
+	})
+}
+
+/// Side table produced by [resolve_calls], mapping [Expression::Call]s to their normalized arguments.
+#[derive(Debug, Default)]
+pub struct Calls<'a> {
+	calls: HashMap<*const Expression, Call<'a>>,
+}
+
+impl<'a> Calls<'a> {
+	/// Arguments of a call to a function whose declaration is known.
+	pub fn get(&self, call: &Expression) -> Option<&Call<'a>> {
+		self.calls.get(&(call as *const _))
+	}
+}
+
+/// Return type of GetState or GotoState, which every script has without declaring them.
+pub(crate) fn state_function(target: &Expression) -> Option<Ty> {
+	let (Expression::Ident(name) | Expression::DotIndex(_, name)) = target else {
+		return None;
+	};
+	match name.to_ascii_lowercase().as_str() {
+		"getstate" => Some(Ty::String),
+		"gotostate" => Some(Ty::None),
+		_ => None,
+	}
+}
+
+/// Function of another script called by `target`, along with the name of the script declaring it.
+/// `base` is the type of what a method is called on.
+/// Unqualified names that this script doesn't inherit are looked up in the global functions of its imports.
+pub(crate) fn external<'a>(
+	script: &str,
+	target: &Expression,
+	base: Option<&Ty>,
+	resolution: &Resolution,
+	hierarchy: &'a dyn Hierarchy,
+	errors: &mut Vec<Error>,
+) -> Option<(&'a str, &'a Statement)> {
+	match target {
+		Expression::Ident(name) => {
+			if let Some(inherited) = hierarchy.function(script, name).flatten() {
+				return Some(inherited);
+			}
+
+			let imported: Vec<_> = resolution
+				.symbols
+				.imports
+				.values()
+				.filter_map(|import| match import {
+					Statement::Import { item, .. } => hierarchy.function(item, name).flatten(),
+					_ => None,
+				})
+				.filter(|(_, function)| is_global(function))
+				.collect();
+			if imported.len() > 1 {
+				errors.push(Error::Ambiguous {
+					name: name.clone(),
+					scripts: imported
+						.iter()
+						.map(|(script, _)| script.to_string())
+						.collect(),
+				});
+			}
+			imported.first().copied()
+		}
+		Expression::DotIndex(_, method) => match base {
+			Some(Ty::Object(script)) => hierarchy.function(script, method).flatten(),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Matches the arguments of every call to a function whose declaration is known with its parameters.
+/// Functions of other scripts are looked up through `hierarchy`, using the types of what they are called on.
+pub fn resolve_calls<'a>(
+	ast: &'a Ast,
+	resolution: &Resolution<'a>,
+	hierarchy: &'a dyn Hierarchy,
+	typing: &Typing,
+	diagnostics: &mut Diagnostics,
+) -> Calls<'a> {
+	let mut out = Calls::default();
+
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| {
+			for expr in stmt.expressions() {
+				expr.visit(&mut |expr| {
+					let Expression::Call(target, args) = expr else {
+						return;
+					};
+
+					let mut errors = vec![];
+					let (script, function) = match resolution.get(target) {
+						Some(Binding::Function(function) | Binding::Event(function)) => {
+							(None, function)
+						}
+						_ => {
+							let base = match target.as_ref() {
+								Expression::DotIndex(base, _) => typing.get(base),
+								_ => None,
+							};
+							let own = &ast.script_info.script_name;
+							match external(own, target, base, resolution, hierarchy, &mut errors) {
+								Some((script, function)) => (Some(script), function),
+								None => return,
+							}
+						}
+					};
+
+					if let Some(call) = match_arguments(function, args, &mut errors) {
+						out.calls.insert(expr, Call { script, ..call });
+					}
+
+					for error in errors {
+						diagnostics.push(Diagnostic::from(error).with_span(stmt.span()));
+					}
+				});
+			}
+		});
+	}
+
+	out
+}
@@ -0,0 +1,206 @@
+use super::resolve::{Binding, Resolution};
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Self cannot be used in Global function {0}")]
+	SelfInGlobal(String),
+
+	#[error("Parent cannot be used in Global function {0}")]
+	ParentInGlobal(String),
+
+	#[error("Global function {function} cannot call member function {name} without an object")]
+	MemberCall { function: String, name: String },
+
+	#[error("Global function {function} cannot access {name}, which belongs to an instance of the script")]
+	MemberAccess { function: String, name: String },
+
+	#[error("Global function {function} cannot call {name}, as it has no state")]
+	StateCall { function: String, name: String },
+
+	#[error("Parent cannot be used in {0}, which does not extend another script")]
+	NoParent(String),
+
+	#[error("Parent can only be used to call a function, as in Parent.OnInit()")]
+	ParentNotCalled,
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::SelfInGlobal(_) => "E0701",
+			Self::ParentInGlobal(_) => "E0702",
+			Self::MemberCall { .. } => "E0703",
+			Self::MemberAccess { .. } => "E0704",
+			Self::StateCall { .. } => "E0705",
+			Self::NoParent(_) => "E0706",
+			Self::ParentNotCalled => "E0707",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Whether a statement is a function declared Global, which is called on the script rather than an instance.
+pub(crate) fn is_global(stmt: &Statement) -> bool {
+	matches!(
+		stmt,
+		Statement::Function {
+			is_global: true,
+			..
+		} | Statement::NativeFunction {
+			is_global: true,
+			..
+		}
+	)
+}
+
+struct Context<'a, 'r> {
+	ast: &'a Ast,
+	resolution: &'r Resolution<'a>,
+	/// Name of the function being checked, if it is Global.
+	global: Option<&'a str>,
+	diagnostics: &'r mut Diagnostics,
+}
+
+impl<'a> Context<'a, '_> {
+	fn report(&mut self, error: Error, span: Span) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(span));
+	}
+
+	fn expression(
+		&mut self,
+		expr: &'a Expression,
+		span: Span,
+		called: &mut HashSet<*const Expression>,
+	) {
+		let function = || self.global.unwrap_or_default().to_owned();
+
+		match expr {
+			Expression::Call(target, _) => match target.as_ref() {
+				Expression::DotIndex(base, _) if matches!(base.as_ref(), Expression::Parent) => {
+					called.insert(base.as_ref());
+				}
+
+				Expression::Ident(name) if self.global.is_some() => {
+					match self.resolution.get(target) {
+						Some(Binding::Function(stmt)) if is_global(stmt) => (),
+						Some(Binding::Function(_) | Binding::Event(_)) => self.report(
+							Error::MemberCall {
+								function: function(),
+								name: name.clone(),
+							},
+							span,
+						),
+						None if name.eq_ignore_ascii_case("GotoState")
+							|| name.eq_ignore_ascii_case("GetState") =>
+						{
+							self.report(
+								Error::StateCall {
+									function: function(),
+									name: name.clone(),
+								},
+								span,
+							)
+						}
+						_ => (),
+					}
+				}
+
+				_ => (),
+			},
+
+			Expression::Ident(name) if self.global.is_some() => {
+				if let Some(Binding::Variable(_) | Binding::Property(_)) = self.resolution.get(expr)
+				{
+					self.report(
+						Error::MemberAccess {
+							function: function(),
+							name: name.clone(),
+						},
+						span,
+					);
+				}
+			}
+
+			Expression::SelfRef if self.global.is_some() => {
+				self.report(Error::SelfInGlobal(function()), span)
+			}
+
+			Expression::Parent => {
+				if self.global.is_some() {
+					self.report(Error::ParentInGlobal(function()), span);
+				} else if self.ast.script_info.extended_type.is_none() {
+					let script = self.ast.script_info.script_name.clone();
+					self.report(Error::NoParent(script), span);
+				} else if !called.contains(&(expr as *const _)) {
+					self.report(Error::ParentNotCalled, span);
+				}
+			}
+
+			_ => (),
+		}
+	}
+
+	fn statement(&mut self, stmt: &'a Statement) {
+		let mut called = HashSet::new();
+		for expr in stmt.expressions() {
+			expr.visit(&mut |expr| self.expression(expr, stmt.span(), &mut called));
+		}
+
+		if let (
+			Some(function),
+			Statement::Assignment { name, .. } | Statement::CompoundAssignment { name, .. },
+		) = (self.global, stmt)
+		{
+			if let Some(Binding::Variable(_) | Binding::Property(_)) = self.resolution.target(stmt)
+			{
+				let error = Error::MemberAccess {
+					function: function.to_owned(),
+					name: name.clone(),
+				};
+				self.report(error, stmt.span());
+			}
+		}
+	}
+}
+
+/// Checks the uses of `Self` and `Parent`, and that Global functions don't use anything tied to an instance.
+pub fn check_context<'a>(ast: &'a Ast, resolution: &Resolution<'a>, diagnostics: &mut Diagnostics) {
+	let mut context = Context {
+		ast,
+		resolution,
+		global: None,
+		diagnostics,
+	};
+
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| {
+			// Every statement of a function's body is visited before the next declaration.
+			match stmt {
+				Statement::Function {
+					name, is_global, ..
+				} => context.global = is_global.then_some(name.as_str()),
+				Statement::Event { .. }
+				| Statement::NativeFunction { .. }
+				| Statement::State { .. }
+				| Statement::Group { .. }
+				| Statement::PropertyFull { .. }
+				| Statement::PropertyAuto { .. }
+				| Statement::PropertyAutoConst { .. }
+				| Statement::Struct { .. } => context.global = None,
+				_ => (),
+			}
+			context.statement(stmt);
+		});
+		context.global = None;
+	}
+}
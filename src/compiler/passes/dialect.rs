@@ -0,0 +1,185 @@
+use super::typecheck::Typing;
+use super::*;
+use crate::compiler::codegen::array_opcode;
+use crate::compiler::pex::Opcode;
+use crate::compiler::{game::Game, types::Ty};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use thiserror::Error;
+
+/// Largest array that can be created with `new` in Skyrim.
+const MAX_ARRAY_SIZE: i64 = 128;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Structs are not supported in {0}")]
+	StructsUnsupported(Game),
+
+	#[error("Field {field} of struct {name} cannot be {ty}, only primitive types, Var and objects are allowed")]
+	InvalidField { name: String, field: String, ty: Ty },
+
+	#[error("Arrays cannot be multi-dimensional, so {0} is not a valid element type")]
+	MultiDimensional(Ty),
+
+	#[error("Array size must be a constant between 1 and {MAX_ARRAY_SIZE} in {0}")]
+	ArraySize(Game),
+
+	#[error("Script {0} has a namespace, which is not supported in {1}")]
+	NamespacesUnsupported(String, Game),
+
+	#[error("{0} is not supported in {1}")]
+	OpcodeUnsupported(String, Game),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::StructsUnsupported(_) => "E0801",
+			Self::InvalidField { .. } => "E0802",
+			Self::MultiDimensional(_) => "E0803",
+			Self::ArraySize(_) => "E0804",
+			Self::NamespacesUnsupported(..) => "E0805",
+			Self::OpcodeUnsupported(..) => "E0806",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+struct Dialect<'d> {
+	game: Game,
+	/// Lowercased names of the structs declared in this script.
+	structs: Vec<String>,
+	diagnostics: &'d mut Diagnostics,
+}
+
+impl Dialect<'_> {
+	fn report(&mut self, error: Error, span: Span) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(span));
+	}
+
+	fn field(&mut self, name: &str, field: &Field, span: Span) {
+		let ty = Ty::parse(&field.0);
+		let valid = match &ty {
+			Ty::Bool | Ty::Int | Ty::Float | Ty::String | Ty::Var => true,
+			Ty::Object(name) => !self.structs.contains(&name.to_ascii_lowercase()),
+			_ => false,
+		};
+
+		if !valid {
+			let ty = match ty {
+				Ty::Object(name) => Ty::Struct(name),
+				ty => ty,
+			};
+
+			let error = Error::InvalidField {
+				name: name.to_owned(),
+				field: field.1.clone(),
+				ty,
+			};
+			self.report(error, span);
+		}
+	}
+
+	fn expression(&mut self, expr: &Expression, span: Span) {
+		match expr {
+			Expression::Array(ty, size) => {
+				if let ty @ Ty::Array(_) = Ty::parse(ty) {
+					self.report(Error::MultiDimensional(ty), span);
+				}
+
+				let constant = matches!(size.as_ref(), Expression::Integer(1..=MAX_ARRAY_SIZE));
+				if self.game == Game::Skyrim && !constant {
+					self.report(Error::ArraySize(self.game), span);
+				}
+			}
+
+			Expression::Struct(_) if !self.game.has_structs() => {
+				self.report(Error::StructsUnsupported(self.game), span);
+			}
+
+			_ => (),
+		}
+	}
+
+	fn statement(&mut self, stmt: &Statement) {
+		if let Statement::Struct {
+			name, fields, span, ..
+		} = stmt
+		{
+			if self.game.has_structs() {
+				for field in fields {
+					self.field(name, field, *span);
+				}
+			} else {
+				self.report(Error::StructsUnsupported(self.game), *span);
+			}
+		}
+
+		for expr in stmt.expressions() {
+			expr.visit(&mut |expr| self.expression(expr, stmt.span()));
+		}
+	}
+}
+
+/// Checks the rules for structs, arrays and namespaces that depend on the game being compiled for.
+pub fn check_dialect(ast: &Ast, game: Game, diagnostics: &mut Diagnostics) {
+	let structs = ast
+		.statements
+		.iter()
+		.filter_map(|stmt| match stmt {
+			Statement::Struct { name, .. } => Some(name.to_ascii_lowercase()),
+			_ => None,
+		})
+		.collect();
+
+	let mut dialect = Dialect {
+		game,
+		structs,
+		diagnostics,
+	};
+
+	let info = &ast.script_info;
+	if info.script_name.contains(':') && !game.has_namespaces() {
+		let error = Error::NamespacesUnsupported(info.script_name.clone(), game);
+		dialect.report(error, info.span);
+	}
+
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| dialect.statement(stmt));
+	}
+}
+
+/// Checks that expressions only compile to opcodes `game` has, which for arrays depends on their types.
+pub fn check_opcodes(ast: &Ast, game: Game, typing: &Typing, diagnostics: &mut Diagnostics) {
+	let needed = |expr: &Expression| match expr {
+		Expression::Is(..) => Some((Opcode::Is, "The is operator".to_owned())),
+		Expression::Call(target, _) => match target.as_ref() {
+			Expression::DotIndex(base, method)
+				if matches!(typing.get(base), Some(Ty::Array(_))) =>
+			{
+				Some((array_opcode(method)?, format!("Array method {method}")))
+			}
+			_ => None,
+		},
+		_ => None,
+	};
+
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| {
+			for expr in stmt.expressions() {
+				expr.visit(&mut |expr| match needed(expr) {
+					Some((opcode, what)) if !opcode.is_supported(game) => {
+						let error = Error::OpcodeUnsupported(what, game);
+						diagnostics.push(Diagnostic::from(error).with_span(stmt.span()));
+					}
+					_ => (),
+				});
+			}
+		});
+	}
+}
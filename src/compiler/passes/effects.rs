@@ -0,0 +1,108 @@
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::Format;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{0} has no effect as a statement, only function calls can be used on their own")]
+	NoEffect(&'static str),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::NoEffect(_) => "E1001",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// What an expression is, for describing it in an error.
+fn describe(expr: &Expression) -> &'static str {
+	match expr {
+		Expression::Addition(..)
+		| Expression::Subtraction(..)
+		| Expression::Multiplication(..)
+		| Expression::Division(..)
+		| Expression::Modulo(..)
+		| Expression::Negate(_) => "Arithmetic",
+		Expression::GreaterThan(..)
+		| Expression::LessThan(..)
+		| Expression::GreaterThanOrEqual(..)
+		| Expression::LessThanOrEqual(..)
+		| Expression::Equal(..)
+		| Expression::NotEqual(..) => "Comparison",
+		Expression::And(..) | Expression::Or(..) | Expression::Not(_) => "Logical expression",
+		Expression::Cast(..) => "Cast",
+		Expression::Is(..) => "Type check",
+		Expression::Array(..) | Expression::Struct(_) => "New",
+		Expression::DotIndex(..) | Expression::BracketIndex(..) => "Member access",
+		Expression::Ident(_) | Expression::SelfRef | Expression::Parent => "Variable",
+		Expression::Bool(_)
+		| Expression::String(_)
+		| Expression::Integer(_)
+		| Expression::Float(_)
+		| Expression::None => "Literal",
+		Expression::Call(..) => "Call",
+	}
+}
+
+fn assignable(expr: &Expression) -> bool {
+	matches!(
+		expr,
+		Expression::Ident(_) | Expression::DotIndex(..) | Expression::BracketIndex(..)
+	)
+}
+
+/// Assignment the author most likely meant to write instead of `expr`.
+fn intended(expr: &Expression) -> Option<String> {
+	let (lhs, op, rhs) = match expr {
+		Expression::Equal(lhs, rhs) => (lhs, "=", rhs),
+		Expression::Addition(lhs, rhs) => (lhs, "+=", rhs),
+		Expression::Subtraction(lhs, rhs) => (lhs, "-=", rhs),
+		Expression::Multiplication(lhs, rhs) => (lhs, "*=", rhs),
+		Expression::Division(lhs, rhs) => (lhs, "/=", rhs),
+		Expression::Modulo(lhs, rhs) => (lhs, "%=", rhs),
+		_ => return None,
+	};
+
+	// Compound assignments only work on plain variables.
+	let valid = match op {
+		"=" => assignable(lhs),
+		_ => matches!(lhs.as_ref(), Expression::Ident(_)),
+	};
+
+	valid.then(|| {
+		let (lhs, rhs) = (lhs.as_ref().clone(), rhs.as_ref().clone());
+		format!("{} {op} {}", lhs.format(), rhs.format())
+	})
+}
+
+/// Checks that expressions used as statements are calls, as anything else would do nothing.
+pub fn check_effects(ast: &Ast, diagnostics: &mut Diagnostics) {
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| {
+			let Statement::Expression { expr, span } = stmt else {
+				return;
+			};
+
+			if matches!(expr, Expression::Call(..)) {
+				return;
+			}
+
+			let diagnostic = Diagnostic::from(Error::NoEffect(describe(expr))).with_span(*span);
+			diagnostics.push(match intended(expr) {
+				Some(assignment) => {
+					diagnostic.with_fix("did you mean to assign", *span, assignment)
+				}
+				None => diagnostic,
+			});
+		});
+	}
+}
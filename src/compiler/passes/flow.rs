@@ -0,0 +1,140 @@
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Function {0} does not return a value on every path")]
+	MissingReturn(String),
+
+	#[error("{0} has no return type, so it cannot return a value")]
+	UnexpectedValue(String),
+
+	#[error("Function {function} must return a value of type {ty}")]
+	MissingValue { function: String, ty: String },
+
+	#[error("unreachable statement")]
+	Unreachable,
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::MissingReturn(_) => "E0601",
+			Self::UnexpectedValue(_) => "E0602",
+			Self::MissingValue { .. } => "E0603",
+			Self::Unreachable => "W0601",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		match error {
+			Error::Unreachable => Diagnostic::warning(error.code(), error.to_string()),
+			error => Diagnostic::error(error.code(), error.to_string()),
+		}
+	}
+}
+
+struct Flow<'a, 'd> {
+	/// Name and return type of the function being checked.
+	function: (&'a str, Option<&'a str>),
+	diagnostics: &'d mut Diagnostics,
+}
+
+impl Flow<'_, '_> {
+	fn report(&mut self, error: Error, span: Span) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(span));
+	}
+
+	/// Whether control can never reach the end of this block, reporting anything after the point it stops.
+	fn block(&mut self, body: &[Statement]) -> bool {
+		let mut diverges = false;
+		for stmt in body {
+			if diverges {
+				self.report(Error::Unreachable, stmt.span());
+				return true;
+			}
+			diverges = self.statement(stmt);
+		}
+		diverges
+	}
+
+	/// Whether control can never continue past this statement.
+	fn statement(&mut self, stmt: &Statement) -> bool {
+		match stmt {
+			Statement::Return { value, span } => {
+				match (value, self.function) {
+					(Some(_), (name, None)) => {
+						self.report(Error::UnexpectedValue(name.to_owned()), *span)
+					}
+					(None, (name, Some(ty))) => self.report(
+						Error::MissingValue {
+							function: name.to_owned(),
+							ty: ty.to_owned(),
+						},
+						*span,
+					),
+					_ => (),
+				}
+				true
+			}
+
+			Statement::If {
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				let mut diverges = self.block(body);
+				for (_, body) in elifs {
+					diverges &= self.block(body);
+				}
+
+				match else_block {
+					Some(body) => self.block(body) && diverges,
+					None => false,
+				}
+			}
+
+			// Papyrus has no break, so a loop on a constant true condition can only be left by returning.
+			Statement::While { cond, body, .. } => {
+				self.block(body);
+				matches!(cond, Expression::Bool(true))
+			}
+
+			_ => false,
+		}
+	}
+}
+
+fn function(stmt: &Statement, diagnostics: &mut Diagnostics) {
+	let (name, return_type, body) = match stmt {
+		Statement::Function {
+			name,
+			return_type,
+			body,
+			..
+		} => (name, return_type.as_deref(), body),
+		Statement::Event { name, body, .. } => (name, None, body),
+		_ => return,
+	};
+
+	let mut flow = Flow {
+		function: (name, return_type),
+		diagnostics,
+	};
+
+	if !flow.block(body) && return_type.is_some() {
+		flow.report(Error::MissingReturn(name.clone()), stmt.span());
+	}
+}
+
+/// Checks that every function with a return type returns a value, and that no statement is unreachable.
+pub fn check_flow(ast: &Ast, diagnostics: &mut Diagnostics) {
+	for stmt in &ast.statements {
+		stmt.visit(&mut |stmt| function(stmt, diagnostics));
+	}
+}
@@ -0,0 +1,280 @@
+use super::resolve::Symbols;
+use super::*;
+use crate::compiler::types::Ty;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::project::Project;
+use crate::Format;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{child}.{name} does not match the signature of {parent}.{name}: {reason}")]
+	SignatureMismatch {
+		child: String,
+		parent: String,
+		name: String,
+		reason: String,
+	},
+
+	#[error("{child}.{name} redeclares {parent}.{name}")]
+	Redeclared {
+		child: String,
+		parent: String,
+		name: String,
+	},
+
+	#[error("{child}.{name} overrides native function {parent}.{name}, but {child} is not Native")]
+	NativeOverride {
+		child: String,
+		parent: String,
+		name: String,
+	},
+
+	#[error("{child} extends Const script {parent}, but is not Const itself")]
+	ConstParent { child: String, parent: String },
+
+	#[error("Native script {child} extends {parent}, which is not Native")]
+	NativeParent { child: String, parent: String },
+
+	#[error("{child}.{name} uses Parent, but does not override a function of a parent script")]
+	NotOverriding { child: String, name: String },
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::SignatureMismatch { .. } => "E0301",
+			Self::Redeclared { .. } => "E0302",
+			Self::NativeOverride { .. } => "E0303",
+			Self::ConstParent { .. } => "E0304",
+			Self::NativeParent { .. } => "E0305",
+			Self::NotOverriding { .. } => "E0306",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Name, return type and parameters of a function or event.
+fn signature(stmt: &Statement) -> Option<(&String, Ty, &[Parameter])> {
+	match stmt {
+		Statement::Function {
+			name,
+			return_type,
+			parameters,
+			..
+		}
+		| Statement::NativeFunction {
+			name,
+			return_type,
+			parameters,
+			..
+		} => Some((
+			name,
+			return_type.as_deref().map_or(Ty::None, Ty::parse),
+			parameters,
+		)),
+		Statement::Event {
+			name, parameters, ..
+		} => Some((name, Ty::None, parameters)),
+		_ => None,
+	}
+}
+
+/// Describes how the signature of `child` differs from `parent`, if at all.
+pub(crate) fn compare(child: &Statement, parent: &Statement) -> Option<String> {
+	let (_, child_ret, child_params) = signature(child)?;
+	let (_, parent_ret, parent_params) = signature(parent)?;
+
+	if matches!(child, Statement::Event { .. }) != matches!(parent, Statement::Event { .. }) {
+		return Some(String::from(
+			"functions and events cannot override each other",
+		));
+	}
+
+	if child_ret != parent_ret {
+		return Some(format!("returns {child_ret} instead of {parent_ret}"));
+	}
+
+	if child_params.len() != parent_params.len() {
+		return Some(format!(
+			"takes {} parameters instead of {}",
+			child_params.len(),
+			parent_params.len()
+		));
+	}
+
+	for (child, parent) in child_params.iter().zip(parent_params) {
+		let (child_ty, parent_ty) = (Ty::parse(&child.0), Ty::parse(&parent.0));
+		if child_ty != parent_ty {
+			return Some(format!(
+				"parameter {} is {child_ty} instead of {parent_ty}",
+				child.1
+			));
+		}
+
+		let default = |param: &Parameter| param.2.clone().map(Format::format);
+		match (default(child), default(parent)) {
+			(Some(a), Some(b)) if a.eq_ignore_ascii_case(&b) => (),
+			(None, None) => (),
+			_ => {
+				return Some(format!(
+					"parameter {} has a different default value",
+					child.1
+				))
+			}
+		}
+	}
+
+	None
+}
+
+fn uses_parent(function: &Statement) -> bool {
+	let mut found = false;
+	function.visit(&mut |stmt| {
+		for expr in stmt.expressions() {
+			expr.visit(&mut |expr| found |= matches!(expr, Expression::Parent));
+		}
+	});
+	found
+}
+
+/// Checks a loaded script against every script it extends.
+pub fn check_inheritance(project: &Project, name: &str, diagnostics: &mut Diagnostics) {
+	let mut chain = project.chain(name);
+	let Some(script) = chain.next() else {
+		return;
+	};
+
+	let info = &script.ast.script_info;
+	let child = &info.script_name;
+	// Redeclarations within a script are reported by resolve.
+	let symbols = Symbols::new(&script.ast, &mut Diagnostics::new());
+
+	let ancestors = chain
+		.map(|parent| {
+			let symbols = Symbols::new(&parent.ast, &mut Diagnostics::new());
+			(&parent.ast.script_info, &parent.path, symbols)
+		})
+		.collect::<Vec<_>>();
+
+	// Without every parent, a function could be overriding one that isn't loaded.
+	let complete = ancestors
+		.last()
+		.is_some_and(|(info, ..)| info.extended_type.is_none());
+
+	// Points at the declaration in the parent script that conflicts with the child.
+	let report =
+		|diagnostics: &mut Diagnostics, error: Error, span: Span, parent: (Span, &PathBuf)| {
+			diagnostics.push(Diagnostic::from(error).with_span(span).with_label(
+				parent.0,
+				Some(parent.1.clone()),
+				"declared here",
+			));
+		};
+
+	if let Some((parent, path, _)) = ancestors.first() {
+		if parent.is_const && !info.is_const {
+			let error = Error::ConstParent {
+				child: child.clone(),
+				parent: parent.script_name.clone(),
+			};
+			report(diagnostics, error, info.span, (parent.span, path));
+		}
+
+		if info.is_native && !parent.is_native {
+			let error = Error::NativeParent {
+				child: child.clone(),
+				parent: parent.script_name.clone(),
+			};
+			report(diagnostics, error, info.span, (parent.span, path));
+		}
+	}
+
+	let functions = symbols
+		.functions
+		.iter()
+		.chain(symbols.states.values().flat_map(|s| s.functions.iter()));
+
+	for (key, function) in functions {
+		let overridden = ancestors
+			.iter()
+			.find_map(|(info, path, symbols)| Some((*info, *path, *symbols.functions.get(key)?)));
+
+		let Some((parent, path, overridden)) = overridden else {
+			// Parent.Foo() is only valid when there is a parent version of this function to call.
+			if complete && uses_parent(function) {
+				let error = Error::NotOverriding {
+					child: child.clone(),
+					name: signature(function).unwrap().0.clone(),
+				};
+				diagnostics.push(Diagnostic::from(error).with_span(function.span()));
+			}
+			continue;
+		};
+
+		let name = signature(function).unwrap().0.clone();
+
+		if matches!(overridden, Statement::NativeFunction { .. }) && !info.is_native {
+			let error = Error::NativeOverride {
+				child: child.clone(),
+				parent: parent.script_name.clone(),
+				name: name.clone(),
+			};
+			report(
+				diagnostics,
+				error,
+				function.span(),
+				(overridden.span(), path),
+			);
+		}
+
+		if let Some(reason) = compare(function, overridden) {
+			let error = Error::SignatureMismatch {
+				child: child.clone(),
+				parent: parent.script_name.clone(),
+				name,
+				reason,
+			};
+			report(
+				diagnostics,
+				error,
+				function.span(),
+				(overridden.span(), path),
+			);
+		}
+	}
+
+	for (key, stmt) in symbols.properties.iter().chain(&symbols.variables) {
+		let redeclared = ancestors.iter().find_map(|(info, path, symbols)| {
+			symbols
+				.properties
+				.get(key)
+				.or_else(|| symbols.variables.get(key))
+				.map(|stmt| (*info, *path, *stmt))
+		});
+
+		if let Some((parent, path, previous)) = redeclared {
+			let name = match stmt {
+				Statement::Declaration { name, .. }
+				| Statement::Definition { name, .. }
+				| Statement::PropertyFull { name, .. }
+				| Statement::PropertyAuto { name, .. }
+				| Statement::PropertyAutoConst { name, .. } => name.clone(),
+				_ => key.clone(),
+			};
+
+			let error = Error::Redeclared {
+				child: child.clone(),
+				parent: parent.script_name.clone(),
+				name,
+			};
+			report(diagnostics, error, stmt.span(), (previous.span(), path));
+		}
+	}
+}
@@ -0,0 +1,70 @@
+use super::*;
+use crate::compiler::conversion::constant;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Initial value of {0} must be a literal, as it is set before any code runs")]
+	NotConstant(String),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::NotConstant(_) => "E1301",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Whether `expr` is an Int literal, which the type checker reports if it doesn't fit.
+fn is_integer(expr: &Expression) -> bool {
+	match expr {
+		Expression::Integer(_) => true,
+		Expression::Negate(inner) => matches!(inner.as_ref(), Expression::Integer(_)),
+		_ => false,
+	}
+}
+
+fn statement(stmt: &Statement, diagnostics: &mut Diagnostics) {
+	let values = match stmt {
+		Statement::Definition { name, value, .. }
+		| Statement::PropertyAutoConst { name, value, .. }
+		| Statement::PropertyAuto {
+			name,
+			value: Some(value),
+			..
+		} => vec![(name, value)],
+		Statement::Struct { fields, .. } => fields
+			.iter()
+			.filter_map(|Field(_, name, value, ..)| Some((name, value.as_ref()?)))
+			.collect(),
+		Statement::Group { properties, .. } => {
+			properties
+				.iter()
+				.for_each(|stmt| statement(stmt, diagnostics));
+			return;
+		}
+		_ => return,
+	};
+
+	for (name, value) in values {
+		if constant(value).is_none() && !is_integer(value) {
+			diagnostics
+				.push(Diagnostic::from(Error::NotConstant(name.clone())).with_span(stmt.span()));
+		}
+	}
+}
+
+/// Checks that the initial values of variables, properties and struct members are known before any code runs.
+pub fn check_initial_values(ast: &Ast, diagnostics: &mut Diagnostics) {
+	for stmt in &ast.statements {
+		statement(stmt, diagnostics);
+	}
+}
@@ -0,0 +1,166 @@
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+	Script,
+	State,
+	Group,
+	Property,
+	/// Body of a function or event, including any nested blocks.
+	Function,
+}
+
+impl fmt::Display for Scope {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Script => write!(f, "at script scope"),
+			Self::State => write!(f, "inside of a state"),
+			Self::Group => write!(f, "inside of a group"),
+			Self::Property => write!(f, "inside of a property"),
+			Self::Function => write!(f, "inside of a function or event"),
+		}
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{construct} is not allowed {scope}, only {allowed}")]
+	Misplaced {
+		construct: &'static str,
+		scope: Scope,
+		allowed: &'static str,
+	},
+
+	#[error("Property {property} can only contain Get and Set functions, not {function}")]
+	NotAccessor { property: String, function: String },
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Misplaced { .. } => "E0501",
+			Self::NotAccessor { .. } => "E0502",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Describes a statement, along with where it may appear.
+fn describe(stmt: &Statement) -> (&'static str, &'static [Scope], &'static str) {
+	use Scope::*;
+
+	const DECLARATION: &str = "at script scope or inside of a function or event";
+	const CODE: &str = "inside of a function or event";
+	const SCRIPT: &str = "at script scope";
+
+	match stmt {
+		Statement::Function { .. } => (
+			"Function",
+			&[Script, State, Property],
+			"at script scope or inside of a state",
+		),
+		Statement::Event { .. } => (
+			"Event",
+			&[Script, State],
+			"at script scope or inside of a state",
+		),
+		Statement::NativeFunction { .. } => ("Native function", &[Script], SCRIPT),
+		Statement::PropertyFull { .. }
+		| Statement::PropertyAuto { .. }
+		| Statement::PropertyAutoConst { .. } => (
+			"Property",
+			&[Script, Group],
+			"at script scope or inside of a group",
+		),
+		// Variables inside of states are reported by the state validation instead.
+		Statement::Declaration { .. } | Statement::Definition { .. } => {
+			("Variable", &[Script, State, Function], DECLARATION)
+		}
+		Statement::State { .. } => ("State", &[Script], SCRIPT),
+		Statement::Group { .. } => ("Group", &[Script], SCRIPT),
+		Statement::Struct { .. } => ("Struct", &[Script], SCRIPT),
+		Statement::Import { .. } => ("Import", &[Script], SCRIPT),
+		Statement::If { .. } => ("If", &[Function], CODE),
+		Statement::While { .. } => ("While", &[Function], CODE),
+		Statement::Return { .. } => ("Return", &[Function], CODE),
+		Statement::Assignment { .. } | Statement::CompoundAssignment { .. } => {
+			("Assignment", &[Function], CODE)
+		}
+		Statement::Expression { .. } => ("Expression", &[Function], CODE),
+	}
+}
+
+fn check(stmt: &Statement, scope: Scope, errors: &mut Diagnostics) {
+	let (construct, scopes, allowed) = describe(stmt);
+	if !scopes.contains(&scope) {
+		let error = Error::Misplaced {
+			construct,
+			scope,
+			allowed,
+		};
+		errors.push(Diagnostic::from(error).with_span(stmt.span()));
+	}
+
+	match stmt {
+		Statement::If {
+			body,
+			elifs,
+			else_block,
+			..
+		} => {
+			body.iter().for_each(|s| check(s, Scope::Function, errors));
+			for (_, body) in elifs {
+				body.iter().for_each(|s| check(s, Scope::Function, errors));
+			}
+			if let Some(body) = else_block {
+				body.iter().for_each(|s| check(s, Scope::Function, errors));
+			}
+		}
+
+		Statement::While { body, .. }
+		| Statement::Function { body, .. }
+		| Statement::Event { body, .. } => body.iter().for_each(|s| check(s, Scope::Function, errors)),
+
+		Statement::State { body, .. } => body.iter().for_each(|s| check(s, Scope::State, errors)),
+		Statement::Group { properties, .. } => properties
+			.iter()
+			.for_each(|s| check(s, Scope::Group, errors)),
+
+		Statement::PropertyFull {
+			name, functions, ..
+		} => {
+			for function in std::iter::once(&functions.0).chain(&functions.1) {
+				check(function, Scope::Property, errors);
+
+				if let Statement::Function { name: accessor, .. } = function.as_ref() {
+					if !accessor.eq_ignore_ascii_case("Get")
+						&& !accessor.eq_ignore_ascii_case("Set")
+					{
+						let error = Error::NotAccessor {
+							property: name.clone(),
+							function: accessor.clone(),
+						};
+						errors.push(Diagnostic::from(error).with_span(function.span()));
+					}
+				}
+			}
+		}
+
+		_ => (),
+	}
+}
+
+/// Checks that every statement is somewhere the official compiler allows it.
+pub fn check_placement(ast: &Ast, diagnostics: &mut Diagnostics) {
+	for stmt in &ast.statements {
+		check(stmt, Scope::Script, diagnostics);
+	}
+}
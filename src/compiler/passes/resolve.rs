@@ -0,0 +1,594 @@
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Papyrus identifiers are case-insensitive, so every table is keyed by the lowercased name.
+#[inline(always)]
+pub(crate) fn key(name: &str) -> String {
+	name.to_ascii_lowercase()
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{0} declared multiple times")]
+	Redeclared(String),
+
+	#[error("variable {0} not declared")]
+	Undeclared(String),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Redeclared(_) => "E0101",
+			Self::Undeclared(_) => "E0102",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Reports `stmt` redeclaring a name first declared by `previous`.
+fn redeclared(diagnostics: &mut Diagnostics, name: &str, stmt: &Statement, previous: &Statement) {
+	diagnostics.push(
+		Diagnostic::from(Error::Redeclared(name.to_owned()))
+			.with_span(stmt.span())
+			.with_label(previous.span(), None, "first declared here"),
+	);
+}
+
+/// The declaration an identifier refers to.
+#[derive(Debug, Clone, Copy)]
+pub enum Binding<'a> {
+	/// [Statement::Declaration] or [Statement::Definition] inside of a function or event.
+	Local(&'a Statement),
+	Parameter(&'a Parameter),
+	/// [Statement::Declaration] or [Statement::Definition] at script scope.
+	Variable(&'a Statement),
+	/// Any of the property statements.
+	Property(&'a Statement),
+	/// [Statement::Function] or [Statement::NativeFunction]
+	Function(&'a Statement),
+	Event(&'a Statement),
+	Struct(&'a Statement),
+	Field(&'a Field),
+	/// Script brought into scope through [Statement::Import]
+	Import(&'a Statement),
+	/// Any other script referenced by name, including this one.
+	Script(&'a str),
+}
+
+impl<'a> Binding<'a> {
+	/// Declared type of the value this binding refers to, if it is a value.
+	pub fn ty(&self) -> Option<&'a Type> {
+		match self {
+			Self::Local(stmt) | Self::Variable(stmt) | Self::Property(stmt) => match stmt {
+				Statement::Declaration { ty, .. }
+				| Statement::Definition { ty, .. }
+				| Statement::PropertyAuto { ty, .. }
+				| Statement::PropertyAutoConst { ty, .. }
+				| Statement::PropertyFull { ty, .. } => Some(ty),
+				_ => None,
+			},
+			Self::Parameter(param) => Some(&param.0),
+			Self::Field(field) => Some(&field.0),
+			_ => None,
+		}
+	}
+
+	fn callable(stmt: &'a Statement) -> Self {
+		match stmt {
+			Statement::Event { .. } => Self::Event(stmt),
+			_ => Self::Function(stmt),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct State<'a> {
+	pub statement: &'a Statement,
+	pub functions: IndexMap<String, &'a Statement>,
+}
+
+/// Everything declared at script scope, keyed by lowercased name.
+#[derive(Debug, Default)]
+pub struct Symbols<'a> {
+	pub variables: IndexMap<String, &'a Statement>,
+	pub properties: IndexMap<String, &'a Statement>,
+	/// Functions and events of the empty state.
+	pub functions: IndexMap<String, &'a Statement>,
+	pub structs: IndexMap<String, &'a Statement>,
+	pub imports: IndexMap<String, &'a Statement>,
+	pub states: IndexMap<String, State<'a>>,
+}
+
+impl<'a> Symbols<'a> {
+	pub fn new(ast: &'a Ast, diagnostics: &mut Diagnostics) -> Self {
+		let mut symbols = Self::default();
+		for stmt in &ast.statements {
+			symbols.declare(stmt, None, diagnostics);
+		}
+		symbols
+	}
+
+	fn declare(&mut self, stmt: &'a Statement, state: Option<&str>, errors: &mut Diagnostics) {
+		fn insert<'a>(
+			table: &mut IndexMap<String, &'a Statement>,
+			name: &str,
+			stmt: &'a Statement,
+			errors: &mut Diagnostics,
+		) {
+			if let Some(previous) = table.insert(key(name), stmt) {
+				redeclared(errors, name, stmt, previous);
+			}
+		}
+
+		match stmt {
+			Statement::Declaration { name, .. } | Statement::Definition { name, .. } => {
+				insert(&mut self.variables, name, stmt, errors)
+			}
+
+			Statement::PropertyFull { name, .. }
+			| Statement::PropertyAuto { name, .. }
+			| Statement::PropertyAutoConst { name, .. } => insert(&mut self.properties, name, stmt, errors),
+
+			Statement::Function { name, .. }
+			| Statement::NativeFunction { name, .. }
+			| Statement::Event { name, .. } => match state.and_then(|s| self.states.get_mut(&key(s))) {
+				Some(state) => insert(&mut state.functions, name, stmt, errors),
+				None => insert(&mut self.functions, name, stmt, errors),
+			},
+
+			Statement::Struct { name, .. } => insert(&mut self.structs, name, stmt, errors),
+			Statement::Import { item, .. } => insert(&mut self.imports, item, stmt, errors),
+
+			Statement::Group { properties, .. } => {
+				for property in properties {
+					self.declare(property, state, errors);
+				}
+			}
+
+			Statement::State { name, body, .. } => {
+				if let Some(previous) = self.states.get(&key(name)) {
+					redeclared(errors, name, stmt, previous.statement);
+				} else {
+					self.states.insert(
+						key(name),
+						State {
+							statement: stmt,
+							functions: IndexMap::new(),
+						},
+					);
+				}
+
+				for item in body {
+					self.declare(item, Some(name), errors);
+				}
+			}
+
+			_ => (),
+		}
+	}
+
+	/// Finds a function or event, looking in the empty state first.
+	pub fn function(&self, name: &str) -> Option<&'a Statement> {
+		let name = key(name);
+		self.functions.get(&name).copied().or_else(|| {
+			self.states
+				.values()
+				.find_map(|state| state.functions.get(&name).copied())
+		})
+	}
+
+	/// Finds a member accessible through `Script.Member` or an instance of this script.
+	pub fn member(&self, name: &str) -> Option<Binding<'a>> {
+		let name = key(name);
+		if let Some(stmt) = self.properties.get(&name) {
+			Some(Binding::Property(stmt))
+		} else {
+			self.function(&name).map(Binding::callable)
+		}
+	}
+
+	/// Finds a field of a struct declared in this script.
+	pub fn field(&self, ty: &str, name: &str) -> Option<&'a Field> {
+		match self.structs.get(&key(ty)) {
+			Some(Statement::Struct { fields, .. }) => {
+				fields.iter().find(|f| f.1.eq_ignore_ascii_case(name))
+			}
+			_ => None,
+		}
+	}
+}
+
+/// Side table produced by [resolve], mapping nodes of an [Ast] to their declarations.
+#[derive(Debug)]
+pub struct Resolution<'a> {
+	pub symbols: Symbols<'a>,
+
+	/// Expressions that could not be bound inside of this script.
+	/// These may still be inherited from a parent script or be global functions of an import.
+	pub unresolved: Vec<&'a Expression>,
+
+	expressions: HashMap<*const Expression, Binding<'a>>,
+	targets: HashMap<*const Statement, Binding<'a>>,
+}
+
+impl<'a> Resolution<'a> {
+	/// Declaration an [Expression::Ident], [Expression::DotIndex] or [Expression::Call] target refers to.
+	pub fn get(&self, expr: &Expression) -> Option<Binding<'a>> {
+		self.expressions.get(&(expr as *const _)).copied()
+	}
+
+	/// Declaration the variable of an [Statement::Assignment] or [Statement::CompoundAssignment] refers to.
+	pub fn target(&self, stmt: &Statement) -> Option<Binding<'a>> {
+		self.targets.get(&(stmt as *const _)).copied()
+	}
+}
+
+struct Resolver<'a, 'd> {
+	ast: &'a Ast,
+	scopes: Vec<IndexMap<String, Binding<'a>>>,
+	out: Resolution<'a>,
+
+	diagnostics: &'d mut Diagnostics,
+	/// Span of the innermost statement being resolved.
+	span: Span,
+}
+
+impl<'a> Resolver<'a, '_> {
+	fn report(&mut self, error: Error) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(self.span));
+	}
+
+	fn enter_scope(&mut self) {
+		self.scopes.push(IndexMap::new());
+	}
+
+	fn exit_scope(&mut self) {
+		self.scopes.pop();
+	}
+
+	fn declare_local(&mut self, name: &str, binding: Binding<'a>) {
+		let name_key = key(name);
+		if self.scopes.iter().any(|s| s.contains_key(&name_key)) {
+			self.report(Error::Redeclared(name.to_owned()));
+		} else if let Some(scope) = self.scopes.last_mut() {
+			scope.insert(name_key, binding);
+		}
+	}
+
+	/// Looks up a name in value position.
+	fn lookup(&self, name: &str) -> Option<Binding<'a>> {
+		let name_key = key(name);
+		let symbols = &self.out.symbols;
+
+		self.scopes
+			.iter()
+			.rev()
+			.find_map(|s| s.get(&name_key).copied())
+			.or_else(|| {
+				symbols
+					.variables
+					.get(&name_key)
+					.map(|s| Binding::Variable(s))
+			})
+			.or_else(|| {
+				symbols
+					.properties
+					.get(&name_key)
+					.map(|s| Binding::Property(s))
+			})
+			.or_else(|| symbols.imports.get(&name_key).map(|s| Binding::Import(s)))
+			.or_else(|| {
+				let own = self.ast.script_info.script_name.as_str();
+				(own.eq_ignore_ascii_case(name) || name_key == "self")
+					.then_some(Binding::Script(own))
+			})
+	}
+
+	/// Looks up `member` on a value of the given binding.
+	fn member(&self, of: Binding<'a>, member: &str) -> Option<Binding<'a>> {
+		let own_script = &self.ast.script_info.script_name;
+		match of {
+			Binding::Script(name) if name.eq_ignore_ascii_case(own_script) => {
+				self.out.symbols.member(member)
+			}
+			_ => {
+				let ty = of.ty()?;
+				if ty.eq_ignore_ascii_case(own_script) {
+					self.out.symbols.member(member)
+				} else {
+					self.out.symbols.field(ty, member).map(Binding::Field)
+				}
+			}
+		}
+	}
+
+	fn bind(&mut self, expr: &'a Expression, binding: Option<Binding<'a>>) {
+		match binding {
+			Some(binding) => {
+				self.out.expressions.insert(expr, binding);
+			}
+			None => self.out.unresolved.push(expr),
+		}
+	}
+
+	fn undeclared(&mut self, name: &str) {
+		// Without a parent, there's nowhere else the variable could come from.
+		if self.ast.script_info.extended_type.is_none() {
+			self.report(Error::Undeclared(name.to_owned()));
+		}
+	}
+
+	fn expression(&mut self, expr: &'a Expression) {
+		match expr {
+			Expression::Ident(name) => {
+				let binding = self.lookup(name);
+				if binding.is_none() {
+					self.undeclared(name);
+				}
+				self.bind(expr, binding);
+			}
+
+			Expression::SelfRef => {
+				let name = self.ast.script_info.script_name.as_str();
+				self.bind(expr, Some(Binding::Script(name)));
+			}
+
+			Expression::Parent => {
+				let parent = self.ast.script_info.extended_type.as_deref();
+				self.bind(expr, parent.map(Binding::Script));
+			}
+
+			Expression::DotIndex(base, member) => {
+				match base.as_ref() {
+					// Anything unknown on the left of a dot is assumed to be a script name.
+					Expression::Ident(name) => {
+						let binding = self.lookup(name).unwrap_or(Binding::Script(name));
+						self.out.expressions.insert(base.as_ref(), binding);
+					}
+					_ => self.expression(base),
+				}
+
+				let binding = self.get(base).and_then(|b| self.member(b, member));
+				self.bind(expr, binding);
+			}
+
+			Expression::Call(target, args) => {
+				match target.as_ref() {
+					Expression::Ident(name) => {
+						let binding = self.out.symbols.function(name).map(Binding::callable);
+						self.bind(target, binding);
+					}
+					_ => self.expression(target),
+				}
+
+				for arg in args {
+					match arg {
+						Argument::Named(_, value) | Argument::Anonymous(value) => {
+							self.expression(value)
+						}
+					}
+				}
+			}
+
+			Expression::Addition(lhs, rhs)
+			| Expression::Subtraction(lhs, rhs)
+			| Expression::Multiplication(lhs, rhs)
+			| Expression::Division(lhs, rhs)
+			| Expression::Modulo(lhs, rhs)
+			| Expression::GreaterThan(lhs, rhs)
+			| Expression::LessThan(lhs, rhs)
+			| Expression::GreaterThanOrEqual(lhs, rhs)
+			| Expression::LessThanOrEqual(lhs, rhs)
+			| Expression::Equal(lhs, rhs)
+			| Expression::NotEqual(lhs, rhs)
+			| Expression::And(lhs, rhs)
+			| Expression::Or(lhs, rhs)
+			| Expression::BracketIndex(lhs, rhs) => {
+				self.expression(lhs);
+				self.expression(rhs);
+			}
+
+			Expression::Not(expr)
+			| Expression::Negate(expr)
+			| Expression::Cast(expr, _)
+			| Expression::Is(expr, _)
+			| Expression::Array(_, expr) => self.expression(expr),
+
+			Expression::Bool(_)
+			| Expression::String(_)
+			| Expression::Integer(_)
+			| Expression::Float(_)
+			| Expression::None
+			| Expression::Struct(_) => (),
+		}
+	}
+
+	fn get(&self, expr: &Expression) -> Option<Binding<'a>> {
+		self.out.get(expr)
+	}
+
+	fn target(&mut self, stmt: &'a Statement, name: &str) {
+		match self.lookup(name) {
+			Some(binding) => {
+				self.out.targets.insert(stmt, binding);
+			}
+			None => self.undeclared(name),
+		}
+	}
+
+	fn body(&mut self, body: &'a [Statement]) {
+		self.enter_scope();
+		for stmt in body {
+			self.statement(stmt);
+		}
+		self.exit_scope();
+	}
+
+	fn function(&mut self, parameters: &'a [Parameter], body: &'a [Statement]) {
+		self.enter_scope();
+		for param in parameters {
+			if let Some(value) = &param.2 {
+				self.expression(value);
+			}
+			self.declare_local(&param.1, Binding::Parameter(param));
+		}
+
+		for stmt in body {
+			self.statement(stmt);
+		}
+		self.exit_scope();
+	}
+
+	fn statement(&mut self, stmt: &'a Statement) {
+		let outer = std::mem::replace(&mut self.span, stmt.span());
+		self.statement_kind(stmt);
+		self.span = outer;
+	}
+
+	fn statement_kind(&mut self, stmt: &'a Statement) {
+		match stmt {
+			Statement::If {
+				cond,
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				self.expression(cond);
+				self.body(body);
+
+				for (cond, body) in elifs {
+					self.expression(cond);
+					self.body(body);
+				}
+
+				if let Some(body) = else_block {
+					self.body(body);
+				}
+			}
+
+			Statement::While { cond, body, .. } => {
+				self.expression(cond);
+				self.body(body);
+			}
+
+			Statement::Function {
+				parameters, body, ..
+			}
+			| Statement::Event {
+				parameters, body, ..
+			} => self.function(parameters, body),
+
+			Statement::NativeFunction { parameters, .. } => self.function(parameters, &[]),
+
+			Statement::Return { value, .. } => {
+				if let Some(value) = value {
+					self.expression(value);
+				}
+			}
+
+			Statement::PropertyFull { functions, .. } => {
+				self.statement(&functions.0);
+				if let Some(set) = &functions.1 {
+					self.statement(set);
+				}
+			}
+
+			Statement::PropertyAuto { value, .. } => {
+				if let Some(value) = value {
+					self.expression(value);
+				}
+			}
+
+			Statement::PropertyAutoConst { value, .. } => self.expression(value),
+
+			Statement::State { body, .. }
+			| Statement::Group {
+				properties: body, ..
+			} => {
+				for stmt in body {
+					self.statement(stmt);
+				}
+			}
+
+			Statement::Definition { name, value, .. } => {
+				self.expression(value);
+				if !self.scopes.is_empty() {
+					self.declare_local(name, Binding::Local(stmt));
+				}
+			}
+
+			Statement::Declaration { name, .. } => {
+				if !self.scopes.is_empty() {
+					self.declare_local(name, Binding::Local(stmt));
+				}
+			}
+
+			Statement::Assignment {
+				name,
+				indexes,
+				value,
+				..
+			} => {
+				self.target(stmt, name);
+				for index in indexes {
+					if let Index::Bracket(expr) = index {
+						self.expression(expr);
+					}
+				}
+				self.expression(value);
+			}
+
+			Statement::CompoundAssignment { name, value, .. } => {
+				self.target(stmt, name);
+				self.expression(value);
+			}
+
+			Statement::Expression { expr, .. } => self.expression(expr),
+
+			Statement::Struct { fields, .. } => {
+				for field in fields {
+					if let Some(value) = &field.2 {
+						self.expression(value);
+					}
+				}
+			}
+
+			Statement::Import { .. } => (),
+		}
+	}
+}
+
+/// Binds every name used in the script to its declaration.
+pub fn resolve<'a>(ast: &'a Ast, diagnostics: &mut Diagnostics) -> Resolution<'a> {
+	let symbols = Symbols::new(ast, diagnostics);
+
+	let mut resolver = Resolver {
+		ast,
+		scopes: vec![],
+		out: Resolution {
+			symbols,
+			unresolved: vec![],
+			expressions: HashMap::new(),
+			targets: HashMap::new(),
+		},
+		diagnostics,
+		span: ast.script_info.span,
+	};
+
+	for stmt in &ast.statements {
+		resolver.statement(stmt);
+	}
+
+	resolver.out
+}
@@ -0,0 +1,194 @@
+use super::inheritance::compare;
+use super::resolve::{key, Symbols};
+use super::*;
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::project::Project;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("{state}.{name} is not defined in the empty state")]
+	NotInEmptyState { state: String, name: String },
+
+	#[error("{state}.{name} does not match the signature of {name} in the empty state: {reason}")]
+	SignatureMismatch {
+		state: String,
+		name: String,
+		reason: String,
+	},
+
+	#[error("{name} cannot be declared inside of state {state}")]
+	DeclarationInState { state: String, name: String },
+
+	#[error("Only one state can be Auto, but {first} and {second} both are")]
+	MultipleAuto { first: String, second: String },
+
+	#[error("GotoState target {0} is not a state of this script or its parents")]
+	UnknownState(String),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::NotInEmptyState { .. } => "E0401",
+			Self::SignatureMismatch { .. } => "E0402",
+			Self::DeclarationInState { .. } => "E0403",
+			Self::MultipleAuto { .. } => "E0404",
+			Self::UnknownState(_) => "E0405",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+fn report(diagnostics: &mut Diagnostics, error: Error, span: Span) {
+	diagnostics.push(Diagnostic::from(error).with_span(span));
+}
+
+/// State name passed to a `GotoState("Name")` call on this script, if it is a literal.
+fn goto_target(expr: &Expression) -> Option<&str> {
+	let Expression::Call(target, args) = expr else {
+		return None;
+	};
+
+	// Other objects have states of their own, which can't be checked here.
+	let name = match target.as_ref() {
+		Expression::Ident(name) => name,
+		Expression::DotIndex(base, name) if matches!(base.as_ref(), Expression::SelfRef) => name,
+		_ => return None,
+	};
+
+	match args.as_slice() {
+		[arg] if name.eq_ignore_ascii_case("GotoState") => match arg.value() {
+			Expression::String(state) => Some(state.trim_matches('"')),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Checks the states of a loaded script, along with the states its `GotoState` calls refer to.
+pub fn check_states(project: &Project, name: &str, diagnostics: &mut Diagnostics) {
+	let chain = project.chain(name).collect::<Vec<_>>();
+	let Some(script) = chain.first() else {
+		return;
+	};
+
+	// Without every parent, a missing function or state could be in one that isn't loaded.
+	let complete = chain
+		.last()
+		.is_some_and(|s| s.ast.script_info.extended_type.is_none());
+
+	let symbols = chain
+		.iter()
+		.map(|s| Symbols::new(&s.ast, &mut Diagnostics::new()))
+		.collect::<Vec<_>>();
+
+	let mut auto: Option<(&String, Span)> = None;
+
+	for stmt in &script.ast.statements {
+		let Statement::State {
+			auto: is_auto,
+			name: state,
+			body,
+			..
+		} = stmt
+		else {
+			continue;
+		};
+
+		if *is_auto {
+			match auto {
+				Some((first, span)) => diagnostics.push(
+					Diagnostic::from(Error::MultipleAuto {
+						first: first.clone(),
+						second: state.clone(),
+					})
+					.with_span(stmt.span())
+					.with_label(span, None, "first Auto state"),
+				),
+				None => auto = Some((state, stmt.span())),
+			}
+		}
+
+		for item in body {
+			match item {
+				Statement::Function { name, .. }
+				| Statement::NativeFunction { name, .. }
+				| Statement::Event { name, .. } => {
+					let empty = symbols.iter().find_map(|s| s.functions.get(&key(name)));
+
+					match empty {
+						Some(empty) => {
+							if let Some(reason) = compare(item, empty) {
+								let error = Error::SignatureMismatch {
+									state: state.clone(),
+									name: name.clone(),
+									reason,
+								};
+								diagnostics.push(
+									Diagnostic::from(error).with_span(item.span()).with_label(
+										empty.span(),
+										None,
+										"empty state version",
+									),
+								);
+							}
+						}
+						None if complete => report(
+							diagnostics,
+							Error::NotInEmptyState {
+								state: state.clone(),
+								name: name.clone(),
+							},
+							item.span(),
+						),
+						None => (),
+					}
+				}
+
+				Statement::Declaration { name, .. }
+				| Statement::Definition { name, .. }
+				| Statement::PropertyFull { name, .. }
+				| Statement::PropertyAuto { name, .. }
+				| Statement::PropertyAutoConst { name, .. } => report(
+					diagnostics,
+					Error::DeclarationInState {
+						state: state.clone(),
+						name: name.clone(),
+					},
+					item.span(),
+				),
+
+				_ => (),
+			}
+		}
+	}
+
+	if complete {
+		let mut targets = vec![];
+		for stmt in &script.ast.statements {
+			stmt.visit(&mut |stmt| {
+				for expr in stmt.expressions() {
+					expr.visit(&mut |expr| {
+						targets.extend(goto_target(expr).map(|target| (target, stmt.span())))
+					});
+				}
+			});
+		}
+
+		for (target, span) in targets {
+			// The empty state is always valid.
+			let exists =
+				target.is_empty() || symbols.iter().any(|s| s.states.contains_key(&key(target)));
+
+			if !exists {
+				report(diagnostics, Error::UnknownState(target.to_owned()), span);
+			}
+		}
+	}
+}
@@ -0,0 +1,791 @@
+use super::calls::{external, match_arguments, state_function, ArgumentValue};
+use super::resolve::{key, Binding, Resolution};
+use super::*;
+use crate::compiler::{
+	conversion::{Conversion, Hierarchy},
+	types::Ty,
+};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::parser::Rule;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("expected {expected}, got {got}")]
+	Mismatch { expected: Ty, got: Ty },
+
+	#[error("cannot apply {op} to {lhs} and {rhs}")]
+	InvalidOperands { op: &'static str, lhs: Ty, rhs: Ty },
+
+	#[error("cannot apply {op} to {ty}")]
+	InvalidOperand { op: &'static str, ty: Ty },
+
+	#[error("{ty} has no member {member}")]
+	NoMember { ty: Ty, member: String },
+
+	#[error("cannot index into {0}")]
+	NotIndexable(Ty),
+
+	#[error("{0} is not a value")]
+	NotAValue(String),
+
+	#[error("cannot cast {from} to {to}")]
+	InvalidCast { from: Ty, to: Ty },
+
+	#[error("type of {0} is unknown, as it isn't declared in any loaded script")]
+	Unknown(String),
+
+	#[error("{name} could be a script or a property inherited from {parent}, which isn't loaded")]
+	Ambiguous { name: String, parent: String },
+
+	#[error("{0} is out of range for Int, which holds {min} to {max}", min = i32::MIN, max = i32::MAX)]
+	OutOfRange(i64),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Mismatch { .. } => "E0201",
+			Self::InvalidOperands { .. } => "E0202",
+			Self::InvalidOperand { .. } => "E0203",
+			Self::NoMember { .. } => "E0204",
+			Self::NotIndexable(_) => "E0205",
+			Self::NotAValue(_) => "E0206",
+			Self::InvalidCast { .. } => "E0207",
+			Self::Unknown(_) => "E0208",
+			Self::Ambiguous { .. } => "E0209",
+			Self::OutOfRange(_) => "E0210",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+/// Side table produced by [typecheck], mapping expressions to their inferred type.
+#[derive(Debug, Default)]
+pub struct Typing {
+	types: HashMap<*const Expression, Ty>,
+	/// Types of the variables assignments write to, before any index.
+	targets: HashMap<*const Statement, Ty>,
+	/// Types of what each index of an assignment's target refers to.
+	indexes: HashMap<*const Index, Ty>,
+	/// Names left of a dot that are properties inherited from a parent, rather than scripts.
+	inherited: HashSet<*const Expression>,
+	unknown: Diagnostics,
+}
+
+impl Typing {
+	/// Inferred type of an expression. Missing if it couldn't be known from this script alone.
+	pub fn get(&self, expr: &Expression) -> Option<&Ty> {
+		self.types.get(&(expr as *const _))
+	}
+
+	/// Type of the variable or property a [Statement::Assignment] or [Statement::CompoundAssignment] writes to.
+	pub fn target(&self, stmt: &Statement) -> Option<&Ty> {
+		self.targets.get(&(stmt as *const _))
+	}
+
+	/// Type of what an index of an assignment's target refers to, the last one being what is written to.
+	pub fn index(&self, index: &Index) -> Option<&Ty> {
+		self.indexes.get(&(index as *const _))
+	}
+
+	/// Whether a name that [resolve](super::resolve::resolve) took for a script is a property inherited from a parent.
+	pub fn is_inherited(&self, expr: &Expression) -> bool {
+		self.inherited.contains(&(expr as *const _))
+	}
+
+	/// Values whose type couldn't be inferred, which can't be compiled.
+	/// They aren't errors otherwise, as a script can use what it inherits without its parent being loaded.
+	pub fn unknown(&self) -> &Diagnostics {
+		&self.unknown
+	}
+}
+
+struct TypeChecker<'a, 'r> {
+	info: &'a ScriptInfo,
+	resolution: &'r Resolution<'a>,
+	hierarchy: &'a dyn Hierarchy,
+	return_type: Option<Ty>,
+	out: Typing,
+
+	diagnostics: &'r mut Diagnostics,
+	/// Span of the innermost statement being checked.
+	span: Span,
+}
+
+impl<'a, 'r> TypeChecker<'a, 'r> {
+	fn report(&mut self, error: Error) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(self.span));
+	}
+
+	/// Type of an Int literal, reporting it if it doesn't fit.
+	fn integer(&mut self, value: i64) -> Option<Ty> {
+		if i32::try_from(value).is_err() {
+			self.report(Error::OutOfRange(value));
+		}
+		Some(Ty::Int)
+	}
+
+	/// Records a value whose type couldn't be inferred.
+	fn unknown(&mut self, error: Error) {
+		self.out
+			.unknown
+			.push(Diagnostic::from(error).with_span(self.span));
+	}
+
+	/// Type of a property of `script`, following [Hierarchy::property].
+	/// Those of this script are known even if the hierarchy doesn't have it.
+	fn property(&self, script: &str, name: &str) -> Option<Option<Ty>> {
+		if script.eq_ignore_ascii_case(&self.info.script_name) {
+			if let Some(stmt) = self.resolution.symbols.properties.get(&key(name)) {
+				return Some(Binding::Property(stmt).ty().map(|ty| self.ty(ty)));
+			}
+		}
+		self.hierarchy.property(script, name)
+	}
+
+	/// Type of a property this script inherits, if it can be known.
+	fn inherited(&self, name: &str) -> Option<Option<Ty>> {
+		self.property(&self.info.script_name, name)
+	}
+
+	/// Type of a name left of a dot that this script doesn't declare,
+	/// which is a property if it inherits one by that name, or a script otherwise.
+	fn script_or_property(&mut self, expr: &'a Expression, name: &str) -> Option<Ty> {
+		match self.inherited(name) {
+			Some(Some(ty)) => {
+				self.out.inherited.insert(expr);
+				Some(ty)
+			}
+			Some(None) => Some(Ty::Object(name.to_owned())),
+			// Only scripts that were loaded are certain without the parent.
+			None => match &self.info.extended_type {
+				Some(parent) if self.hierarchy.extends(name, name) != Some(true) => {
+					self.unknown(Error::Ambiguous {
+						name: name.to_owned(),
+						parent: parent.clone(),
+					});
+					None
+				}
+				_ => Some(Ty::Object(name.to_owned())),
+			},
+		}
+	}
+
+	/// Resolves a type as written in the source, taking structs declared in this script into account.
+	fn ty(&self, ty: &str) -> Ty {
+		match Ty::parse(ty) {
+			Ty::Object(name) if self.resolution.symbols.structs.contains_key(&key(&name)) => {
+				Ty::Struct(name)
+			}
+			Ty::Array(inner) => match *inner {
+				Ty::Object(name) if self.resolution.symbols.structs.contains_key(&key(&name)) => {
+					Ty::Array(Box::new(Ty::Struct(name)))
+				}
+				inner => Ty::Array(Box::new(inner)),
+			},
+			ty => ty,
+		}
+	}
+
+	fn binding(&self, binding: Binding<'a>) -> Option<Ty> {
+		match binding {
+			Binding::Script(name) => Some(Ty::Object(name.to_owned())),
+			Binding::Import(Statement::Import { item, .. }) => Some(Ty::Object(item.clone())),
+			binding => binding.ty().map(|ty| self.ty(ty)),
+		}
+	}
+
+	fn expect(&mut self, got: Option<Ty>, expected: &Ty) {
+		if let Some(got) = got {
+			if !got.assignable_to(expected, self.hierarchy) {
+				self.report(Error::Mismatch {
+					expected: expected.clone(),
+					got,
+				});
+			}
+		}
+	}
+
+	fn arithmetic(&mut self, op: &'static str, lhs: Option<Ty>, rhs: Option<Ty>) -> Option<Ty> {
+		match (lhs?, rhs?) {
+			(Ty::String, _) | (_, Ty::String) if op == "+" => Some(Ty::String),
+			(Ty::Int, Ty::Int) => Some(Ty::Int),
+			// Only integers have a remainder.
+			(lhs, rhs) if lhs.is_numeric() && rhs.is_numeric() && op != "%" => Some(Ty::Float),
+			(lhs, rhs) => {
+				self.report(Error::InvalidOperands { op, lhs, rhs });
+				None
+			}
+		}
+	}
+
+	fn comparison(&mut self, op: &'static str, lhs: Option<Ty>, rhs: Option<Ty>) -> Option<Ty> {
+		if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+			let valid = match op {
+				"==" | "!=" => {
+					lhs.assignable_to(&rhs, self.hierarchy)
+						|| rhs.assignable_to(&lhs, self.hierarchy)
+				}
+				_ => {
+					(lhs.is_numeric() && rhs.is_numeric())
+						|| (lhs == Ty::String && rhs == Ty::String)
+				}
+			};
+
+			if !valid {
+				self.report(Error::InvalidOperands { op, lhs, rhs });
+			}
+		}
+
+		Some(Ty::Bool)
+	}
+
+	/// Checks the arguments of a call to `function`, whose types are resolved in this script if it is `local`.
+	fn arguments(&mut self, function: &'a Statement, args: &'a [Argument], local: bool) {
+		for arg in args {
+			self.expression(arg.value());
+		}
+
+		// Arity and naming errors are reported by resolve_calls.
+		let Some(call) = match_arguments(function, args, &mut vec![]) else {
+			return;
+		};
+
+		let parameters = match function {
+			Statement::Function { parameters, .. }
+			| Statement::NativeFunction { parameters, .. }
+			| Statement::Event { parameters, .. } => parameters,
+			_ => return,
+		};
+
+		for (param, value) in parameters.iter().zip(call.arguments) {
+			if let Some(ArgumentValue::Given(value)) = value {
+				let got = self.out.get(value).cloned();
+				let expected = match local {
+					true => self.ty(&param.0),
+					false => Ty::parse(&param.0),
+				};
+				self.expect(got, &expected);
+			}
+		}
+	}
+
+	fn call(&mut self, target: &'a Expression, args: &'a [Argument]) -> Option<Ty> {
+		let base = match target {
+			Expression::DotIndex(base, method) => {
+				let base = self.expression(base);
+
+				// Arrays have a few builtin methods.
+				if let Some(Ty::Array(_)) = base {
+					for arg in args {
+						self.expression(arg.value());
+					}
+
+					return match method.to_ascii_lowercase().as_str() {
+						"find" | "rfind" | "findstruct" | "rfindstruct" => Some(Ty::Int),
+						_ => Some(Ty::None),
+					};
+				}
+				base
+			}
+			Expression::Ident(_) => None,
+			target => self.expression(target),
+		};
+
+		match self.resolution.get(target) {
+			Some(Binding::Function(function) | Binding::Event(function)) => {
+				self.arguments(function, args, true);
+				match function {
+					Statement::Function { return_type, .. }
+					| Statement::NativeFunction { return_type, .. } => {
+						Some(return_type.as_deref().map_or(Ty::None, |ty| self.ty(ty)))
+					}
+					_ => Some(Ty::None),
+				}
+			}
+			_ => {
+				// Functions this script inherits or imports, or those of other scripts.
+				let script = &self.info.script_name;
+				// Ambiguous imports are reported by resolve_calls.
+				let declared = external(
+					script,
+					target,
+					base.as_ref(),
+					self.resolution,
+					self.hierarchy,
+					&mut vec![],
+				);
+				if let Some((_, function)) = declared {
+					self.arguments(function, args, false);
+					return match function {
+						Statement::Function { return_type, .. }
+						| Statement::NativeFunction { return_type, .. } => {
+							Some(return_type.as_deref().map_or(Ty::None, Ty::parse))
+						}
+						_ => Some(Ty::None),
+					};
+				}
+
+				for arg in args {
+					self.expression(arg.value());
+				}
+				state_function(target)
+			}
+		}
+	}
+
+	fn member(&mut self, expr: &'a Expression, base: &'a Expression, member: &str) -> Option<Ty> {
+		let base_ty = self.expression(base);
+		if let Some(binding) = self.resolution.get(expr) {
+			return match binding {
+				Binding::Function(_) | Binding::Event(_) => {
+					self.report(Error::NotAValue(member.to_owned()));
+					None
+				}
+				binding => self.binding(binding),
+			};
+		}
+
+		match base_ty {
+			Some(Ty::Array(_)) if member.eq_ignore_ascii_case("Length") => Some(Ty::Int),
+			// Structs that aren't bound by name, like `points[0].x`, are checked against their fields here.
+			Some(Ty::Struct(name)) => match self.resolution.symbols.field(&name, member) {
+				Some(field) => Some(self.ty(&field.0)),
+				None => {
+					self.report(Error::NoMember {
+						ty: Ty::Struct(name),
+						member: member.to_owned(),
+					});
+					None
+				}
+			},
+			Some(ty @ (Ty::Array(_) | Ty::Int | Ty::Float | Ty::Bool | Ty::String)) => {
+				self.report(Error::NoMember {
+					ty,
+					member: member.to_owned(),
+				});
+				None
+			}
+			Some(Ty::Object(script)) => self.property(&script, member).flatten(),
+			_ => None,
+		}
+	}
+
+	fn expression(&mut self, expr: &'a Expression) -> Option<Ty> {
+		let reported = (self.diagnostics.len(), self.out.unknown.len());
+		let ty = match expr {
+			Expression::Bool(_) => Some(Ty::Bool),
+			Expression::Integer(value) => self.integer(*value),
+			Expression::Float(_) => Some(Ty::Float),
+			Expression::String(_) => Some(Ty::String),
+			Expression::None => Some(Ty::None),
+
+			Expression::SelfRef | Expression::Parent => {
+				self.resolution.get(expr).and_then(|b| self.binding(b))
+			}
+
+			Expression::Ident(name) => match self.resolution.get(expr) {
+				Some(Binding::Function(_) | Binding::Event(_)) => {
+					self.report(Error::NotAValue(name.clone()));
+					None
+				}
+				Some(Binding::Script(script)) if script.eq_ignore_ascii_case(name) => {
+					self.script_or_property(expr, name)
+				}
+				Some(binding) => self.binding(binding),
+				None => self.inherited(name).flatten(),
+			},
+
+			Expression::Addition(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.arithmetic("+", lhs, rhs)
+			}
+			Expression::Subtraction(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.arithmetic("-", lhs, rhs)
+			}
+			Expression::Multiplication(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.arithmetic("*", lhs, rhs)
+			}
+			Expression::Division(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.arithmetic("/", lhs, rhs)
+			}
+			Expression::Modulo(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.arithmetic("%", lhs, rhs)
+			}
+
+			Expression::GreaterThan(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison(">", lhs, rhs)
+			}
+			Expression::LessThan(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison("<", lhs, rhs)
+			}
+			Expression::GreaterThanOrEqual(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison(">=", lhs, rhs)
+			}
+			Expression::LessThanOrEqual(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison("<=", lhs, rhs)
+			}
+			Expression::Equal(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison("==", lhs, rhs)
+			}
+			Expression::NotEqual(lhs, rhs) => {
+				let (lhs, rhs) = (self.expression(lhs), self.expression(rhs));
+				self.comparison("!=", lhs, rhs)
+			}
+
+			// Every type can be used as a condition.
+			Expression::And(lhs, rhs) | Expression::Or(lhs, rhs) => {
+				self.expression(lhs);
+				self.expression(rhs);
+				Some(Ty::Bool)
+			}
+			Expression::Not(expr) => {
+				self.expression(expr);
+				Some(Ty::Bool)
+			}
+
+			Expression::Negate(expr) => match expr.as_ref() {
+				// The literal is negated before it is checked, so the smallest Int can be written.
+				Expression::Integer(value) => self.integer(-value),
+				expr => match self.expression(expr) {
+					Some(ty) if ty.is_numeric() => Some(ty),
+					Some(ty) => {
+						self.report(Error::InvalidOperand { op: "-", ty });
+						None
+					}
+					None => None,
+				},
+			},
+
+			Expression::Cast(expr, ty) => {
+				let to = self.ty(ty);
+				if let Some(from) = self.expression(expr) {
+					if !Conversion::between(&from, &to, self.hierarchy).is_legal() {
+						self.report(Error::InvalidCast {
+							from,
+							to: to.clone(),
+						});
+					}
+				}
+				Some(to)
+			}
+
+			Expression::Is(expr, _) => {
+				self.expression(expr);
+				Some(Ty::Bool)
+			}
+
+			Expression::DotIndex(base, member) => self.member(expr, base, member),
+
+			Expression::BracketIndex(array, index) => {
+				let index = self.expression(index);
+				self.expect(index, &Ty::Int);
+
+				match self.expression(array) {
+					Some(Ty::Array(inner)) => Some(*inner),
+					Some(ty) => {
+						self.report(Error::NotIndexable(ty));
+						None
+					}
+					None => None,
+				}
+			}
+
+			Expression::Call(target, args) => self.call(target, args),
+
+			Expression::Array(ty, size) => {
+				let size = self.expression(size);
+				self.expect(size, &Ty::Int);
+				Some(Ty::Array(Box::new(self.ty(ty))))
+			}
+
+			Expression::Struct(ty) => Some(self.ty(ty)),
+		};
+
+		if let Some(ty) = &ty {
+			self.out.types.insert(expr, ty.clone());
+		}
+
+		// Only the innermost value is recorded, unless something else was wrong with it.
+		if ty.is_none() && reported == (self.diagnostics.len(), self.out.unknown.len()) {
+			let name = match expr {
+				Expression::Ident(name) | Expression::DotIndex(_, name) => Some(name),
+				Expression::Call(target, _) => match target.as_ref() {
+					Expression::Ident(name) | Expression::DotIndex(_, name) => Some(name),
+					_ => None,
+				},
+				_ => None,
+			};
+			if let Some(name) = name {
+				self.unknown(Error::Unknown(name.clone()));
+			}
+		}
+
+		ty
+	}
+
+	/// Type of the variable being assigned to, after following each of its indexes.
+	fn target(&mut self, stmt: &'a Statement, indexes: &'a [Index]) -> Option<Ty> {
+		let mut ty = match self.resolution.target(stmt) {
+			Some(binding) => self.binding(binding),
+			// Anything else is a property, possibly inherited.
+			None => {
+				let name = match stmt {
+					Statement::Assignment { name, .. }
+					| Statement::CompoundAssignment { name, .. } => name,
+					_ => return None,
+				};
+				let ty = self.inherited(name).flatten();
+				if ty.is_none() {
+					self.unknown(Error::Unknown(name.clone()));
+				}
+				ty
+			}
+		};
+		if let Some(ty) = &ty {
+			self.out.targets.insert(stmt, ty.clone());
+		}
+
+		for index in indexes {
+			ty = match (index, ty) {
+				(Index::Bracket(expr), ty) => {
+					let index = self.expression(expr);
+					self.expect(index, &Ty::Int);
+
+					match ty {
+						Some(Ty::Array(inner)) => Some(*inner),
+						Some(ty) => {
+							self.report(Error::NotIndexable(ty));
+							None
+						}
+						None => None,
+					}
+				}
+				(Index::Dot(member), Some(Ty::Struct(name))) => {
+					let field = self.resolution.symbols.field(&name, member);
+					if field.is_none() {
+						self.report(Error::NoMember {
+							ty: Ty::Struct(name),
+							member: member.clone(),
+						});
+					}
+					field.map(|f| self.ty(&f.0))
+				}
+				(Index::Dot(member), Some(Ty::Object(script))) => {
+					let ty = self.property(&script, member).flatten();
+					if ty.is_none() {
+						self.unknown(Error::Unknown(member.clone()));
+					}
+					ty
+				}
+				(Index::Dot(_), _) => None,
+			};
+
+			if let Some(ty) = &ty {
+				self.out.indexes.insert(index, ty.clone());
+			}
+		}
+
+		ty
+	}
+
+	fn body(&mut self, body: &'a [Statement]) {
+		for stmt in body {
+			self.statement(stmt);
+		}
+	}
+
+	fn parameters(&mut self, parameters: &'a [Parameter]) {
+		for param in parameters {
+			if let Some(value) = &param.2 {
+				let got = self.expression(value);
+				let expected = self.ty(&param.0);
+				self.expect(got, &expected);
+			}
+		}
+	}
+
+	fn statement(&mut self, stmt: &'a Statement) {
+		let outer = std::mem::replace(&mut self.span, stmt.span());
+		self.statement_kind(stmt);
+		self.span = outer;
+	}
+
+	fn statement_kind(&mut self, stmt: &'a Statement) {
+		match stmt {
+			Statement::If {
+				cond,
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				self.expression(cond);
+				self.body(body);
+
+				for (cond, body) in elifs {
+					self.expression(cond);
+					self.body(body);
+				}
+
+				if let Some(body) = else_block {
+					self.body(body);
+				}
+			}
+
+			Statement::While { cond, body, .. } => {
+				self.expression(cond);
+				self.body(body);
+			}
+
+			Statement::Function {
+				return_type,
+				parameters,
+				body,
+				..
+			} => {
+				self.parameters(parameters);
+
+				let outer = self
+					.return_type
+					.replace(return_type.as_deref().map_or(Ty::None, |ty| self.ty(ty)));
+				self.body(body);
+				self.return_type = outer;
+			}
+
+			Statement::Event {
+				parameters, body, ..
+			} => {
+				self.parameters(parameters);
+
+				let outer = self.return_type.replace(Ty::None);
+				self.body(body);
+				self.return_type = outer;
+			}
+
+			Statement::NativeFunction { parameters, .. } => self.parameters(parameters),
+
+			Statement::Return {
+				value: Some(value), ..
+			} => {
+				let got = self.expression(value);
+				if let Some(expected) = self.return_type.clone() {
+					// Returning a value from a function without a return type is reported elsewhere.
+					if expected != Ty::None {
+						self.expect(got, &expected);
+					}
+				}
+			}
+
+			Statement::Return { value: None, .. } => (),
+
+			Statement::PropertyFull { functions, .. } => {
+				self.statement(&functions.0);
+				if let Some(set) = &functions.1 {
+					self.statement(set);
+				}
+			}
+
+			Statement::PropertyAuto {
+				ty,
+				value: Some(value),
+				..
+			}
+			| Statement::PropertyAutoConst { ty, value, .. }
+			| Statement::Definition { ty, value, .. } => {
+				let got = self.expression(value);
+				let expected = self.ty(ty);
+				self.expect(got, &expected);
+			}
+
+			Statement::PropertyAuto { value: None, .. } | Statement::Declaration { .. } => (),
+
+			Statement::State { body, .. }
+			| Statement::Group {
+				properties: body, ..
+			} => self.body(body),
+
+			Statement::Assignment { indexes, value, .. } => {
+				let expected = self.target(stmt, indexes);
+				let got = self.expression(value);
+				if let Some(expected) = expected {
+					self.expect(got, &expected);
+				}
+			}
+
+			Statement::CompoundAssignment { op, value, .. } => {
+				let target = self.target(stmt, &[]);
+				let got = self.expression(value);
+
+				let op = match op {
+					Rule::op_add => "+",
+					Rule::op_sub => "-",
+					Rule::op_mul => "*",
+					Rule::op_div => "/",
+					_ => "%",
+				};
+
+				if let Some(result) = self.arithmetic(op, target.clone(), got) {
+					if let Some(target) = target {
+						self.expect(Some(result), &target);
+					}
+				}
+			}
+
+			Statement::Expression { expr, .. } => {
+				self.expression(expr);
+			}
+
+			Statement::Struct { fields, .. } => {
+				for field in fields {
+					if let Some(value) = &field.2 {
+						let got = self.expression(value);
+						let expected = self.ty(&field.0);
+						self.expect(got, &expected);
+					}
+				}
+			}
+
+			Statement::Import { .. } => (),
+		}
+	}
+}
+
+/// Infers the type of every expression and checks them against their declared types.
+/// Objects are converted following `hierarchy`, which is `&()` for a script on its own.
+pub fn typecheck<'a>(
+	ast: &'a Ast,
+	resolution: &Resolution<'a>,
+	hierarchy: &'a dyn Hierarchy,
+	diagnostics: &mut Diagnostics,
+) -> Typing {
+	let mut checker = TypeChecker {
+		info: &ast.script_info,
+		resolution,
+		hierarchy,
+		return_type: None,
+		out: Typing::default(),
+		diagnostics,
+		span: ast.script_info.span,
+	};
+
+	checker.body(&ast.statements);
+	checker.out
+}
@@ -0,0 +1,141 @@
+use super::*;
+use crate::compiler::flags::{Flags, Target};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("User flag {0} is not declared in the flags file")]
+	Unknown(String),
+
+	#[error("User flag {flag} cannot be used on {target}")]
+	NotAllowed { flag: String, target: Target },
+
+	#[error("Local variable {0} cannot have user flags")]
+	Local(String),
+}
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Unknown(_) => "E1101",
+			Self::NotAllowed { .. } => "E1102",
+			Self::Local(_) => "E1103",
+		}
+	}
+}
+
+impl From<Error> for Diagnostic {
+	fn from(error: Error) -> Self {
+		Diagnostic::error(error.code(), error.to_string())
+	}
+}
+
+struct UserFlags<'f, 'd> {
+	flags: &'f Flags,
+	diagnostics: &'d mut Diagnostics,
+}
+
+impl UserFlags<'_, '_> {
+	fn report(&mut self, error: Error, span: Span) {
+		self.diagnostics
+			.push(Diagnostic::from(error).with_span(span));
+	}
+
+	/// Checks flags written on a declaration that any of `targets` can have.
+	fn check(&mut self, names: &[String], targets: &[Target], span: Span) {
+		for name in names {
+			let flags = self.flags.resolve(name);
+			if flags.is_empty() {
+				self.report(Error::Unknown(name.clone()), span);
+				continue;
+			}
+
+			let allowed = flags
+				.iter()
+				.all(|flag| targets.iter().any(|t| flag.targets.contains(t)));
+			if !allowed {
+				let error = Error::NotAllowed {
+					flag: name.clone(),
+					target: targets[0],
+				};
+				self.report(error, span);
+			}
+		}
+	}
+
+	fn locals(&mut self, body: &[Statement]) {
+		for stmt in body {
+			stmt.visit(&mut |stmt| match stmt {
+				Statement::Declaration {
+					name, flags, span, ..
+				}
+				| Statement::Definition {
+					name, flags, span, ..
+				} if !flags.is_empty() => self.report(Error::Local(name.clone()), *span),
+				_ => (),
+			});
+		}
+	}
+
+	fn statement(&mut self, stmt: &Statement) {
+		let span = stmt.span();
+		match stmt {
+			Statement::Function { body, .. } => {
+				self.check(stmt.flags(), &[Target::Function], span);
+				self.locals(body);
+			}
+			Statement::Event { body, .. } => self.locals(body),
+			Statement::NativeFunction { .. } => {
+				self.check(stmt.flags(), &[Target::Function], span);
+			}
+
+			Statement::PropertyFull { functions, .. } => {
+				self.check(stmt.flags(), &[Target::Property], span);
+				self.statement(&functions.0);
+				if let Some(set) = &functions.1 {
+					self.statement(set);
+				}
+			}
+			// The flags of auto properties also go on the variables behind them.
+			Statement::PropertyAuto { .. } => {
+				self.check(stmt.flags(), &[Target::Property, Target::Variable], span);
+			}
+			Statement::PropertyAutoConst { .. } => {
+				self.check(stmt.flags(), &[Target::Property], span);
+			}
+
+			Statement::Definition { .. } | Statement::Declaration { .. } => {
+				self.check(stmt.flags(), &[Target::Variable], span);
+			}
+
+			Statement::Group { properties, .. } => {
+				self.check(stmt.flags(), &[Target::Group], span);
+				properties.iter().for_each(|stmt| self.statement(stmt));
+			}
+			Statement::State { body, .. } => body.iter().for_each(|stmt| self.statement(stmt)),
+			Statement::Struct { fields, .. } => {
+				self.check(stmt.flags(), &[Target::StructVar], span);
+				for field in fields {
+					self.check(&field.4, &[Target::StructVar], span);
+				}
+			}
+
+			_ => (),
+		}
+	}
+}
+
+/// Checks that user flags are declared by `flags`, and only used on what they can be put on.
+pub fn check_flags(ast: &Ast, flags: &Flags, diagnostics: &mut Diagnostics) {
+	let mut pass = UserFlags { flags, diagnostics };
+	pass.check(
+		&ast.script_info.flags,
+		&[Target::Script],
+		ast.script_info.span,
+	);
+
+	for stmt in &ast.statements {
+		pass.statement(stmt);
+	}
+}
@@ -0,0 +1,354 @@
+//! In-memory form of a compiled script, as written to a `.pex` file.
+//! https://en.uesp.net/wiki/Skyrim_Mod:Compiled_Script_File_Format
+//! Fields only Fallout 4 has are ignored when writing for Skyrim.
+
+use super::game::Game;
+use std::fmt;
+
+/// A compiled script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pex {
+	pub header: Header,
+	/// Left out of release builds.
+	pub debug_info: Option<DebugInfo>,
+	/// Names and bit indices of every user flag the compiler knew about.
+	pub user_flags: Vec<UserFlag>,
+	pub objects: Vec<Object>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+	/// Decides the byte order, version and layout of the file.
+	pub game: Game,
+	/// Seconds since the Unix epoch.
+	pub compilation_time: u64,
+	/// Path of the source file, relative to the source directory.
+	pub source: String,
+	pub user: String,
+	pub machine: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugInfo {
+	/// Seconds since the Unix epoch.
+	pub modification_time: u64,
+	pub functions: Vec<DebugFunction>,
+	/// Fallout 4 only.
+	pub property_groups: Vec<PropertyGroup>,
+	/// Fallout 4 only.
+	pub struct_orders: Vec<StructOrder>,
+}
+
+/// Source line of each instruction of a function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugFunction {
+	pub object: String,
+	pub state: String,
+	pub function: String,
+	pub kind: FunctionKind,
+	pub lines: Vec<u16>,
+}
+
+/// Properties of a `Group`, in the order they were declared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyGroup {
+	pub object: String,
+	/// Empty for properties outside of any group.
+	pub name: String,
+	pub doc: String,
+	pub user_flags: u32,
+	pub properties: Vec<String>,
+}
+
+/// Members of a struct, in the order they were declared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructOrder {
+	pub object: String,
+	pub name: String,
+	pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+	Normal = 0,
+	Getter = 1,
+	Setter = 2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserFlag {
+	pub name: String,
+	pub bit: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+	pub name: String,
+	/// Empty if the script doesn't extend another.
+	pub parent: String,
+	pub doc: String,
+	pub is_const: bool,
+	pub user_flags: u32,
+	/// Empty if there is no `Auto` state.
+	pub auto_state: String,
+	pub structs: Vec<Struct>,
+	pub variables: Vec<Variable>,
+	pub properties: Vec<Property>,
+	pub states: Vec<State>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+	pub name: String,
+	pub members: Vec<StructMember>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructMember {
+	pub name: String,
+	pub ty: String,
+	pub user_flags: u32,
+	pub value: Value,
+	pub is_const: bool,
+	pub doc: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+	pub name: String,
+	pub ty: String,
+	pub user_flags: u32,
+	pub value: Value,
+	pub is_const: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+	pub name: String,
+	pub ty: String,
+	pub doc: String,
+	pub user_flags: u32,
+	pub kind: PropertyKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyKind {
+	/// Reads and writes the named variable directly.
+	Auto(String),
+	Full {
+		get: Option<Box<Function>>,
+		set: Option<Box<Function>>,
+	},
+}
+
+impl Property {
+	pub const READ: u8 = 1;
+	pub const WRITE: u8 = 2;
+	pub const AUTO: u8 = 4;
+
+	pub fn flags(&self) -> u8 {
+		match &self.kind {
+			PropertyKind::Auto(_) => Self::READ | Self::WRITE | Self::AUTO,
+			PropertyKind::Full { get, set } => {
+				(get.is_some() as u8 * Self::READ) | (set.is_some() as u8 * Self::WRITE)
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct State {
+	/// Empty for the default state.
+	pub name: String,
+	pub functions: Vec<Function>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+	/// Not written for property getters and setters.
+	pub name: String,
+	pub return_type: String,
+	pub doc: String,
+	pub user_flags: u32,
+	pub is_global: bool,
+	pub is_native: bool,
+	/// Name and type of each parameter.
+	pub parameters: Vec<(String, String)>,
+	/// Name and type of each local variable.
+	pub locals: Vec<(String, String)>,
+	pub instructions: Vec<Instruction>,
+}
+
+impl Function {
+	pub const GLOBAL: u8 = 1;
+	pub const NATIVE: u8 = 2;
+
+	pub fn flags(&self) -> u8 {
+		(self.is_global as u8 * Self::GLOBAL) | (self.is_native as u8 * Self::NATIVE)
+	}
+}
+
+/// Operand of an instruction, or the initial value of a variable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	None,
+	/// Name of a variable, parameter or local.
+	Ident(String),
+	/// Without quotes.
+	String(String),
+	Int(i32),
+	Float(f32),
+	Bool(bool),
+}
+
+/// Written the way assembly listings spell operands.
+impl fmt::Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::None => write!(f, "None"),
+			Self::Ident(name) => write!(f, "{name}"),
+			Self::String(value) => write!(f, "{value:?}"),
+			Self::Int(value) => write!(f, "{value}"),
+			// Spelled the way MSVC prints them, which can't be mistaken for identifiers.
+			Self::Float(value) if value.is_nan() => write!(f, "1.#QNAN"),
+			Self::Float(value) if value.is_infinite() => {
+				write!(f, "{}1.#INF", if *value < 0.0 { "-" } else { "" })
+			}
+			Self::Float(value) => write!(f, "{value:?}"),
+			Self::Bool(value) => write!(f, "{}", if *value { "True" } else { "False" }),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+	pub opcode: Opcode,
+	/// Every operand, including those of variadic opcodes, without the count that precedes them.
+	pub args: Vec<Value>,
+}
+
+impl Instruction {
+	pub fn new(opcode: Opcode, args: Vec<Value>) -> Self {
+		Self { opcode, args }
+	}
+}
+
+impl fmt::Display for Instruction {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.opcode.name())?;
+		for arg in &self.args {
+			write!(f, " {arg}")?;
+		}
+		Ok(())
+	}
+}
+
+macro_rules! opcodes {
+	($($variant:ident = $code:literal, $name:literal, $args:literal $(+ $variadic:ident)?;)*) => {
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+		pub enum Opcode {
+			$($variant = $code,)*
+		}
+
+		impl Opcode {
+			pub fn code(self) -> u8 {
+				self as u8
+			}
+
+			pub fn from_code(code: u8) -> Option<Self> {
+				match code {
+					$($code => Some(Self::$variant),)*
+					_ => None,
+				}
+			}
+
+			/// Mnemonic used in assembly listings.
+			pub fn name(self) -> &'static str {
+				match self {
+					$(Self::$variant => $name,)*
+				}
+			}
+
+			/// Finds an opcode by its mnemonic, in any case.
+			pub fn from_name(name: &str) -> Option<Self> {
+				match name.to_ascii_lowercase().as_str() {
+					$($name => Some(Self::$variant),)*
+					_ => None,
+				}
+			}
+
+			/// Number of operands before any variadic ones.
+			pub fn fixed_args(self) -> usize {
+				match self {
+					$(Self::$variant => $args,)*
+				}
+			}
+
+			/// Whether the opcode takes any number of extra operands, preceded by their count.
+			pub fn is_variadic(self) -> bool {
+				match self {
+					$(Self::$variant => opcodes!(@variadic $($variadic)?),)*
+				}
+			}
+		}
+	};
+	(@variadic variadic) => { true };
+	(@variadic) => { false };
+}
+
+opcodes! {
+	Nop = 0, "nop", 0;
+	IAdd = 1, "iadd", 3;
+	FAdd = 2, "fadd", 3;
+	ISub = 3, "isub", 3;
+	FSub = 4, "fsub", 3;
+	IMul = 5, "imul", 3;
+	FMul = 6, "fmul", 3;
+	IDiv = 7, "idiv", 3;
+	FDiv = 8, "fdiv", 3;
+	IMod = 9, "imod", 3;
+	Not = 10, "not", 2;
+	INeg = 11, "ineg", 2;
+	FNeg = 12, "fneg", 2;
+	Assign = 13, "assign", 2;
+	Cast = 14, "cast", 2;
+	CmpEq = 15, "cmp_eq", 3;
+	CmpLt = 16, "cmp_lt", 3;
+	CmpLe = 17, "cmp_le", 3;
+	CmpGt = 18, "cmp_gt", 3;
+	CmpGe = 19, "cmp_ge", 3;
+	Jmp = 20, "jmp", 1;
+	JmpT = 21, "jmpt", 2;
+	JmpF = 22, "jmpf", 2;
+	CallMethod = 23, "callmethod", 3 + variadic;
+	CallParent = 24, "callparent", 2 + variadic;
+	CallStatic = 25, "callstatic", 3 + variadic;
+	Return = 26, "return", 1;
+	StrCat = 27, "strcat", 3;
+	PropGet = 28, "propget", 3;
+	PropSet = 29, "propset", 3;
+	ArrayCreate = 30, "array_create", 2;
+	ArrayLength = 31, "array_length", 2;
+	ArrayGetElement = 32, "array_getelement", 3;
+	ArraySetElement = 33, "array_setelement", 3;
+	ArrayFindElement = 34, "array_findelement", 4;
+	ArrayRFindElement = 35, "array_rfindelement", 4;
+	Is = 36, "is", 3;
+	StructCreate = 37, "struct_create", 1;
+	StructGet = 38, "struct_get", 3;
+	StructSet = 39, "struct_set", 3;
+	ArrayFindStruct = 40, "array_findstruct", 5;
+	ArrayRFindStruct = 41, "array_rfindstruct", 5;
+	ArrayAdd = 42, "array_add", 3;
+	ArrayInsert = 43, "array_insert", 3;
+	ArrayRemoveLast = 44, "array_removelast", 1;
+	ArrayRemove = 45, "array_remove", 3;
+	ArrayClear = 46, "array_clear", 1;
+}
+
+impl Opcode {
+	/// Whether the opcode exists in the bytecode of `game`.
+	pub fn is_supported(self, game: Game) -> bool {
+		game == Game::Fallout4 || self.code() <= Self::ArrayRFindElement.code()
+	}
+}
@@ -0,0 +1,82 @@
+use super::conversion::{Conversion, Hierarchy};
+use std::fmt;
+
+/// A resolved Papyrus type.
+#[derive(Debug, Clone)]
+pub enum Ty {
+	None,
+	Bool,
+	Int,
+	Float,
+	String,
+	/// Fallout 4's variant type.
+	Var,
+	/// Any script, by name.
+	Object(String),
+	Struct(String),
+	Array(Box<Ty>),
+}
+
+impl Ty {
+	/// Parses a type as written in source, without knowing which names are structs.
+	pub fn parse(ty: &str) -> Self {
+		match ty.strip_suffix("[]") {
+			Some(inner) => Self::Array(Box::new(Self::parse(inner.trim_end()))),
+			None => match ty.to_ascii_lowercase().as_str() {
+				"none" => Self::None,
+				"bool" => Self::Bool,
+				"int" => Self::Int,
+				"float" => Self::Float,
+				"string" => Self::String,
+				"var" => Self::Var,
+				_ => Self::Object(ty.to_owned()),
+			},
+		}
+	}
+
+	pub fn is_numeric(&self) -> bool {
+		matches!(self, Self::Int | Self::Float)
+	}
+
+	/// Whether this is a reference type, which can hold `None`.
+	pub fn is_nullable(&self) -> bool {
+		matches!(
+			self,
+			Self::Object(_) | Self::Struct(_) | Self::Array(_) | Self::Var
+		)
+	}
+
+	/// Whether a value of this type can be implicitly assigned to `to`.
+	pub fn assignable_to(&self, to: &Ty, hierarchy: &dyn Hierarchy) -> bool {
+		Conversion::between(self, to, hierarchy).is_implicit()
+	}
+}
+
+impl PartialEq for Ty {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Object(a), Self::Object(b)) | (Self::Struct(a), Self::Struct(b)) => {
+				a.eq_ignore_ascii_case(b)
+			}
+			(Self::Array(a), Self::Array(b)) => a == b,
+			(a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
+		}
+	}
+}
+
+impl Eq for Ty {}
+
+impl fmt::Display for Ty {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::None => write!(f, "None"),
+			Self::Bool => write!(f, "Bool"),
+			Self::Int => write!(f, "Int"),
+			Self::Float => write!(f, "Float"),
+			Self::String => write!(f, "String"),
+			Self::Var => write!(f, "Var"),
+			Self::Object(name) | Self::Struct(name) => write!(f, "{name}"),
+			Self::Array(inner) => write!(f, "{inner}[]"),
+		}
+	}
+}
@@ -0,0 +1,227 @@
+//! Errors and warnings reported by the parser and compiler passes, with enough information to point at the source.
+
+use crate::parser::ast::Span;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+	Warning,
+	Error,
+}
+
+impl fmt::Display for Severity {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Warning => write!(f, "warning"),
+			Self::Error => write!(f, "error"),
+		}
+	}
+}
+
+/// Additional location related to a diagnostic, possibly in another script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+	pub span: Span,
+	/// [None] if it is in the same file as the diagnostic.
+	pub file: Option<PathBuf>,
+	pub message: String,
+}
+
+/// Replacing the source at `span` with `replacement` would resolve the diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+	pub message: String,
+	pub span: Span,
+	pub replacement: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	/// Stable code identifying the kind of diagnostic, like `E0101`.
+	pub code: &'static str,
+	pub message: String,
+	/// Where the problem is, if it can be pinned to a place in the source.
+	pub span: Option<Span>,
+	pub labels: Vec<Label>,
+	pub notes: Vec<String>,
+	pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+	pub fn new(severity: Severity, code: &'static str, message: impl Into<String>) -> Self {
+		Self {
+			severity,
+			code,
+			message: message.into(),
+			span: None,
+			labels: vec![],
+			notes: vec![],
+			fix: None,
+		}
+	}
+
+	pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+		Self::new(Severity::Error, code, message)
+	}
+
+	pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+		Self::new(Severity::Warning, code, message)
+	}
+
+	pub fn with_span(mut self, span: Span) -> Self {
+		self.span = Some(span);
+		self
+	}
+
+	pub fn with_label(
+		mut self,
+		span: Span,
+		file: Option<PathBuf>,
+		message: impl Into<String>,
+	) -> Self {
+		self.labels.push(Label {
+			span,
+			file,
+			message: message.into(),
+		});
+		self
+	}
+
+	pub fn with_note(mut self, note: impl Into<String>) -> Self {
+		self.notes.push(note.into());
+		self
+	}
+
+	pub fn with_fix(
+		mut self,
+		message: impl Into<String>,
+		span: Span,
+		replacement: impl Into<String>,
+	) -> Self {
+		self.fix = Some(Fix {
+			message: message.into(),
+			span,
+			replacement: replacement.into(),
+		});
+		self
+	}
+
+	pub fn is_error(&self) -> bool {
+		self.severity == Severity::Error
+	}
+}
+
+impl fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+
+		if let Some(span) = self.span {
+			write!(f, "\n  --> {span}")?;
+		}
+
+		for label in &self.labels {
+			match &label.file {
+				Some(file) => write!(
+					f,
+					"\n  --> {}:{}: {}",
+					file.display(),
+					label.span,
+					label.message
+				)?,
+				None => write!(f, "\n  --> {}: {}", label.span, label.message)?,
+			}
+		}
+
+		for note in &self.notes {
+			write!(f, "\n  = note: {note}")?;
+		}
+
+		if let Some(fix) = &self.fix {
+			write!(f, "\n  = help: {}: `{}`", fix.message, fix.replacement)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Collects the diagnostics of every pass, in the order they were reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics(Vec<Diagnostic>);
+
+impl Diagnostics {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn push(&mut self, diagnostic: Diagnostic) {
+		self.0.push(diagnostic);
+	}
+
+	pub fn extend(&mut self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+		self.0.extend(diagnostics);
+	}
+
+	pub fn has_errors(&self) -> bool {
+		self.0.iter().any(Diagnostic::is_error)
+	}
+
+	pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+		self.0.iter().filter(|d| d.is_error())
+	}
+
+	pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+		self.0.iter().filter(|d| !d.is_error())
+	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+		self.0.iter()
+	}
+
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
+impl fmt::Display for Diagnostics {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, diagnostic) in self.0.iter().enumerate() {
+			if i > 0 {
+				writeln!(f)?;
+			}
+			write!(f, "{diagnostic}")?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for Diagnostics {}
+
+impl From<Diagnostic> for Diagnostics {
+	fn from(diagnostic: Diagnostic) -> Self {
+		Self(vec![diagnostic])
+	}
+}
+
+impl IntoIterator for Diagnostics {
+	type Item = Diagnostic;
+	type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+	type Item = &'a Diagnostic;
+	type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.0.iter()
+	}
+}
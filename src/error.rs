@@ -4,6 +4,12 @@ use thiserror::Error;
 pub enum Error {
 	#[error("Parsing error: {0}")]
 	Parsing(#[from] crate::parser::Error),
+
+	#[error("Project error: {0}")]
+	Project(#[from] crate::project::Error),
+
+	#[error("{0}")]
+	Diagnostics(#[from] crate::diagnostics::Diagnostics),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
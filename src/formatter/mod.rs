@@ -15,6 +15,8 @@ impl Format for Expression {
 			Self::Float(f) => f.to_string(),
 			Self::String(s) => s,
 			Self::Ident(i) => i,
+			Self::SelfRef => String::from("Self"),
+			Self::Parent => String::from("Parent"),
 			Self::None => String::from("None"),
 
 			Self::Is(expr, ty) => format!("{} is {ty}", expr.format()),
@@ -40,6 +42,7 @@ impl Format for Expression {
 			Self::Subtraction(lhs, rhs) => format!("{} - {}", lhs.format(), rhs.format()),
 			Self::Multiplication(lhs, rhs) => format!("{} * {}", lhs.format(), rhs.format()),
 			Self::Division(lhs, rhs) => format!("{} / {}", lhs.format(), rhs.format()),
+			Self::Modulo(lhs, rhs) => format!("{} % {}", lhs.format(), rhs.format()),
 
 			Self::Equal(lhs, rhs) => format!("{} == {}", lhs.format(), rhs.format()),
 			Self::NotEqual(lhs, rhs) => format!("{} != {}", lhs.format(), rhs.format()),
@@ -121,6 +124,7 @@ impl Format for Statement {
 				body,
 				elifs,
 				else_block,
+				..
 			} => {
 				let formatted_elifs = if elifs.is_empty() {
 					String::new()
@@ -142,13 +146,14 @@ impl Format for Statement {
 				)
 			}
 
-			Statement::While { cond, body } => {
+			Statement::While { cond, body, .. } => {
 				format!("while {}\n\t{}\nendwhile", cond.format(), body.format())
 			}
 			Statement::Assignment {
 				name,
 				indexes,
 				value,
+				..
 			} => {
 				if indexes.is_empty() {
 					format!("{name} = {}", value.format())
@@ -162,23 +167,27 @@ impl Format for Statement {
 				name,
 				parameters,
 				body,
+				is_global,
+				..
 			} => match return_type {
 				Some(ret) => format!(
-					"{ret} function {name}({})\n\t{}\nendfunction",
+					"{ret} function {name}({}){}\n\t{}\nendfunction",
 					parameters
 						.into_iter()
 						.map(Format::format)
 						.collect::<Vec<_>>()
 						.join(", "),
+					if is_global { " global" } else { "" },
 					body.format()
 				),
 				None => format!(
-					"function {name}({})\n\t{}\nendfunction",
+					"function {name}({}){}\n\t{}\nendfunction",
 					parameters
 						.into_iter()
 						.map(Format::format)
 						.collect::<Vec<_>>()
 						.join(", "),
+					if is_global { " global" } else { "" },
 					body.format()
 				),
 			},
@@ -187,32 +196,37 @@ impl Format for Statement {
 				return_type,
 				name,
 				parameters,
+				is_global,
+				..
 			} => match return_type {
 				Some(ret) => format!(
-					"{ret} function {name}({}) native",
+					"{ret} function {name}({}){} native",
 					parameters
 						.into_iter()
 						.map(Format::format)
 						.collect::<Vec<_>>()
-						.join(", ")
+						.join(", "),
+					if is_global { " global" } else { "" }
 				),
 				None => format!(
-					"function {name}({}) native",
+					"function {name}({}){} native",
 					parameters
 						.into_iter()
 						.map(Format::format)
 						.collect::<Vec<_>>()
-						.join(", ")
+						.join(", "),
+					if is_global { " global" } else { "" }
 				),
 			},
 
-			Statement::Return { value } => {
+			Statement::Return { value, .. } => {
 				format!("return {}", value.map(Format::format).unwrap_or_default())
 			}
 			Statement::Event {
 				name,
 				parameters,
 				body,
+				..
 			} => format!(
 				"event {name}({}) {} endevent",
 				parameters
@@ -227,20 +241,27 @@ impl Format for Statement {
 				ty,
 				name,
 				functions,
+				..
 			} => format!(
 				"{ty} property {name} {} {} endproperty",
 				functions.0.format(),
 				functions.1.map(|x| x.format()).unwrap_or_default()
 			),
-			Statement::PropertyAuto { ty, name, value } => format!(
+			Statement::PropertyAuto {
+				ty, name, value, ..
+			} => format!(
 				"{ty} property {name} = {} auto",
 				value.map(Format::format).unwrap_or_default()
 			),
-			Statement::PropertyAutoConst { ty, name, value } => {
+			Statement::PropertyAutoConst {
+				ty, name, value, ..
+			} => {
 				format!("{ty} property {name} = {} AutoReadOnly", value.format())
 			}
 
-			Statement::State { auto, name, body } => {
+			Statement::State {
+				auto, name, body, ..
+			} => {
 				if auto {
 					format!("state {}\n\t{}\nendstate", name, body.format())
 				} else {
@@ -248,22 +269,28 @@ impl Format for Statement {
 				}
 			}
 
-			Statement::Definition { ty, name, value } => {
+			Statement::Definition {
+				ty, name, value, ..
+			} => {
 				format!("{ty} {name} = {}", value.format())
 			}
-			Statement::Declaration { ty, name } => format!("{ty} {name}"),
-			Statement::Group { name, properties } => {
+			Statement::Declaration { ty, name, .. } => format!("{ty} {name}"),
+			Statement::Group {
+				name, properties, ..
+			} => {
 				format!("group {name} {} endgroup", properties.format())
 			}
-			Statement::CompoundAssignment { name, op, value } => {
+			Statement::CompoundAssignment {
+				name, op, value, ..
+			} => {
 				format!("{name} {}= {}", op.format(), value.format())
 			}
-			Statement::Struct { name, fields } => {
+			Statement::Struct { name, fields, .. } => {
 				format!("struct {}\n\t{}\nendstruct", name, fields.format())
 			}
-			Statement::Import { item } => format!("import {item}"),
+			Statement::Import { item, .. } => format!("import {item}"),
 
-			Statement::Expression { expr } => expr.format(),
+			Statement::Expression { expr, .. } => expr.format(),
 		}
 	}
 }
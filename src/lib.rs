@@ -4,6 +4,9 @@ extern crate pest_derive;
 pub mod error;
 pub use error::Error;
 
+pub mod diagnostics;
+pub use diagnostics::{Diagnostic, Diagnostics};
+
 pub mod formatter;
 pub use formatter::Format;
 
@@ -11,6 +14,9 @@ pub mod optimizer;
 pub use optimizer::optimize;
 
 pub mod parser;
-pub use parser::{parse_module, PestParser};
+pub use parser::{parse, parse_module, PestParser};
 
 pub mod compiler;
+
+pub mod project;
+pub use project::Project;
@@ -1,4 +1,5 @@
 use super::*;
+use crate::compiler::{conversion, Ty};
 
 pub struct ConstEval;
 
@@ -12,7 +13,7 @@ impl Optimizer for ConstEval {
 			Statement::Assignment { value, .. } => Self::expression(value),
 			Statement::CompoundAssignment { value, .. } => Self::expression(value),
 
-			Statement::Expression { expr } => Self::expression(expr),
+			Statement::Expression { expr, .. } => Self::expression(expr),
 			_ => (),
 		}
 	}
@@ -157,6 +158,11 @@ impl Optimizer for ConstEval {
 				_ => Self::expression(unary),
 			},
 
+			Expression::Cast(value, ty) => match conversion::fold(value, &Ty::parse(ty)) {
+				Some(folded) => *expr = folded,
+				None => Self::expression(value),
+			},
+
 			_ => (),
 		}
 	}
@@ -1,13 +1,50 @@
-#[derive(Debug, Default)]
+/// Location of a node in the source it was parsed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span {
+	/// Byte offsets
+	pub start: usize,
+	pub end: usize,
+
+	/// 1-based line and column of `start`
+	pub line: usize,
+	pub column: usize,
+}
+
+impl std::fmt::Display for Span {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}", self.line, self.column)
+	}
+}
+
+impl From<pest::Span<'_>> for Span {
+	fn from(span: pest::Span<'_>) -> Self {
+		let (line, column) = span.start_pos().line_col();
+		Self {
+			start: span.start(),
+			end: span.end(),
+			line,
+			column,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct ScriptInfo {
 	pub script_name: String,
 
 	pub extended_type: Option<String>,
 	pub is_conditional: bool,
+	pub is_const: bool,
+	pub is_native: bool,
+	/// User flags, as written.
+	pub flags: Vec<String>,
+
+	/// Span of the `ScriptName` header.
+	pub span: Span,
 }
 
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Ast {
 	pub script_info: ScriptInfo,
 	pub statements: Vec<Statement>,
@@ -16,7 +53,7 @@ pub struct Ast {
 pub type Type = String;
 
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Statement {
 	/// Vector of conditions and statements.
 	/// Condition is None in case of `else`.
@@ -27,11 +64,13 @@ pub enum Statement {
 		elifs: Vec<(Expression, Vec<Self>)>,
 
 		else_block: Option<Vec<Self>>,
+		span: Span,
 	},
 
 	While {
 		cond: Expression,
 		body: Vec<Self>,
+		span: Span,
 	},
 
 	Function {
@@ -39,93 +78,136 @@ pub enum Statement {
 		name: String,
 		parameters: Vec<Parameter>,
 		body: Vec<Self>,
+		/// Called on the script rather than an instance of it.
+		is_global: bool,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	NativeFunction {
 		return_type: Option<Type>,
 		name: String,
 		parameters: Vec<Parameter>,
+		is_global: bool,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	Return {
 		value: Option<Expression>,
+		span: Span,
 	},
 
 	Event {
 		name: String,
 		parameters: Vec<Parameter>,
 		body: Vec<Self>,
+		span: Span,
 	},
 
 	PropertyFull {
 		ty: Type,
 		name: String,
 		functions: (Box<Self>, Option<Box<Self>>),
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	PropertyAuto {
 		ty: Type,
 		name: String,
 		value: Option<Expression>,
+		/// Whether the variable behind it can't change after it is first set.
+		is_const: bool,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	PropertyAutoConst {
 		ty: Type,
 		name: String,
 		value: Expression,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	State {
 		auto: bool,
 		name: String,
 		body: Vec<Self>,
+		span: Span,
 	},
 
 	Definition {
 		ty: Type,
 		name: String,
 		value: Expression,
+		/// Whether it can't change after it is first set.
+		is_const: bool,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	Declaration {
 		ty: Type,
 		name: String,
+		/// Whether it can't change after it is first set.
+		is_const: bool,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	Group {
 		name: String,
 		properties: Vec<Self>,
+		/// User flags, as written.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	Assignment {
 		name: String,
 		indexes: Vec<Index>,
 		value: Expression,
+		span: Span,
 	},
 
 	CompoundAssignment {
 		name: String,
 		op: super::Rule,
 		value: Expression,
+		span: Span,
 	},
 
 	/// Certain expressions (function calls) can be used as statements.
 	Expression {
 		expr: Expression,
+		span: Span,
 	},
 
 	Struct {
 		name: String,
 		fields: Vec<Field>,
+		/// User flags, as written. The PEX has none for structs, so they go on each member.
+		flags: Vec<String>,
+		span: Span,
 	},
 
 	/// Import ObjectReference
 	Import {
 		item: String,
+		span: Span,
 	},
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Index {
 	Dot(String),
 	Bracket(Expression),
@@ -146,6 +228,9 @@ pub enum Expression {
 	/// /
 	Division(Box<Self>, Box<Self>),
 
+	/// %
+	Modulo(Box<Self>, Box<Self>),
+
 	/// >
 	GreaterThan(Box<Self>, Box<Self>),
 
@@ -194,6 +279,12 @@ pub enum Expression {
 	/// Hello
 	Ident(String),
 
+	/// Self, the instance the current function was called on
+	SelfRef,
+
+	/// Parent, for calling the version of a function this one overrides
+	Parent,
+
 	/// True or false
 	Bool(bool),
 
@@ -216,7 +307,7 @@ pub enum Expression {
 	Struct(Type),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Parameter(pub Type, pub String, pub Option<Expression>);
 
 #[derive(Debug, Clone)]
@@ -225,5 +316,202 @@ pub enum Argument {
 	Anonymous(Expression),
 }
 
-#[derive(Debug)]
-pub struct Field(pub Type, pub String, pub Option<Expression>);
+impl Argument {
+	pub fn value(&self) -> &Expression {
+		match self {
+			Self::Named(_, value) | Self::Anonymous(value) => value,
+		}
+	}
+}
+
+/// Type, name, default value, whether it is const and user flags of a struct member.
+#[derive(Debug, Clone)]
+pub struct Field(
+	pub Type,
+	pub String,
+	pub Option<Expression>,
+	pub bool,
+	pub Vec<String>,
+);
+
+impl Statement {
+	pub fn span(&self) -> Span {
+		match self {
+			Self::If { span, .. }
+			| Self::While { span, .. }
+			| Self::Function { span, .. }
+			| Self::NativeFunction { span, .. }
+			| Self::Return { span, .. }
+			| Self::Event { span, .. }
+			| Self::PropertyFull { span, .. }
+			| Self::PropertyAuto { span, .. }
+			| Self::PropertyAutoConst { span, .. }
+			| Self::State { span, .. }
+			| Self::Definition { span, .. }
+			| Self::Declaration { span, .. }
+			| Self::Group { span, .. }
+			| Self::Assignment { span, .. }
+			| Self::CompoundAssignment { span, .. }
+			| Self::Expression { span, .. }
+			| Self::Struct { span, .. }
+			| Self::Import { span, .. } => *span,
+		}
+	}
+
+	/// Name of whatever this statement declares.
+	pub fn name(&self) -> Option<&str> {
+		match self {
+			Self::Function { name, .. }
+			| Self::NativeFunction { name, .. }
+			| Self::Event { name, .. }
+			| Self::PropertyFull { name, .. }
+			| Self::PropertyAuto { name, .. }
+			| Self::PropertyAutoConst { name, .. }
+			| Self::State { name, .. }
+			| Self::Definition { name, .. }
+			| Self::Declaration { name, .. }
+			| Self::Group { name, .. }
+			| Self::Struct { name, .. } => Some(name),
+			Self::Import { item, .. } => Some(item),
+			_ => None,
+		}
+	}
+
+	/// User flags written on whatever this statement declares.
+	pub fn flags(&self) -> &[String] {
+		match self {
+			Self::Function { flags, .. }
+			| Self::NativeFunction { flags, .. }
+			| Self::PropertyFull { flags, .. }
+			| Self::PropertyAuto { flags, .. }
+			| Self::PropertyAutoConst { flags, .. }
+			| Self::Definition { flags, .. }
+			| Self::Declaration { flags, .. }
+			| Self::Group { flags, .. }
+			| Self::Struct { flags, .. } => flags,
+			_ => &[],
+		}
+	}
+
+	/// Expressions owned by this statement, not including those of nested statements.
+	pub fn expressions(&self) -> Vec<&Expression> {
+		match self {
+			Self::If { cond, elifs, .. } => std::iter::once(cond)
+				.chain(elifs.iter().map(|(cond, _)| cond))
+				.collect(),
+			Self::While { cond, .. } => vec![cond],
+			Self::Function { parameters, .. }
+			| Self::NativeFunction { parameters, .. }
+			| Self::Event { parameters, .. } => parameters.iter().filter_map(|p| p.2.as_ref()).collect(),
+			Self::Return { value, .. } | Self::PropertyAuto { value, .. } => value.iter().collect(),
+			Self::PropertyAutoConst { value, .. }
+			| Self::Definition { value, .. }
+			| Self::CompoundAssignment { value, .. } => vec![value],
+			Self::Assignment { indexes, value, .. } => indexes
+				.iter()
+				.filter_map(|index| match index {
+					Index::Bracket(expr) => Some(expr),
+					Index::Dot(_) => None,
+				})
+				.chain(std::iter::once(value))
+				.collect(),
+			Self::Expression { expr, .. } => vec![expr],
+			Self::Struct { fields, .. } => fields.iter().filter_map(|f| f.2.as_ref()).collect(),
+			Self::PropertyFull { .. }
+			| Self::State { .. }
+			| Self::Declaration { .. }
+			| Self::Group { .. }
+			| Self::Import { .. } => vec![],
+		}
+	}
+
+	/// Calls `f` on this statement and every statement nested inside of it.
+	pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Statement)) {
+		f(self);
+
+		match self {
+			Self::If {
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				body.iter().for_each(|s| s.visit(f));
+				for (_, body) in elifs {
+					body.iter().for_each(|s| s.visit(f));
+				}
+				if let Some(body) = else_block {
+					body.iter().for_each(|s| s.visit(f));
+				}
+			}
+
+			Self::While { body, .. }
+			| Self::Function { body, .. }
+			| Self::Event { body, .. }
+			| Self::State { body, .. }
+			| Self::Group {
+				properties: body, ..
+			} => body.iter().for_each(|s| s.visit(f)),
+
+			Self::PropertyFull { functions, .. } => {
+				functions.0.visit(f);
+				if let Some(set) = &functions.1 {
+					set.visit(f);
+				}
+			}
+
+			_ => (),
+		}
+	}
+}
+
+impl Expression {
+	/// Calls `f` on this expression and every expression nested inside of it.
+	pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Expression)) {
+		f(self);
+
+		match self {
+			Self::Addition(lhs, rhs)
+			| Self::Subtraction(lhs, rhs)
+			| Self::Multiplication(lhs, rhs)
+			| Self::Division(lhs, rhs)
+			| Self::Modulo(lhs, rhs)
+			| Self::GreaterThan(lhs, rhs)
+			| Self::LessThan(lhs, rhs)
+			| Self::GreaterThanOrEqual(lhs, rhs)
+			| Self::LessThanOrEqual(lhs, rhs)
+			| Self::Equal(lhs, rhs)
+			| Self::NotEqual(lhs, rhs)
+			| Self::And(lhs, rhs)
+			| Self::Or(lhs, rhs)
+			| Self::BracketIndex(lhs, rhs) => {
+				lhs.visit(f);
+				rhs.visit(f);
+			}
+
+			Self::Not(expr)
+			| Self::Negate(expr)
+			| Self::Cast(expr, _)
+			| Self::Is(expr, _)
+			| Self::DotIndex(expr, _)
+			| Self::Array(_, expr) => expr.visit(f),
+
+			Self::Call(target, args) => {
+				target.visit(f);
+				for arg in args {
+					arg.value().visit(f);
+				}
+			}
+
+			Self::Ident(_)
+			| Self::SelfRef
+			| Self::Parent
+			| Self::Bool(_)
+			| Self::String(_)
+			| Self::Integer(_)
+			| Self::Float(_)
+			| Self::None
+			| Self::Struct(_) => (),
+		}
+	}
+}
@@ -1,4 +1,5 @@
-use super::Rule;
+use super::{ast::Span, Rule};
+use crate::diagnostics::Diagnostic;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -18,3 +19,57 @@ pub enum Error {
 }
 
 pub type Result<'a, T> = std::result::Result<T, Error>;
+
+impl Error {
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::Parsing(_) => "E0001",
+			Self::Expected { .. } => "E0002",
+			Self::UnexpectedEOI(_) => "E0003",
+		}
+	}
+
+	/// Where in the source the error happened, if known.
+	pub fn span(&self) -> Option<Span> {
+		match self {
+			Self::Parsing(e) => {
+				let (start, end) = match e.location {
+					pest::error::InputLocation::Pos(pos) => (pos, pos),
+					pest::error::InputLocation::Span(span) => span,
+				};
+				let (line, column) = match e.line_col {
+					pest::error::LineColLocation::Pos(pos) => pos,
+					pest::error::LineColLocation::Span(start, _) => start,
+				};
+				Some(Span {
+					start,
+					end,
+					line,
+					column,
+				})
+			}
+			Self::Expected { trace, .. } => Some(Span {
+				line: trace.0,
+				column: trace.1,
+				..Default::default()
+			}),
+			Self::UnexpectedEOI(_) => None,
+		}
+	}
+}
+
+impl From<&Error> for Diagnostic {
+	fn from(error: &Error) -> Self {
+		let message = match error {
+			// Pest's own message includes a rendering of the source, only the variant is wanted here.
+			Error::Parsing(e) => e.variant.message().into_owned(),
+			e => e.to_string(),
+		};
+
+		let diagnostic = Diagnostic::error(error.code(), message);
+		match error.span() {
+			Some(span) => diagnostic.with_span(span),
+			None => diagnostic,
+		}
+	}
+}
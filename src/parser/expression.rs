@@ -27,7 +27,7 @@ pub static PRATT_PARSER: Lazy<PrattParser<Rule>> = Lazy::new(|| {
 		.op(binary!(op_and, op_or))
 		.op(binary!(op_eq, op_neq, op_geq, op_leq, op_gt, op_lt))
 		.op(binary!(op_add, op_sub))
-		.op(binary!(op_mul, op_div))
+		.op(binary!(op_mul, op_div, op_mod))
 		.op(unary!(not, neg))
 		.op(postfix!(cast, type_check, call, dot_index, bracket_index))
 });
@@ -62,10 +62,20 @@ impl<'a> ParseExpression for Pair<'a, Rule> {
 		// Todo: Make these functions fallible instead of panicking
 		fn primary(prim: Pair<Rule>) -> Expression {
 			match prim.as_rule() {
-				Rule::ident => Expression::Ident(prim.ident()),
-				Rule::hexadecimal => Expression::Integer(
-					i64::from_str_radix(prim.as_str().trim_start_matches("0x"), 16).unwrap(),
-				),
+				Rule::ident => match prim.as_str().to_ascii_lowercase().as_str() {
+					"self" => Expression::SelfRef,
+					"parent" => Expression::Parent,
+					_ => Expression::Ident(prim.ident()),
+				},
+				Rule::hexadecimal => {
+					let value =
+						i64::from_str_radix(prim.as_str().trim_start_matches("0x"), 16).unwrap();
+					// Hexadecimal literals are the bits of an Int, so 0xFFFFFFFF is -1.
+					Expression::Integer(match u32::try_from(value) {
+						Ok(bits) => bits as i32 as i64,
+						Err(_) => value,
+					})
+				}
 				Rule::decimal => Expression::Float(prim.as_str().parse().unwrap()),
 				Rule::integer => Expression::Integer(prim.as_str().parse().unwrap()),
 				Rule::string => Expression::String(prim.as_str().to_owned()),
@@ -96,6 +106,7 @@ impl<'a> ParseExpression for Pair<'a, Rule> {
 				Rule::op_sub => Expression::Subtraction(Box::new(lhs), Box::new(rhs)),
 				Rule::op_mul => Expression::Multiplication(Box::new(lhs), Box::new(rhs)),
 				Rule::op_div => Expression::Division(Box::new(lhs), Box::new(rhs)),
+				Rule::op_mod => Expression::Modulo(Box::new(lhs), Box::new(rhs)),
 
 				Rule::op_gt => Expression::GreaterThan(Box::new(lhs), Box::new(rhs)),
 				Rule::op_lt => Expression::LessThan(Box::new(lhs), Box::new(rhs)),
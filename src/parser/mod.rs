@@ -2,7 +2,7 @@
 #[grammar = "parser/papyrus.pest"]
 pub struct PestParser;
 
-pub(crate) mod ast;
+pub mod ast;
 mod error;
 mod expression;
 mod statement;
@@ -19,8 +19,13 @@ use self::statement::ParseStatement;
 type Result<T> = error::Result<'static, T>;
 
 trait PestWalker {
-	fn expect_rule(&mut self, rule: Rule) -> Result<Pair<Rule>>;
-	fn opt_rule(&mut self, rule: Rule) -> Option<Pair<Rule>>;
+	fn expect_rule(&mut self, rule: Rule) -> Result<Pair<'_, Rule>>;
+	fn opt_rule(&mut self, rule: Rule) -> Option<Pair<'_, Rule>>;
+	/// Takes every [Rule::user_flag] up to the next other node.
+	fn user_flags(&mut self) -> Vec<String>;
+	/// Takes every [Rule::user_flag] and [Rule::const_flag] up to the next other node,
+	/// returning the user flags and whether there was a const flag.
+	fn variable_flags(&mut self) -> (Vec<String>, bool);
 }
 
 /// All of these functions assume they are on a node with the correct matching [Rule].
@@ -43,7 +48,7 @@ impl<'a> PestNode for Pair<'a, Rule> {
 }
 
 impl<'a> PestWalker for Pairs<'a, Rule> {
-	fn expect_rule(&mut self, expecting: Rule) -> Result<Pair<Rule>> {
+	fn expect_rule(&mut self, expecting: Rule) -> Result<Pair<'_, Rule>> {
 		match self.peek() {
 			Some(pair) => {
 				let got = pair.as_rule();
@@ -62,7 +67,7 @@ impl<'a> PestWalker for Pairs<'a, Rule> {
 		}
 	}
 
-	fn opt_rule(&mut self, expecting: Rule) -> Option<Pair<Rule>> {
+	fn opt_rule(&mut self, expecting: Rule) -> Option<Pair<'_, Rule>> {
 		match self.peek() {
 			Some(pair) if pair.as_rule() == expecting => {
 				self.next();
@@ -71,6 +76,31 @@ impl<'a> PestWalker for Pairs<'a, Rule> {
 			_ => None,
 		}
 	}
+
+	fn user_flags(&mut self) -> Vec<String> {
+		std::iter::from_fn(|| self.opt_rule(Rule::user_flag).map(PestNode::ident)).collect()
+	}
+
+	fn variable_flags(&mut self) -> (Vec<String>, bool) {
+		let (mut flags, mut is_const) = (vec![], false);
+		loop {
+			if self.opt_rule(Rule::const_flag).is_some() {
+				is_const = true;
+			} else if let Some(flag) = self.opt_rule(Rule::user_flag) {
+				flags.push(flag.ident());
+			} else {
+				return (flags, is_const);
+			}
+		}
+	}
+}
+
+/// Parses a script, reporting failure as a [Diagnostic](crate::Diagnostic) rather than an [Error].
+pub fn parse(source: impl AsRef<str>) -> (Option<Ast>, crate::Diagnostics) {
+	match parse_module(source) {
+		Ok(ast) => (Some(ast), crate::Diagnostics::new()),
+		Err(e) => (None, crate::Diagnostic::from(&e).into()),
+	}
 }
 
 pub fn parse_module(source: impl AsRef<str>) -> Result<Ast> {
@@ -86,7 +116,23 @@ pub fn parse_module(source: impl AsRef<str>) -> Result<Ast> {
 				let mut inner = item.into_inner();
 				script_info.script_name = inner.expect_rule(Rule::ident)?.ident();
 				script_info.extended_type = inner.opt_rule(Rule::r#type).map(PestNode::ty);
-				script_info.is_conditional = inner.peek().is_some();
+
+				for flag in inner {
+					if let Some(user_flag) = flag.clone().into_inner().next() {
+						let name = user_flag.ident();
+						if name.eq_ignore_ascii_case("conditional") {
+							script_info.is_conditional = true;
+						}
+						script_info.flags.push(name);
+						continue;
+					}
+
+					match flag.as_str().to_ascii_lowercase().as_str() {
+						"const" => script_info.is_const = true,
+						"native" => script_info.is_native = true,
+						_ => (),
+					}
+				}
 			}
 
 			Rule::body => {
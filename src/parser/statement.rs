@@ -1,4 +1,4 @@
-use crate::parser::ast::Field;
+use crate::parser::ast::{Field, Span};
 
 use super::{
 	ast::{Index, Parameter},
@@ -22,7 +22,9 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 				if i.as_rule() == Rule::statement {
 					i.statement()
 				} else {
-					i.expression().map(|e| Statement::Expression { expr: e })
+					let span = Span::from(i.as_span());
+					i.expression()
+						.map(|expr| Statement::Expression { expr, span })
 				}
 			})
 			.collect()
@@ -46,6 +48,7 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 	}
 
 	fn statement(self) -> Result<Statement> {
+		let span = Span::from(self.as_span());
 		let stmt = self.into_inner().next().unwrap();
 		let (rule, mut inner) = (stmt.as_rule(), stmt.into_inner());
 
@@ -72,6 +75,7 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 				}
 
 				Statement::If {
+					span,
 					cond,
 					body,
 
@@ -81,80 +85,130 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 			}
 
 			Rule::r#while => Statement::While {
+				span,
 				cond: inner.expect_rule(Rule::expression)?.expression()?,
 				body: inner.expect_rule(Rule::body)?.body()?,
 			},
 
-			Rule::full_property => Statement::PropertyFull {
-				ty: inner.expect_rule(Rule::r#type)?.ty(),
-				name: inner.expect_rule(Rule::ident)?.ident(),
-				functions: (
+			Rule::full_property => {
+				let ty = inner.expect_rule(Rule::r#type)?.ty();
+				let name = inner.expect_rule(Rule::ident)?.ident();
+				let mut flags = inner.user_flags();
+				let functions = (
 					Box::new(inner.expect_rule(Rule::statement)?.statement()?),
 					inner
 						.opt_rule(Rule::statement)
 						.and_then(|e| e.statement().ok().map(Box::new)),
-				),
-			},
+				);
+				flags.extend(inner.user_flags());
 
-			Rule::auto_property => Statement::PropertyAuto {
-				ty: inner.expect_rule(Rule::r#type)?.ty(),
-				name: inner.expect_rule(Rule::ident)?.ident(),
-				value: inner
+				Statement::PropertyFull {
+					span,
+					ty,
+					name,
+					functions,
+					flags,
+				}
+			}
+
+			Rule::auto_property => {
+				let ty = inner.expect_rule(Rule::r#type)?.ty();
+				let name = inner.expect_rule(Rule::ident)?.ident();
+				let value = inner
 					.opt_rule(Rule::expression)
-					.and_then(|e| e.expression().ok()),
-			},
+					.and_then(|e| e.expression().ok());
+
+				let (flags, is_const) = inner.variable_flags();
+
+				Statement::PropertyAuto {
+					span,
+					ty,
+					name,
+					value,
+					is_const,
+					flags,
+				}
+			}
 
 			Rule::const_property => Statement::PropertyAutoConst {
+				span,
 				ty: inner.expect_rule(Rule::r#type)?.ty(),
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				value: inner.expect_rule(Rule::expression)?.expression()?,
+				flags: inner.user_flags(),
 			},
 
 			Rule::auto_state => Statement::State {
+				span,
 				auto: true,
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				body: inner.expect_rule(Rule::body)?.body()?,
 			},
 
 			Rule::normal_state => Statement::State {
+				span,
 				auto: false,
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				body: inner.expect_rule(Rule::body)?.body()?,
 			},
 
 			Rule::native_function => Statement::NativeFunction {
+				span,
 				return_type: inner.opt_rule(Rule::r#type).map(PestNode::ty),
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				parameters: inner.expect_rule(Rule::parameters)?.params()?,
+				is_global: inner.opt_rule(Rule::global).is_some(),
+				flags: {
+					inner.opt_rule(Rule::global);
+					inner.user_flags()
+				},
 			},
 
 			Rule::global_function => Statement::Function {
+				span,
 				return_type: inner.opt_rule(Rule::r#type).map(PestNode::ty),
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				parameters: inner.expect_rule(Rule::parameters)?.params()?,
+				is_global: inner.expect_rule(Rule::global).map(|_| true)?,
+				flags: inner.user_flags(),
 				body: inner.expect_rule(Rule::body)?.body()?,
 			},
 
 			Rule::method_function => Statement::Function {
+				span,
 				return_type: inner.opt_rule(Rule::r#type).map(PestNode::ty),
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				parameters: inner.expect_rule(Rule::parameters)?.params()?,
+				is_global: false,
+				flags: inner.user_flags(),
 				body: inner.expect_rule(Rule::body)?.body()?,
 			},
 
 			Rule::r#return => Statement::Return {
+				span,
 				value: inner
 					.opt_rule(Rule::expression)
 					.and_then(|e| e.expression().ok()),
 			},
 
-			Rule::definition => Statement::Definition {
-				ty: inner.expect_rule(Rule::r#type)?.ty(),
-				name: inner.expect_rule(Rule::ident)?.ident(),
-				value: inner.expect_rule(Rule::expression)?.expression()?,
-			},
+			Rule::definition => {
+				let ty = inner.expect_rule(Rule::r#type)?.ty();
+				let name = inner.expect_rule(Rule::ident)?.ident();
+				let value = inner.expect_rule(Rule::expression)?.expression()?;
+				let (flags, is_const) = inner.variable_flags();
+
+				Statement::Definition {
+					span,
+					ty,
+					name,
+					value,
+					is_const,
+					flags,
+				}
+			}
 
 			Rule::event => Statement::Event {
+				span,
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				parameters: inner.expect_rule(Rule::parameters)?.params()?,
 				body: inner.expect_rule(Rule::body)?.body()?,
@@ -177,6 +231,7 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 				}
 
 				Statement::Assignment {
+					span,
 					name,
 					indexes,
 					value: inner.expect_rule(Rule::expression)?.expression()?,
@@ -184,16 +239,28 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 			}
 
 			Rule::group => Statement::Group {
+				span,
 				name: inner.expect_rule(Rule::ident)?.ident(),
+				flags: inner.user_flags(),
 				properties: inner.expect_rule(Rule::body)?.body()?,
 			},
 
-			Rule::declaration => Statement::Declaration {
-				ty: inner.expect_rule(Rule::r#type)?.ty(),
-				name: inner.expect_rule(Rule::ident)?.ident(),
-			},
+			Rule::declaration => {
+				let ty = inner.expect_rule(Rule::r#type)?.ty();
+				let name = inner.expect_rule(Rule::ident)?.ident();
+				let (flags, is_const) = inner.variable_flags();
+
+				Statement::Declaration {
+					span,
+					ty,
+					name,
+					is_const,
+					flags,
+				}
+			}
 
 			Rule::compound_assignment => Statement::CompoundAssignment {
+				span,
 				name: inner.expect_rule(Rule::ident)?.ident(),
 				op: inner.next().unwrap().as_rule(),
 				value: inner.expect_rule(Rule::expression)?.expression()?,
@@ -202,22 +269,25 @@ impl<'a> ParseStatement for Pair<'a, Rule> {
 			Rule::r#struct => {
 				fn struct_field(f: Pair<Rule>) -> Result<Field> {
 					let mut inner = f.into_inner();
-					Ok(Field(
-						inner.expect_rule(Rule::r#type)?.ty(),
-						inner.expect_rule(Rule::ident)?.ident(),
-						inner
-							.opt_rule(Rule::expression)
-							.and_then(|e| e.expression().ok()),
-					))
+					let ty = inner.expect_rule(Rule::r#type)?.ty();
+					let name = inner.expect_rule(Rule::ident)?.ident();
+					let value = inner
+						.opt_rule(Rule::expression)
+						.and_then(|e| e.expression().ok());
+					let (flags, is_const) = inner.variable_flags();
+					Ok(Field(ty, name, value, is_const, flags))
 				}
 
 				Statement::Struct {
+					span,
 					name: inner.expect_rule(Rule::ident)?.ident(),
+					flags: inner.user_flags(),
 					fields: inner.map(struct_field).collect::<Result<Vec<_>>>()?,
 				}
 			}
 
 			Rule::import => Statement::Import {
+				span,
 				item: inner.expect_rule(Rule::ident)?.ident(),
 			},
 
@@ -0,0 +1,524 @@
+//! Multiple scripts loaded from a set of import directories, like the official compiler's `-i` flag.
+
+use crate::compiler::{Hierarchy, Ty};
+use crate::parser::ast::*;
+use indexmap::{IndexMap, IndexSet};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("Script {0} was not found in any import directory")]
+	NotFound(String),
+
+	#[error("Failed to read {}: {source}", path.display())]
+	Io {
+		path: PathBuf,
+		source: std::io::Error,
+	},
+
+	#[error("Failed to parse {}: {source}", path.display())]
+	Parsing {
+		path: PathBuf,
+		source: Box<crate::parser::Error>,
+	},
+
+	#[error("Script name {name} does not match file name {}", path.display())]
+	NameMismatch { name: String, path: PathBuf },
+
+	#[error("Script {0} extends itself")]
+	CyclicInheritance(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub struct Script {
+	pub path: PathBuf,
+	pub ast: Ast,
+}
+
+#[derive(Debug, Default)]
+pub struct Project {
+	imports: Vec<PathBuf>,
+
+	/// Keyed by lowercased script name.
+	scripts: IndexMap<String, Script>,
+	/// Names that aren't in any import directory, so they aren't searched for again.
+	missing: IndexSet<String>,
+}
+
+/// Path of a script relative to an import directory, `Namespace:Name` becoming `Namespace/Name.psc`.
+pub(crate) fn relative_path(name: &str) -> PathBuf {
+	let mut path: PathBuf = name.split(':').collect();
+	path.set_extension("psc");
+	path
+}
+
+/// Finds a file under `dir`, matching each component of `relative` case-insensitively.
+fn find_case_insensitive(dir: &Path, relative: &Path) -> Option<PathBuf> {
+	let exact = dir.join(relative);
+	if exact.is_file() {
+		return Some(exact);
+	}
+
+	let mut current = dir.to_path_buf();
+	for component in relative.iter() {
+		let entry = std::fs::read_dir(&current).ok()?.find_map(|entry| {
+			let entry = entry.ok()?;
+			entry
+				.file_name()
+				.to_string_lossy()
+				.eq_ignore_ascii_case(&component.to_string_lossy())
+				.then(|| entry.path())
+		})?;
+		current = entry;
+	}
+
+	current.is_file().then_some(current)
+}
+
+impl Project {
+	/// Creates a project searching the given directories, where later directories take priority.
+	pub fn new<P: Into<PathBuf>>(imports: impl IntoIterator<Item = P>) -> Self {
+		Self {
+			imports: imports.into_iter().map(Into::into).collect(),
+			..Default::default()
+		}
+	}
+
+	pub fn imports(&self) -> &[PathBuf] {
+		&self.imports
+	}
+
+	/// Finds the source file of a script, without loading it.
+	pub fn locate(&self, name: &str) -> Option<PathBuf> {
+		let relative = relative_path(name);
+		self.imports
+			.iter()
+			.rev()
+			.find_map(|dir| find_case_insensitive(dir, &relative))
+	}
+
+	/// Gets a script that has already been loaded.
+	pub fn get(&self, name: &str) -> Option<&Script> {
+		self.scripts.get(&name.to_ascii_lowercase())
+	}
+
+	pub fn scripts(&self) -> impl Iterator<Item = &Script> {
+		self.scripts.values()
+	}
+
+	/// Adds an already parsed script, like the one being compiled.
+	pub fn insert(&mut self, path: impl Into<PathBuf>, ast: Ast) -> Result<&Script> {
+		let path = path.into();
+		let name = ast.script_info.script_name.clone();
+
+		let relative = relative_path(&name);
+		let matches = path
+			.components()
+			.rev()
+			.zip(relative.components().rev())
+			.all(|(a, b)| {
+				a.as_os_str()
+					.to_string_lossy()
+					.eq_ignore_ascii_case(&b.as_os_str().to_string_lossy())
+			});
+
+		if !matches {
+			return Err(Error::NameMismatch { name, path });
+		}
+
+		let key = name.to_ascii_lowercase();
+		self.missing.shift_remove(&key);
+		self.scripts.insert(key.clone(), Script { path, ast });
+		Ok(&self.scripts[&key])
+	}
+
+	/// Parses a single script from the import directories, if it isn't already loaded.
+	fn load_script(&mut self, name: &str) -> Result<()> {
+		let key = name.to_ascii_lowercase();
+		if self.scripts.contains_key(&key) {
+			return Ok(());
+		}
+
+		if self.missing.contains(&key) {
+			return Err(Error::NotFound(name.to_owned()));
+		}
+
+		let Some(path) = self.locate(name) else {
+			self.missing.insert(key);
+			return Err(Error::NotFound(name.to_owned()));
+		};
+
+		let source = std::fs::read_to_string(&path).map_err(|source| Error::Io {
+			path: path.clone(),
+			source,
+		})?;
+
+		let ast = crate::parse_module(source).map_err(|source| Error::Parsing {
+			path: path.clone(),
+			source: Box::new(source),
+		})?;
+
+		if !ast.script_info.script_name.eq_ignore_ascii_case(name) {
+			return Err(Error::NameMismatch {
+				name: ast.script_info.script_name,
+				path,
+			});
+		}
+
+		self.scripts.insert(key, Script { path, ast });
+		Ok(())
+	}
+
+	/// Loads a script and every script it extends.
+	fn load_chain(&mut self, name: &str) -> Result<()> {
+		let mut seen = IndexSet::new();
+		let mut current = Some(name.to_owned());
+
+		while let Some(name) = current {
+			if !seen.insert(name.to_ascii_lowercase()) {
+				return Err(Error::CyclicInheritance(name));
+			}
+
+			self.load_script(&name)?;
+			current = self
+				.get(&name)
+				.unwrap()
+				.ast
+				.script_info
+				.extended_type
+				.clone();
+		}
+
+		Ok(())
+	}
+
+	/// Loads a script along with its parents, imports and the scripts of every type any of them references.
+	/// Referenced types that can't be found are skipped, as they may be structs or variables.
+	pub fn load(&mut self, name: &str) -> Result<&Script> {
+		self.load_chain(name)?;
+
+		let ast = &self.get(name).unwrap().ast;
+
+		let imports = ast
+			.statements
+			.iter()
+			.filter_map(|stmt| match stmt {
+				Statement::Import { item, .. } => Some(item.clone()),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+
+		for import in &imports {
+			self.load_chain(import)?;
+		}
+
+		// What parents and imports declare is used with the types they are declared with.
+		let mut referenced = IndexSet::new();
+		let names = std::iter::once(name).chain(imports.iter().map(String::as_str));
+		for script in names.flat_map(|name| self.chain(name)) {
+			references(&script.ast.statements, &mut referenced);
+		}
+		referenced.retain(|ty| {
+			!matches!(
+				ty.to_ascii_lowercase().as_str(),
+				"bool" | "int" | "float" | "string" | "var" | "none"
+			)
+		});
+
+		for ty in referenced {
+			match self.load_chain(&ty) {
+				Ok(()) | Err(Error::NotFound(_)) => (),
+				Err(why) => return Err(why),
+			}
+		}
+
+		Ok(self.get(name).unwrap())
+	}
+
+	/// Iterates over a loaded script and every loaded script it extends, starting with itself.
+	pub fn chain<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a Script> + 'a {
+		let mut current = self.get(name);
+		let mut seen = IndexSet::new();
+
+		std::iter::from_fn(move || {
+			let script = current.take()?;
+			if !seen.insert(script.ast.script_info.script_name.to_ascii_lowercase()) {
+				return None;
+			}
+
+			current = script
+				.ast
+				.script_info
+				.extended_type
+				.as_deref()
+				.and_then(|parent| self.get(parent));
+
+			Some(script)
+		})
+	}
+
+	/// First statement of `script` or a script it extends that `find` matches,
+	/// `Some(None)` if there is none, or [None] if not every script of the chain is loaded.
+	fn find<'a, T>(
+		&'a self,
+		script: &str,
+		find: impl Fn(&'a Script, &'a Statement) -> Option<T>,
+	) -> Option<Option<T>> {
+		let mut last = None;
+		for script in self.chain(script) {
+			// Properties can be declared inside of groups.
+			let statements = script.ast.statements.iter().flat_map(|stmt| match stmt {
+				Statement::Group { properties, .. } => properties.iter().collect(),
+				stmt => vec![stmt],
+			});
+			for stmt in statements {
+				if let Some(found) = find(script, stmt) {
+					return Some(Some(found));
+				}
+			}
+			last = Some(script);
+		}
+
+		match last?.ast.script_info.extended_type {
+			Some(_) => None,
+			None => Some(None),
+		}
+	}
+}
+
+impl Hierarchy for Project {
+	fn property(&self, script: &str, name: &str) -> Option<Option<Ty>> {
+		self.find(script, |_, stmt| match stmt {
+			Statement::PropertyAuto { name: n, ty, .. }
+			| Statement::PropertyAutoConst { name: n, ty, .. }
+			| Statement::PropertyFull { name: n, ty, .. }
+				if n.eq_ignore_ascii_case(name) =>
+			{
+				Some(Ty::parse(ty))
+			}
+			_ => None,
+		})
+	}
+
+	fn function(&self, script: &str, name: &str) -> Option<Option<(&str, &Statement)>> {
+		self.find(script, |script, stmt| match stmt {
+			Statement::Function { name: n, .. }
+			| Statement::NativeFunction { name: n, .. }
+			| Statement::Event { name: n, .. }
+				if n.eq_ignore_ascii_case(name) =>
+			{
+				Some((script.ast.script_info.script_name.as_str(), stmt))
+			}
+			_ => None,
+		})
+	}
+
+	fn extends(&self, child: &str, parent: &str) -> Option<bool> {
+		let mut last = None;
+		for script in self.chain(child) {
+			if script
+				.ast
+				.script_info
+				.script_name
+				.eq_ignore_ascii_case(parent)
+			{
+				return Some(true);
+			}
+			last = Some(script);
+		}
+
+		// Only certain if the whole chain was loaded.
+		match last?.ast.script_info.extended_type {
+			Some(_) => None,
+			None => Some(false),
+		}
+	}
+}
+
+fn type_name(ty: &str) -> String {
+	ty.trim_end_matches("[]").trim_end().to_owned()
+}
+
+fn expression_references(expr: &Expression, out: &mut IndexSet<String>) {
+	match expr {
+		Expression::Cast(expr, ty) | Expression::Is(expr, ty) | Expression::Array(ty, expr) => {
+			out.insert(type_name(ty));
+			expression_references(expr, out);
+		}
+
+		Expression::Struct(ty) => {
+			out.insert(type_name(ty));
+		}
+
+		// Possibly a script name, as in Debug.Trace()
+		Expression::DotIndex(base, _) => match base.as_ref() {
+			Expression::Ident(name) => {
+				out.insert(name.clone());
+			}
+			base => expression_references(base, out),
+		},
+
+		Expression::Call(target, args) => {
+			expression_references(target, out);
+			for arg in args {
+				expression_references(arg.value(), out);
+			}
+		}
+
+		Expression::Addition(lhs, rhs)
+		| Expression::Subtraction(lhs, rhs)
+		| Expression::Multiplication(lhs, rhs)
+		| Expression::Division(lhs, rhs)
+		| Expression::Modulo(lhs, rhs)
+		| Expression::GreaterThan(lhs, rhs)
+		| Expression::LessThan(lhs, rhs)
+		| Expression::GreaterThanOrEqual(lhs, rhs)
+		| Expression::LessThanOrEqual(lhs, rhs)
+		| Expression::Equal(lhs, rhs)
+		| Expression::NotEqual(lhs, rhs)
+		| Expression::And(lhs, rhs)
+		| Expression::Or(lhs, rhs)
+		| Expression::BracketIndex(lhs, rhs) => {
+			expression_references(lhs, out);
+			expression_references(rhs, out);
+		}
+
+		Expression::Not(expr) | Expression::Negate(expr) => expression_references(expr, out),
+
+		Expression::Ident(_)
+		| Expression::SelfRef
+		| Expression::Parent
+		| Expression::Bool(_)
+		| Expression::String(_)
+		| Expression::Integer(_)
+		| Expression::Float(_)
+		| Expression::None => (),
+	}
+}
+
+fn parameter_references(parameters: &[Parameter], out: &mut IndexSet<String>) {
+	for param in parameters {
+		out.insert(type_name(&param.0));
+		if let Some(value) = &param.2 {
+			expression_references(value, out);
+		}
+	}
+}
+
+/// Collects the name of every type used by these statements.
+fn references(statements: &[Statement], out: &mut IndexSet<String>) {
+	for stmt in statements {
+		match stmt {
+			Statement::If {
+				cond,
+				body,
+				elifs,
+				else_block,
+				..
+			} => {
+				expression_references(cond, out);
+				references(body, out);
+
+				for (cond, body) in elifs {
+					expression_references(cond, out);
+					references(body, out);
+				}
+
+				if let Some(body) = else_block {
+					references(body, out);
+				}
+			}
+
+			Statement::While { cond, body, .. } => {
+				expression_references(cond, out);
+				references(body, out);
+			}
+
+			Statement::Function {
+				return_type,
+				parameters,
+				body,
+				..
+			} => {
+				out.extend(return_type.as_deref().map(type_name));
+				parameter_references(parameters, out);
+				references(body, out);
+			}
+
+			Statement::NativeFunction {
+				return_type,
+				parameters,
+				..
+			} => {
+				out.extend(return_type.as_deref().map(type_name));
+				parameter_references(parameters, out);
+			}
+
+			Statement::Event {
+				parameters, body, ..
+			} => {
+				parameter_references(parameters, out);
+				references(body, out);
+			}
+
+			Statement::Return { value, .. } => {
+				if let Some(value) = value {
+					expression_references(value, out);
+				}
+			}
+
+			Statement::PropertyFull { ty, functions, .. } => {
+				out.insert(type_name(ty));
+				references(std::slice::from_ref(functions.0.as_ref()), out);
+				if let Some(set) = &functions.1 {
+					references(std::slice::from_ref(set.as_ref()), out);
+				}
+			}
+
+			Statement::PropertyAuto { ty, value, .. } => {
+				out.insert(type_name(ty));
+				if let Some(value) = value {
+					expression_references(value, out);
+				}
+			}
+
+			Statement::PropertyAutoConst { ty, value, .. }
+			| Statement::Definition { ty, value, .. } => {
+				out.insert(type_name(ty));
+				expression_references(value, out);
+			}
+
+			Statement::Declaration { ty, .. } => {
+				out.insert(type_name(ty));
+			}
+
+			Statement::State { body, .. }
+			| Statement::Group {
+				properties: body, ..
+			} => references(body, out),
+
+			Statement::Assignment { indexes, value, .. } => {
+				for index in indexes {
+					if let Index::Bracket(expr) = index {
+						expression_references(expr, out);
+					}
+				}
+				expression_references(value, out);
+			}
+
+			Statement::CompoundAssignment { value, .. } => expression_references(value, out),
+			Statement::Expression { expr, .. } => expression_references(expr, out),
+
+			Statement::Struct { fields, .. } => {
+				for field in fields {
+					out.insert(type_name(&field.0));
+				}
+			}
+
+			Statement::Import { .. } => (),
+		}
+	}
+}
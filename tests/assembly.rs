@@ -0,0 +1,370 @@
+/*!
+	Testing Papyrus assembly listings.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod common;
+
+use common::directory;
+use cyperus::{
+	compiler::{
+		assembly::{assemble, emit},
+		compile,
+		pex::{Instruction, Opcode, Value},
+		CompileOptions, Game, Metadata,
+	},
+	parse_module,
+};
+
+fn listing(source: &str, game: Game, debug_info: bool) -> String {
+	let options = CompileOptions {
+		debug_info,
+		assembly: true,
+		metadata: Metadata {
+			compilation_time: Some(10),
+			source: None,
+			user: Some("user".to_owned()),
+			machine: Some("machine".to_owned()),
+		},
+		..CompileOptions::new(game)
+	};
+	let output = compile(&parse_module(source).unwrap(), &options).unwrap();
+	output.assembly.unwrap()
+}
+
+const LOOP: &str = ".info
+	.source \"Test.psc\"
+	.modifyTime 10
+	.compileTime 10
+	.user \"user\"
+	.computer \"machine\"
+.endInfo
+.userFlagsRef
+	.flag hidden 0
+	.flag conditional 1
+.endUserFlagsRef
+.objectTable
+	.object Test Form
+		.userFlags 1
+		.docString \"\"
+		.autoState
+		.variableTable
+			.variable ::Count_var Int
+				.userFlags 2
+				.initialValue None
+			.endVariable
+		.endVariableTable
+		.propertyTable
+			.property Count Int auto
+				.userFlags 0
+				.docString \"\"
+				.autoVar ::Count_var
+			.endProperty
+		.endPropertyTable
+		.stateTable
+			.state
+				.function Foo
+					.userFlags 0
+					.docString \"\"
+					.return Int
+					.paramTable
+						.param a Int
+					.endParamTable
+					.localTable
+						.local ::temp0 Bool
+					.endLocalTable
+					.code
+						label0:
+						cmp_gt ::temp0 a 0 ;@line 4
+						jmpf ::temp0 label1 ;@line 4
+						isub a a 1 ;@line 5
+						jmp label0 ;@line 4
+						label1:
+						return a ;@line 7
+					.endCode
+				.endFunction
+			.endState
+		.endStateTable
+	.endObject
+.endObjectTable
+";
+
+#[test]
+fn test_emit() {
+	let source = "ScriptName Test Extends Form Hidden
+		Int Property Count Auto Conditional
+		Int Function Foo(Int a)
+			While a > 0
+				a -= 1
+			EndWhile
+			Return a
+		EndFunction";
+	assert_eq!(listing(source, Game::Skyrim, true), LOOP);
+
+	// Without debug info, nothing is annotated.
+	let listing = listing(source, Game::Skyrim, false);
+	assert!(!listing.contains(".modifyTime"));
+	assert!(!listing.contains(";@line"));
+	assert!(listing.contains("\t\t\t\t\t\treturn a\n"));
+}
+
+#[test]
+fn test_fallout_4() {
+	let source = "ScriptName Test Const
+		Struct Point
+			Float x = 1.5
+			Int y Const
+		EndStruct
+		Group Stats Collapsed
+			String Property Name = \"Nobody\" AutoReadOnly
+		EndGroup
+		Function Log(String message) Global Native
+		Int count Const
+		Float speed = 2.0 Const";
+	let listing = listing(source, Game::Fallout4, true);
+
+	for expected in [
+		"\t.object Test const\n",
+		"\t\t\t.struct Point\n\t\t\t\t.variable x Float\n",
+		"\t\t\t\t.variable y Int const\n",
+		"\t\t\t.variable count Int const\n",
+		"\t\t\t.variable speed Float const\n",
+		"\t\t\t.propertyGroup Stats\n\t\t\t\t.userFlags 24\n\t\t\t\t.docString \"\"\n\t\t\t\t.property Name\n",
+		"\t\t\t.property Name String\n",
+		"\t\t\t\t.function get\n",
+		"return \"Nobody\" ;@line 7\n",
+		"\t\t\t\t.function Log static native\n",
+	] {
+		assert!(listing.contains(expected), "{expected:?} in\n{listing}");
+	}
+}
+
+#[test]
+fn test_round_trip() {
+	let sources = [
+		(
+			"ScriptName Test Extends Form Hidden
+			Int Property Count Auto Conditional
+			Float Property Speed = 2 AutoReadOnly
+			String Property Name
+				String Function Get()
+					Return \"Tab\\there, done\"
+				EndFunction
+			EndProperty
+			Int Function Foo(Int a, Float b = -1.5)
+				While a > 0 && b != 0.25
+					a -= 1
+					If a == 3
+						Return a
+					EndIf
+				EndWhile
+				Debug.Trace(\"Done\")
+				Return a
+			EndFunction
+			Function Bar() Native
+			Auto State Busy
+				Event OnInit()
+				EndEvent
+				Int Function Foo(Int a, Float b = -1.5)
+					Return Parent.Foo(a, b)
+				EndFunction
+			EndState",
+			Game::Skyrim,
+		),
+		(
+			"ScriptName Outer:Test Const
+			Struct Point
+				Float x = 1.5
+				Int y
+			EndStruct
+			Int Property Total = 5 AutoReadOnly
+			Group Stats Collapsed
+				String Property Label = \"Nobody\" AutoReadOnly
+			EndGroup
+			Point Function Make(Int[] values)
+				Point p = new Point
+				p.y = values.Find(3)
+				Return p
+			EndFunction",
+			Game::Fallout4,
+		),
+	];
+
+	let imports = directory(
+		"round-trip",
+		&[
+			(
+				"Form.psc",
+				"ScriptName Form
+				Int Function Foo(Int a, Float b = -1.5)
+					Return a
+				EndFunction
+				Event OnInit()
+				EndEvent",
+			),
+			(
+				"Debug.psc",
+				"ScriptName Debug
+				Function Trace(String text) Global Native",
+			),
+		],
+	);
+
+	for (source, game) in sources {
+		for debug_info in [true, false] {
+			let options = CompileOptions {
+				debug_info,
+				assembly: true,
+				imports: vec![imports.clone()],
+				..CompileOptions::new(game)
+			};
+			let output = compile(&parse_module(source).unwrap(), &options).unwrap();
+			let listing = output.assembly.unwrap();
+
+			let pex = assemble(&listing, game).unwrap_or_else(|why| panic!("{why}\n{listing}"));
+			assert_eq!(emit(&pex), listing);
+			assert!(pex.encode().unwrap() == output.bytes, "{listing}");
+		}
+	}
+}
+
+#[test]
+fn test_special_floats() {
+	let source = "ScriptName Test
+		Float a
+		Float b
+		Float c";
+	let options = CompileOptions {
+		assembly: true,
+		..CompileOptions::new(Game::Fallout4)
+	};
+	let output = compile(&parse_module(source).unwrap(), &options).unwrap();
+	let mut pex = assemble(&output.assembly.unwrap(), Game::Fallout4).unwrap();
+	let values = [f32::INFINITY, f32::NEG_INFINITY, f32::NAN];
+	for (variable, value) in pex.objects[0].variables.iter_mut().zip(values) {
+		variable.value = Value::Float(value);
+	}
+
+	let listing = emit(&pex);
+	let pex = assemble(&listing, Game::Fallout4).unwrap_or_else(|why| panic!("{why}\n{listing}"));
+	assert_eq!(emit(&pex), listing);
+	let values: Vec<_> = pex.objects[0]
+		.variables
+		.iter()
+		.map(|variable| match variable.value {
+			Value::Float(value) => value,
+			ref value => panic!("expected a Float, got {value:?}"),
+		})
+		.collect();
+	assert_eq!(values[..2], [f32::INFINITY, f32::NEG_INFINITY]);
+	assert!(values[2].is_nan());
+}
+
+#[test]
+fn test_assemble() {
+	// Written by hand, with opcodes in any case and comments of its own.
+	let listing = ".info
+	.source \"Hand.psc\"
+	.compileTime 5
+	.user \"\"
+	.computer \"\"
+.endInfo
+.userFlagsRef
+.endUserFlagsRef
+.objectTable
+	.object Hand Form
+		.userFlags 0
+		.docString \"Written by hand\"
+		.autoState
+		.variableTable
+		.endVariableTable
+		.propertyTable
+		.endPropertyTable
+		.stateTable
+			.state
+				.function Clamp static
+					.userFlags 0
+					.docString \"\"
+					.return Int
+					.paramTable
+						.param value Int
+					.endParamTable
+					.localTable
+						.local big Bool
+					.endLocalTable
+					.code
+						CMP_GT big value 10 ; is it too big?
+						JMPF big done
+						ASSIGN value 10
+					done:
+						RETURN value
+					.endCode
+				.endFunction
+			.endState
+		.endStateTable
+	.endObject
+.endObjectTable
+";
+
+	let pex = assemble(listing, Game::Skyrim).unwrap();
+	assert!(pex.debug_info.is_none());
+	let object = &pex.objects[0];
+	assert_eq!(object.doc, "Written by hand");
+
+	let function = &object.states[0].functions[0];
+	assert!(function.is_global);
+	assert_eq!(
+		function.instructions[1],
+		Instruction::new(
+			Opcode::JmpF,
+			vec![Value::Ident("big".to_owned()), Value::Int(2)]
+		)
+	);
+	assert_eq!(
+		function.instructions[0].args,
+		[
+			Value::Ident("big".to_owned()),
+			Value::Ident("value".to_owned()),
+			Value::Int(10)
+		]
+	);
+
+	// Errors point at the line of the listing they are on.
+	let error = |find: &str, replace: &str, game| {
+		let listing = listing.replace(find, replace);
+		assemble(&listing, game).unwrap_err().to_string()
+	};
+	assert_eq!(
+		error("ASSIGN value 10", "MOVE value 10", Game::Skyrim),
+		"Unknown opcode MOVE at line 33"
+	);
+	assert_eq!(
+		error("ASSIGN value 10", "ASSIGN value", Game::Skyrim),
+		"assign takes 2 operands, but got 1 at line 33"
+	);
+	assert_eq!(
+		error("JMPF big done", "JMPF big end", Game::Skyrim),
+		"Label end is not defined at line 32"
+	);
+	assert_eq!(
+		error("ASSIGN value 10", "STRUCT_CREATE value", Game::Skyrim),
+		"struct_create is not supported by Skyrim at line 33"
+	);
+	assert_eq!(
+		error("RETURN value", "RETURN value ;@line 4", Game::Fallout4),
+		"Instruction has no source line, unlike others of its function at line 31"
+	);
+	assert_eq!(
+		error(".return Int", ".return", Game::Skyrim),
+		"Expected a type, but got end of line at line 23"
+	);
+	assert_eq!(
+		error(".docString \"\"", ".docString \"", Game::Skyrim),
+		"Unterminated string at line 22"
+	);
+	assert_eq!(
+		error(".endObjectTable\n", "", Game::Skyrim),
+		"Expected .endObjectTable, but got end of input"
+	);
+}
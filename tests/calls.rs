@@ -0,0 +1,159 @@
+/*!
+	Testing how call arguments are matched with parameters.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{resolve, resolve_calls, typecheck, ArgumentValue, Calls},
+	parse_module,
+	parser::ast::Statement,
+	Diagnostics, Format, Project,
+};
+
+const SOURCE: &str = "ScriptName Test
+Function Take(Int a, Float b = 2.0, String c = \"c\")
+EndFunction";
+
+fn errors(calls: &str) -> Vec<String> {
+	let ast = parse_module(format!("{SOURCE}\nEvent OnInit()\n{calls}\nEndEvent")).unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+	resolve_calls(&ast, &resolution, &(), &typing, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+/// Arguments given to each call in `body`, with defaults marked as such.
+fn arguments(calls: &Calls, body: &[Statement]) -> Vec<Vec<String>> {
+	body.iter()
+		.map(|stmt| {
+			let Statement::Expression { expr, .. } = stmt else {
+				unreachable!()
+			};
+			calls
+				.get(expr)
+				.unwrap()
+				.arguments
+				.iter()
+				.map(|arg| match arg.unwrap() {
+					ArgumentValue::Given(expr) => expr.clone().format(),
+					ArgumentValue::Default(expr) => format!("default {}", expr.clone().format()),
+				})
+				.collect()
+		})
+		.collect()
+}
+
+#[test]
+fn test_normalized() {
+	let ast = parse_module(format!(
+		"{SOURCE}
+		Event OnInit()
+			Take(1)
+			Take(1, c = \"x\")
+			Take(c = \"y\", a = 3, b = 4.0)
+		EndEvent"
+	))
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+	let calls = resolve_calls(&ast, &resolution, &(), &typing, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	let Statement::Event { body, .. } = &ast.statements[1] else {
+		unreachable!()
+	};
+
+	assert_eq!(
+		arguments(&calls, body),
+		[
+			["1", "default 2", "default \"c\""],
+			["1", "default 2", "\"x\""],
+			["3", "4", "\"y\""],
+		]
+	);
+}
+
+#[test]
+fn test_errors() {
+	assert_eq!(
+		errors(
+			"Take(1, 2.0, \"c\", 4)
+			Take()
+			Take(b = 1.0)
+			Take(1, missing = 2)
+			Take(a = 1, 2.0)
+			Take(1, a = 2)"
+		),
+		[
+			"Take takes 3 arguments, but 4 were given",
+			"Take is missing a value for parameter a",
+			"Take is missing a value for parameter a",
+			"Take has no parameter missing",
+			"positional argument given after named arguments in call to Take",
+			"parameter a of Take is given multiple times",
+		]
+	);
+}
+
+#[test]
+fn test_inherited() {
+	let base = "ScriptName Base
+		Function Take(Int a, Bool b = True, Bool c = True)
+		EndFunction";
+	let child = "ScriptName Child Extends Base
+		Function Give(Base other)
+			Take(1)
+			Take(c = False, a = 3)
+			other.Take(2, False)
+		EndFunction
+		Function Break(Base other)
+			Take(b = False)
+			other.Take(1, d = 2)
+		EndFunction";
+
+	let mut project = Project::new(Vec::<String>::new());
+	for source in [base, child] {
+		let ast = parse_module(source).unwrap();
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+
+	let ast = parse_module(child).unwrap();
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &project, &mut diagnostics);
+	let calls = resolve_calls(&ast, &resolution, &project, &typing, &mut diagnostics);
+
+	let Statement::Function { body, .. } = &ast.statements[0] else {
+		unreachable!()
+	};
+	assert_eq!(
+		arguments(&calls, body),
+		[
+			["1", "default true", "default true"],
+			["3", "default true", "false"],
+			["2", "false", "default true"],
+		]
+	);
+	assert_eq!(
+		calls.get(body[0].expressions()[0]).unwrap().script,
+		Some("Base")
+	);
+
+	let messages = diagnostics
+		.into_iter()
+		.map(|d| d.message)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		messages,
+		[
+			"Take is missing a value for parameter a",
+			"Take has no parameter d",
+		]
+	);
+}
@@ -0,0 +1,397 @@
+/*!
+	Testing code generation for function bodies.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{
+		codegen::{generate, Code},
+		pex::Value,
+		resolve, resolve_calls, typecheck,
+	},
+	parse_module,
+	parser::ast::Ast,
+	Diagnostics, Project,
+};
+
+/// Scripts the tested one can use, along with itself.
+const SCRIPTS: [&str; 4] = [
+	"ScriptName Base
+	Other Function GetOther()
+	EndFunction
+	Event Test()
+	EndEvent",
+	"ScriptName Other
+	Int Function Count()
+	EndFunction",
+	"ScriptName ObjectReference
+	Float Property X Auto",
+	"ScriptName Debug
+	Function Trace(String asTextToPrint, Int aiSeverity = 0) Global Native",
+];
+
+fn project(ast: &Ast) -> Project {
+	let mut project = Project::new(Vec::<String>::new());
+	let scripts = SCRIPTS.iter().map(|source| parse_module(*source).unwrap());
+	for ast in scripts.chain([ast.clone()]) {
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+	project
+}
+
+/// Code of the function or event `Test`.
+fn generated(source: &str) -> Code {
+	let ast = parse_module(source).unwrap();
+	let project = project(&ast);
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &project, &mut diagnostics);
+	let calls = resolve_calls(&ast, &resolution, &project, &typing, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+	assert!(typing.unknown().is_empty(), "{}", typing.unknown());
+
+	let function = ast
+		.statements
+		.iter()
+		.find(|stmt| stmt.name() == Some("Test"))
+		.unwrap();
+
+	let code = generate(
+		&ast,
+		function,
+		&resolution,
+		&project,
+		&calls,
+		&typing,
+		&mut diagnostics,
+	);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+	code
+}
+
+/// One instruction or label per line.
+fn code(source: &str) -> Vec<String> {
+	generated(source)
+		.ir
+		.iter()
+		.map(ToString::to_string)
+		.collect()
+}
+
+#[test]
+fn test_control_flow() {
+	let source = "ScriptName Test
+		Int Function Test(Int a, Float b)
+			If a > 1
+				return a % 3
+			ElseIf b < a
+				return (a + b) as Int
+			Else
+				While a
+					a -= 1
+				EndWhile
+			EndIf
+			return 0
+		EndFunction";
+
+	assert_eq!(
+		code(source),
+		[
+			"cmp_gt ::temp0 a 1",
+			"jmpf ::temp0 label1",
+			"imod ::temp1 a 3",
+			"return ::temp1",
+			"jmp label0",
+			"label1:",
+			"cast ::temp2 a",
+			"cmp_lt ::temp0 b ::temp2",
+			"jmpf ::temp0 label2",
+			"cast ::temp2 a",
+			"fadd ::temp3 ::temp2 b",
+			"cast ::temp1 ::temp3",
+			"return ::temp1",
+			"jmp label0",
+			"label2:",
+			"label3:",
+			"jmpf a label4",
+			"isub a a 1",
+			"jmp label3",
+			"label4:",
+			"label0:",
+			"return 0",
+		]
+	);
+
+	// Jumps are relative to the instruction they are in.
+	let jumps = generated(source)
+		.instructions()
+		.into_iter()
+		.filter(|i| i.opcode.name().starts_with("jmp"))
+		.map(|i| i.args.last().cloned().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(jumps, [4, 12, 6, 4, 3, -2].map(Value::Int));
+
+	// Instructions belong to the line of their statement, and jumps closing a block to the block's.
+	assert_eq!(
+		generated(source).instruction_lines(),
+		[3, 3, 4, 4, 3, 3, 3, 3, 6, 6, 6, 6, 3, 8, 9, 8, 12]
+	);
+}
+
+#[test]
+fn test_expressions() {
+	assert_eq!(
+		code(
+			"ScriptName Test Extends Form
+			Int Property Count Auto
+			Event Test()
+				Count += 1
+				String s = \"a\" + Count
+				Bool b = s && Count || !s
+				Bool c = Self as Form is Actor
+			EndEvent"
+		),
+		[
+			"propget Count self ::temp0",
+			"iadd ::temp0 ::temp0 1",
+			"propset Count self ::temp0",
+			"propget Count self ::temp0",
+			"cast ::temp1 ::temp0",
+			"strcat ::temp2 \"a\" ::temp1",
+			"assign s ::temp2",
+			"cast ::temp5 s",
+			"assign ::temp4 ::temp5",
+			"jmpf ::temp4 label1",
+			"propget Count self ::temp0",
+			"cast ::temp5 ::temp0",
+			"assign ::temp4 ::temp5",
+			"label1:",
+			"assign ::temp3 ::temp4",
+			"jmpt ::temp3 label0",
+			"not ::temp4 s",
+			"assign ::temp3 ::temp4",
+			"label0:",
+			"assign b ::temp3",
+			"cast ::temp6 self",
+			"is ::temp3 ::temp6 Actor",
+			"assign c ::temp3",
+		]
+	);
+}
+
+#[test]
+fn test_literal_casts() {
+	assert_eq!(
+		code(
+			"ScriptName Test
+			Event Test()
+				Bool b = 2
+				b = \"\"
+				String s = 1.5
+				s = False
+				s = None
+			EndEvent"
+		),
+		[
+			"assign b True",
+			"assign b False",
+			"assign s \"1.500000\"",
+			"assign s \"False\"",
+			"assign s \"None\"",
+		]
+	);
+}
+
+#[test]
+fn test_calls() {
+	assert_eq!(
+		code(
+			"ScriptName Test Extends Base
+			Function Foo(Int x, Int y = 2)
+			EndFunction
+			Int Function Bar() Global
+			EndFunction
+			Event Test()
+				Foo(1)
+				Bar()
+				Parent.Test()
+				Debug.Trace(\"hi\")
+				Int i = GetOther().Count()
+			EndEvent"
+		),
+		[
+			"callmethod Foo self ::NoneVar 1 2",
+			"callstatic Test Bar ::NoneVar",
+			"callparent Test ::NoneVar",
+			"callstatic Debug Trace ::NoneVar \"hi\" 0",
+			"callmethod GetOther self ::temp0",
+			"callmethod Count ::temp0 ::temp1",
+			"assign i ::temp1",
+		]
+	);
+}
+
+#[test]
+fn test_members() {
+	let code = generated(
+		"ScriptName Test
+		Struct Point
+			Float x
+		EndStruct
+		Int Property Count Auto
+		Function Test(Point p, ObjectReference ref)
+			Int[] values = new Int[3]
+			values[0] = values.Length
+			Int i = values.Find(1)
+			values.Add(i)
+			p.x = values[1]
+			Self.Count = p.x as Int
+			ref.X = 1.0
+		EndFunction",
+	);
+
+	assert_eq!(
+		code.ir.iter().map(ToString::to_string).collect::<Vec<_>>(),
+		[
+			"array_create ::temp0 3",
+			"assign values ::temp0",
+			"array_length ::temp1 values",
+			"array_setelement values 0 ::temp1",
+			"array_findelement values ::temp1 1 0",
+			"assign i ::temp1",
+			"array_add values i 1",
+			"array_getelement ::temp1 values 1",
+			"cast ::temp2 ::temp1",
+			"struct_set p x ::temp2",
+			"struct_get ::temp2 p x",
+			"cast ::temp1 ::temp2",
+			"propset Count self ::temp1",
+			"propset X ref 1.0",
+		]
+	);
+
+	assert_eq!(
+		code.locals[..4],
+		[
+			("values".to_owned(), "Int[]".to_owned()),
+			("::temp0".to_owned(), "Int[]".to_owned()),
+			("::temp1".to_owned(), "Int".to_owned()),
+			("i".to_owned(), "Int".to_owned()),
+		]
+	);
+}
+
+#[test]
+fn test_temps() {
+	let source = "ScriptName Test
+		Function Test(Int a, Float f)
+			Int x = a * 2 + a * 3
+			Float y = f * 2.0 + a
+			String s = \"\" + (a * 4)
+		EndFunction";
+
+	// Temporaries are reused once read, but only for values of the same type.
+	assert_eq!(
+		code(source),
+		[
+			"imul ::temp0 a 2",
+			"imul ::temp1 a 3",
+			"iadd ::temp2 ::temp0 ::temp1",
+			"assign x ::temp2",
+			"fmul ::temp3 f 2.0",
+			"cast ::temp4 a",
+			"fadd ::temp5 ::temp3 ::temp4",
+			"assign y ::temp5",
+			"imul ::temp0 a 4",
+			"cast ::temp6 ::temp0",
+			"strcat ::temp7 \"\" ::temp6",
+			"assign s ::temp7",
+		]
+	);
+
+	let temps = generated(source)
+		.locals
+		.into_iter()
+		.filter(|(name, _)| name.starts_with("::temp"))
+		.map(|(_, ty)| ty)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		temps,
+		["Int", "Int", "Int", "Float", "Float", "Float", "String", "String"]
+	);
+}
+
+#[test]
+fn test_untyped() {
+	// Without knowing what the parent declares, the sum can't be given a local.
+	let ast = parse_module(
+		"ScriptName Test Extends Unloaded
+		Function Test()
+			Int total = Count() + 1
+		EndFunction",
+	)
+	.unwrap();
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+	let calls = resolve_calls(&ast, &resolution, &(), &typing, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	generate(
+		&ast,
+		&ast.statements[0],
+		&resolution,
+		&(),
+		&calls,
+		&typing,
+		&mut diagnostics,
+	);
+	assert_eq!(
+		diagnostics
+			.into_iter()
+			.map(|d| d.message)
+			.collect::<Vec<_>>(),
+		["cannot generate code for a value whose type is unknown"]
+	);
+}
+
+#[test]
+fn test_undeclared() {
+	// Calls can't be generated without knowing the parameters, except for those every script has.
+	let ast = parse_module(
+		"ScriptName Test
+		Function Test()
+			GotoState(\"Busy\")
+			Missing(1)
+		EndFunction",
+	)
+	.unwrap();
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+	let calls = resolve_calls(&ast, &resolution, &(), &typing, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	let code = generate(
+		&ast,
+		&ast.statements[0],
+		&resolution,
+		&(),
+		&calls,
+		&typing,
+		&mut diagnostics,
+	);
+	assert_eq!(
+		code.ir[0].to_string(),
+		"callmethod GotoState self ::NoneVar \"Busy\""
+	);
+	assert_eq!(
+		diagnostics
+			.into_iter()
+			.map(|d| d.message)
+			.collect::<Vec<_>>(),
+		["cannot call a function whose declaration isn't loaded"]
+	);
+}
@@ -0,0 +1,17 @@
+//! Fixtures shared by the integration tests.
+
+use std::path::PathBuf;
+
+/// Creates a fresh directory with the given files inside.
+pub fn directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("cyperus-{}-{name}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+
+	for (path, source) in files {
+		let path = dir.join(path);
+		std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+		std::fs::write(path, source).unwrap();
+	}
+
+	dir
+}
@@ -0,0 +1,402 @@
+/*!
+	Testing the options of a compilation and what it produces.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod common;
+
+use common::directory;
+use cyperus::{
+	compiler::{compile, CompileOptions, Game, Metadata, OptLevel},
+	parse_module,
+};
+use std::path::Path;
+
+/// Reads the big-endian header of a Skyrim script.
+struct Reader<'a> {
+	bytes: &'a [u8],
+	offset: usize,
+}
+
+impl<'a> Reader<'a> {
+	/// Starts after the magic number and version, at the compilation time.
+	fn new(bytes: &'a [u8]) -> Self {
+		Self { bytes, offset: 8 }
+	}
+
+	fn bytes(&mut self, len: usize) -> &'a [u8] {
+		self.offset += len;
+		&self.bytes[self.offset - len..self.offset]
+	}
+
+	fn u64(&mut self) -> u64 {
+		u64::from_be_bytes(self.bytes(8).try_into().unwrap())
+	}
+
+	fn wstring(&mut self) -> String {
+		let len = u16::from_be_bytes(self.bytes(2).try_into().unwrap()) as usize;
+		String::from_utf8(self.bytes(len).to_vec()).unwrap()
+	}
+}
+
+fn errors(source: &str, options: &CompileOptions) -> Vec<String> {
+	match compile(&parse_module(source).unwrap(), options) {
+		Ok(_) => vec![],
+		Err(diagnostics) => diagnostics.into_iter().map(|d| d.message).collect(),
+	}
+}
+
+#[test]
+fn test_output() {
+	let options = CompileOptions {
+		output_dir: "out".into(),
+		..CompileOptions::new(Game::Fallout4)
+	};
+	let output = compile(&parse_module("ScriptName Quests:Intro").unwrap(), &options).unwrap();
+
+	assert_eq!(output.path, Path::new("out/Quests/Intro.pex"));
+	assert_eq!(output.bytes[..4], [0xDE, 0xC0, 0x57, 0xFA]);
+	assert!(output.dependencies.is_empty());
+	assert!(output.diagnostics.is_empty());
+}
+
+#[test]
+fn test_states() {
+	// Scripts are checked against their own states without any import directories.
+	let options = CompileOptions::new(Game::Skyrim);
+	assert_eq!(
+		errors(
+			"ScriptName A
+			Auto State One
+			EndState
+			Auto State Two
+				Int v
+			EndState",
+			&options,
+		),
+		[
+			"Only one state can be Auto, but One and Two both are",
+			"v cannot be declared inside of state Two",
+		]
+	);
+}
+
+#[test]
+fn test_imports() {
+	let dir = directory(
+		"imports",
+		&[
+			("Form.psc", "ScriptName Form"),
+			(
+				"Actor.psc",
+				"ScriptName Actor Extends Form
+				Function Kill()
+				EndFunction",
+			),
+			(
+				"Debug.psc",
+				"ScriptName Debug
+				Function Trace(String asTextToPrint, Int aiSeverity = 0) Global Native",
+			),
+		],
+	);
+	let options = CompileOptions {
+		imports: vec![dir.clone()],
+		..CompileOptions::new(Game::Skyrim)
+	};
+
+	// Everything that was loaded is a dependency, but not the script itself.
+	let output = compile(
+		&parse_module(
+			"ScriptName Test Extends Actor
+			Event OnInit()
+				Debug.Trace(\"Hi\")
+			EndEvent",
+		)
+		.unwrap(),
+		&options,
+	)
+	.unwrap();
+	assert_eq!(
+		output.dependencies,
+		[
+			dir.join("Actor.psc"),
+			dir.join("Form.psc"),
+			dir.join("Debug.psc")
+		]
+	);
+
+	// Scripts are checked against their parents.
+	assert_eq!(
+		errors(
+			"ScriptName Test Extends Actor
+			Int Function Kill()
+				Return 0
+			EndFunction",
+			&options,
+		),
+		["Test.Kill does not match the signature of Actor.Kill: returns Int instead of None"]
+	);
+
+	// Loaded scripts tell upcasts from downcasts, which need an explicit cast.
+	assert_eq!(
+		errors(
+			"ScriptName Test Extends Actor
+			Function Foo(Form f)
+				Actor a = f
+				Actor b = f as Actor
+				Form c = b
+			EndFunction",
+			&options,
+		),
+		["expected Actor, got Form"]
+	);
+	assert_eq!(
+		errors("ScriptName Test Extends Missing", &options),
+		["Script Missing was not found in any import directory"]
+	);
+}
+
+#[test]
+fn test_inherited() {
+	let count = "ScriptName A Extends ObjectReference
+		Int Function Count()
+			Return GetItemCount(None) + 1
+		EndFunction";
+	let disable = "ScriptName A Extends Quest
+		Event OnInit()
+			PlayerRef.Disable()
+		EndEvent";
+
+	// Without the parents, what they declare can't be known.
+	let options = CompileOptions::new(Game::Skyrim);
+	assert_eq!(
+		errors(count, &options),
+		["type of GetItemCount is unknown, as it isn't declared in any loaded script"]
+	);
+	assert_eq!(
+		errors(disable, &options),
+		["PlayerRef could be a script or a property inherited from Quest, which isn't loaded"]
+	);
+
+	let dir = directory(
+		"inherited",
+		&[
+			("Form.psc", "ScriptName Form"),
+			(
+				"ObjectReference.psc",
+				"ScriptName ObjectReference Extends Form
+				Int Function GetItemCount(Form akItem) Native
+				Function Disable(Bool abFadeOut = False) Native",
+			),
+			(
+				"Quest.psc",
+				"ScriptName Quest Extends Form
+				ObjectReference Property PlayerRef Auto",
+			),
+		],
+	);
+	let options = CompileOptions {
+		imports: vec![dir],
+		assembly: true,
+		..CompileOptions::new(Game::Skyrim)
+	};
+	let listing = |source| {
+		let output = compile(&parse_module(source).unwrap(), &options).unwrap();
+		output.assembly.unwrap()
+	};
+
+	let count = listing(count);
+	for expected in [
+		".local ::temp0 Int\n",
+		"callmethod GetItemCount self ::temp0 None\n",
+		"iadd ::temp1 ::temp0 1\n",
+	] {
+		assert!(count.contains(expected), "{expected:?} in\n{count}");
+	}
+
+	let disable = listing(disable);
+	for expected in [
+		"propget PlayerRef self ::temp0\n",
+		"callmethod Disable ::temp0 ::NoneVar False\n",
+	] {
+		assert!(disable.contains(expected), "{expected:?} in\n{disable}");
+	}
+}
+
+#[test]
+fn test_external_calls() {
+	let dir = directory(
+		"external-calls",
+		&[
+			(
+				"Base.psc",
+				"ScriptName Base
+				Function Take(Int a, Bool b = True, Bool c = True)
+				EndFunction",
+			),
+			(
+				"Util.psc",
+				"ScriptName Util
+				Function Wait(Float afSeconds) Global Native",
+			),
+			(
+				"Timer.psc",
+				"ScriptName Timer
+				Function Wait(Float afSeconds) Global Native",
+			),
+		],
+	);
+	let options = CompileOptions {
+		imports: vec![dir],
+		assembly: true,
+		..CompileOptions::new(Game::Fallout4)
+	};
+
+	// Arguments of inherited functions are put in order, with defaults filled in.
+	let output = compile(
+		&parse_module(
+			"ScriptName Child Extends Base
+			Import Util
+			Event OnInit()
+				Take(1)
+				Take(c = False, a = 3)
+				Wait(1)
+			EndEvent",
+		)
+		.unwrap(),
+		&options,
+	)
+	.unwrap();
+	let listing = output.assembly.unwrap();
+	for expected in [
+		"callmethod Take self ::NoneVar 1 True True\n",
+		"callmethod Take self ::NoneVar 3 True False\n",
+		"callstatic Util Wait ::NoneVar 1.0\n",
+	] {
+		assert!(listing.contains(expected), "{expected:?} in\n{listing}");
+	}
+
+	assert_eq!(
+		errors(
+			"ScriptName Child Extends Base
+			Import Util
+			Import Timer
+			Event OnInit()
+				Take(b = False)
+				Wait(1)
+				Missing()
+			EndEvent",
+			&options,
+		),
+		[
+			"Take is missing a value for parameter a",
+			"Wait could be a global function of any of Util, Timer",
+		]
+	);
+	assert_eq!(
+		errors(
+			"ScriptName Child Extends Base
+			Event OnInit()
+				Missing()
+			EndEvent",
+			&options,
+		),
+		["type of Missing is unknown, as it isn't declared in any loaded script"]
+	);
+}
+
+#[test]
+fn test_flags_file() {
+	let dir = directory("flags-file", &[("Custom.flg", "Flag Hidden 0 { Script")]);
+	let mut options = CompileOptions {
+		flags_file: Some(dir.join("Custom.flg")),
+		..CompileOptions::new(Game::Skyrim)
+	};
+	assert_eq!(
+		errors("ScriptName Test", &options),
+		[format!(
+			"Invalid flags file {}: Expected }}, but got end of input",
+			dir.join("Custom.flg").display()
+		)]
+	);
+
+	options.flags_file = Some(dir.join("Missing.flg"));
+	let errors = errors("ScriptName Test", &options);
+	assert!(
+		errors[0].starts_with("Failed to read flags file"),
+		"{errors:?}"
+	);
+}
+
+#[test]
+fn test_optimization() {
+	let source = "ScriptName Test
+		Int Function Foo()
+			Int x = 1 + 2
+			Return x
+		EndFunction";
+
+	let compile = |optimization| {
+		let options = CompileOptions {
+			optimization,
+			..CompileOptions::new(Game::Skyrim)
+		};
+		compile(&parse_module(source).unwrap(), &options)
+			.unwrap()
+			.bytes
+	};
+
+	// Folding the addition leaves no temporary to hold its result.
+	let has_temp = |bytes: &[u8]| bytes.windows(6).any(|w| w == b"::temp");
+	assert!(has_temp(&compile(OptLevel::None)));
+	assert!(!has_temp(&compile(OptLevel::Full)));
+}
+
+#[test]
+fn test_reproducible() {
+	let ast = parse_module(
+		"ScriptName Test
+		Int Property Count Auto
+		Function Foo()
+		EndFunction",
+	)
+	.unwrap();
+	let header = |bytes: &[u8]| {
+		let mut reader = Reader::new(bytes);
+		let time = reader.u64();
+		(time, [(); 3].map(|_| reader.wstring()))
+	};
+
+	let options = CompileOptions {
+		debug_info: true,
+		metadata: Metadata {
+			compilation_time: Some(1_000),
+			source: Some("Scripts\\Test.psc".to_owned()),
+			user: Some("builder".to_owned()),
+			machine: Some("ci".to_owned()),
+		},
+		..CompileOptions::new(Game::Skyrim)
+	};
+	let bytes = compile(&ast, &options).unwrap().bytes;
+	assert_eq!(
+		header(&bytes),
+		(
+			1_000,
+			["Scripts\\Test.psc".into(), "builder".into(), "ci".into()]
+		)
+	);
+	assert_eq!(bytes, compile(&ast, &options).unwrap().bytes);
+
+	// Whatever isn't given is left out, rather than taken from the environment.
+	let options = CompileOptions {
+		metadata: Metadata::default(),
+		..CompileOptions::new(Game::Skyrim)
+	};
+	let bytes = compile(&ast, &options).unwrap().bytes;
+	assert_eq!(
+		header(&bytes),
+		(0, ["Test.psc".into(), "".into(), "".into()])
+	);
+}
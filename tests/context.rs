@@ -0,0 +1,191 @@
+/*!
+	Testing Global functions and uses of Self and Parent.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{check_context, check_inheritance, resolve},
+	parse_module,
+	parser::ast::{Expression, Statement},
+	Diagnostics, Project,
+};
+
+fn errors(source: &str) -> Vec<String> {
+	let ast = parse_module(source).unwrap();
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	check_context(&ast, &resolution, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_parsing() {
+	let ast = parse_module(
+		"ScriptName Test Extends Base
+		Int Function Foo() Global
+			return 5
+		EndFunction
+		Function Bar() Native Global
+		Function Baz()
+			Self.Foo()
+			Parent.Baz()
+		EndFunction",
+	)
+	.unwrap();
+
+	assert!(matches!(
+		ast.statements[0],
+		Statement::Function {
+			is_global: true,
+			..
+		}
+	));
+	assert!(matches!(
+		ast.statements[1],
+		Statement::NativeFunction {
+			is_global: true,
+			..
+		}
+	));
+
+	let Statement::Function {
+		is_global, body, ..
+	} = &ast.statements[2]
+	else {
+		unreachable!()
+	};
+	assert!(!is_global);
+
+	let bases = body
+		.iter()
+		.map(|stmt| match stmt {
+			Statement::Expression {
+				expr: Expression::Call(target, _),
+				..
+			} => match target.as_ref() {
+				Expression::DotIndex(base, _) => base.as_ref(),
+				target => panic!("expected a method call, got {target:?}"),
+			},
+			stmt => panic!("expected a call, got {stmt:?}"),
+		})
+		.collect::<Vec<_>>();
+
+	assert!(matches!(
+		bases[..],
+		[Expression::SelfRef, Expression::Parent]
+	));
+}
+
+#[test]
+fn test_valid() {
+	assert!(errors(
+		"ScriptName Valid Extends Base
+		Int Count
+		Int Function Double(Int x) Global
+			return Twice(x)
+		EndFunction
+		Int Function Twice(Int x) Global
+			Int count = x * 2
+			return count
+		EndFunction
+		Event OnInit()
+			Parent.OnInit()
+			Self.Count = Double(Count)
+			GotoState(\"Ready\")
+		EndEvent"
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_global() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Int Count
+			Int Property Total Auto
+			Function Member()
+			EndFunction
+			Int Function Foo() Global
+				Member()
+				Count = Total
+				GotoState(\"Ready\")
+				Self.Member()
+				return Count
+			EndFunction"
+		),
+		[
+			"Global function Foo cannot call member function Member without an object",
+			"Global function Foo cannot access Total, which belongs to an instance of the script",
+			"Global function Foo cannot access Count, which belongs to an instance of the script",
+			"Global function Foo cannot call GotoState, as it has no state",
+			"Self cannot be used in Global function Foo",
+			"Global function Foo cannot access Count, which belongs to an instance of the script",
+		]
+	);
+}
+
+#[test]
+fn test_parent() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Event OnInit()
+				Parent.OnInit()
+			EndEvent"
+		),
+		["Parent cannot be used in Invalid, which does not extend another script"]
+	);
+
+	assert_eq!(
+		errors(
+			"ScriptName Invalid Extends Base
+			Function Foo() Global
+				Parent.Foo()
+			EndFunction
+			Function Bar()
+				Base x = Parent
+			EndFunction"
+		),
+		[
+			"Parent cannot be used in Global function Foo",
+			"Parent can only be used to call a function, as in Parent.OnInit()"
+		]
+	);
+}
+
+#[test]
+fn test_overriding() {
+	let mut project = Project::new(Vec::<String>::new());
+	for source in [
+		"ScriptName Child Extends Base
+		Event OnInit()
+			Parent.OnInit()
+		EndEvent
+		Function Foo()
+			Parent.OnInit()
+		EndFunction",
+		"ScriptName Base
+		Event OnInit()
+		EndEvent",
+	] {
+		let ast = parse_module(source).unwrap();
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+
+	let mut diagnostics = Diagnostics::new();
+	check_inheritance(&project, "Child", &mut diagnostics);
+
+	let errors = diagnostics
+		.into_iter()
+		.map(|d| d.message)
+		.collect::<Vec<_>>();
+	assert_eq!(
+		errors,
+		["Child.Foo uses Parent, but does not override a function of a parent script"]
+	);
+}
@@ -0,0 +1,121 @@
+/*!
+	Testing the type conversion rules.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{resolve, typecheck, Conversion, Hierarchy, Lowering, Ty},
+	optimize, parse_module,
+	parser::ast::{Expression, Statement},
+	Diagnostics,
+};
+
+/// Actor extends ObjectReference, which extends Form.
+struct Forms;
+
+impl Hierarchy for Forms {
+	fn extends(&self, child: &str, parent: &str) -> Option<bool> {
+		let chain = ["actor", "objectreference", "form"];
+		let child = chain.iter().position(|x| x.eq_ignore_ascii_case(child))?;
+		let parent = chain.iter().position(|x| x.eq_ignore_ascii_case(parent))?;
+		Some(child <= parent)
+	}
+}
+
+fn between(from: &str, to: &str) -> Conversion {
+	Conversion::between(&Ty::parse(from), &Ty::parse(to), &Forms)
+}
+
+#[test]
+fn test_primitives() {
+	use Conversion::*;
+	use Lowering::*;
+
+	for (from, to, expected) in [
+		("int", "int", Implicit(Assign)),
+		("Int", "Float", Implicit(Cast)),
+		("Float", "Int", Explicit(Cast)),
+		("String", "Int", Explicit(Cast)),
+		("Int[]", "String", Implicit(Cast)),
+		("Actor", "Bool", Implicit(Cast)),
+		("None", "Actor", Implicit(Assign)),
+		("None", "Int", Illegal),
+		("Int", "Var", Implicit(Cast)),
+		("Var", "Float", Explicit(Cast)),
+		("Int[]", "Var", Illegal),
+		("Int", "None", Illegal),
+		("Int[]", "Float[]", Illegal),
+	] {
+		assert_eq!(between(from, to), expected, "{from} -> {to}");
+	}
+}
+
+#[test]
+fn test_objects() {
+	use Conversion::*;
+	use Lowering::*;
+
+	for (from, to, expected) in [
+		("Actor", "Form", Implicit(Cast)),
+		("Form", "Actor", Explicit(Cast)),
+		("Actor[]", "ObjectReference[]", Implicit(Cast)),
+		("Form[]", "Actor[]", Explicit(Cast)),
+		("Actor", "Int", Illegal),
+		("Int", "Actor", Illegal),
+	] {
+		assert_eq!(between(from, to), expected, "{from} -> {to}");
+	}
+}
+
+#[test]
+fn test_cast_check() {
+	let ast = parse_module(
+		"ScriptName Test
+		int a = None as Int
+		int[] b = new float[2] as int[]
+		float c = \"5.5\" as Float",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	typecheck(&ast, &resolution, &(), &mut diagnostics);
+	let errors = diagnostics
+		.into_iter()
+		.map(|d| d.message)
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		errors,
+		["cannot cast None to Int", "cannot cast Float[] to Int[]"]
+	);
+}
+
+#[test]
+fn test_folding() {
+	let mut ast = parse_module(
+		"ScriptName Test
+		float a = 5 as Float
+		int b = 7.9 as Int
+		string c = True as String
+		Actor d = None as Actor",
+	)
+	.unwrap();
+
+	optimize(&mut ast);
+
+	let values = ast
+		.statements
+		.iter()
+		.map(|stmt| match stmt {
+			Statement::Definition { value, .. } => value,
+			stmt => panic!("expected a definition, got {stmt:?}"),
+		})
+		.collect::<Vec<_>>();
+
+	assert!(matches!(values[0], Expression::Float(f) if *f == 5.0));
+	assert!(matches!(values[1], Expression::Integer(7)));
+	assert!(matches!(values[2], Expression::String(s) if s == "\"True\""));
+	assert!(matches!(values[3], Expression::Cast(..)));
+}
@@ -0,0 +1,91 @@
+/*!
+	Testing diagnostics reported by the parser and compiler.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{check_inheritance, compile, validate, CompileOptions, Game},
+	diagnostics::Severity,
+	parse, parse_module, Project,
+};
+
+#[test]
+fn test_parse() {
+	let (ast, diagnostics) = parse("ScriptName Test\nint x = ");
+	assert!(ast.is_none());
+	assert!(diagnostics.has_errors());
+
+	let diagnostic = diagnostics.iter().next().unwrap();
+	assert_eq!(diagnostic.code, "E0001");
+	assert_eq!(diagnostic.span.map(|s| s.line), Some(2));
+
+	let (ast, diagnostics) = parse("ScriptName Test");
+	assert!(ast.is_some());
+	assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_spans() {
+	let ast = parse_module(
+		"ScriptName Test
+		int x
+		Function Foo()
+			y = 5
+		EndFunction
+		float x",
+	)
+	.unwrap();
+
+	let diagnostics = validate(&ast, Game::Fallout4);
+	let found = diagnostics
+		.iter()
+		.map(|d| (d.severity, d.code, d.span.unwrap().line))
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		found,
+		[(Severity::Error, "E0101", 6), (Severity::Error, "E0102", 4)]
+	);
+
+	// Redeclarations point back at the first declaration.
+	let redeclared = diagnostics.iter().next().unwrap();
+	assert_eq!(redeclared.labels[0].span.line, 2);
+	assert!(redeclared.labels[0].file.is_none());
+}
+
+#[test]
+fn test_labels() {
+	let mut project = Project::new(Vec::<String>::new());
+	for source in [
+		"ScriptName Child Extends Base\nInt Function Foo()\nEndFunction",
+		"ScriptName Base\n\nFunction Foo()\nEndFunction",
+	] {
+		let ast = parse_module(source).unwrap();
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+
+	let mut diagnostics = Default::default();
+	check_inheritance(&project, "Child", &mut diagnostics);
+
+	let diagnostic = diagnostics.iter().next().unwrap();
+	assert_eq!(diagnostic.code, "E0301");
+	assert_eq!(diagnostic.span.unwrap().line, 2);
+
+	let label = &diagnostic.labels[0];
+	assert_eq!(label.span.line, 3);
+	assert_eq!(label.file.as_deref(), Some("Base.psc".as_ref()));
+}
+
+#[test]
+fn test_compile() {
+	// Errors are reported instead of panicking.
+	let ast = parse_module("ScriptName Test\nint x = \"five\"").unwrap();
+	let diagnostics = compile(&ast, &CompileOptions::new(Game::Fallout4)).unwrap_err();
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(
+		diagnostics.to_string(),
+		"error[E0201]: expected Int, got String\n  --> 2:1"
+	);
+}
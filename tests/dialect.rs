@@ -0,0 +1,154 @@
+/*!
+	Testing struct and array rules for each game.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{check_dialect, check_opcodes, compile, resolve, typecheck, CompileOptions, Game},
+	parse_module, Diagnostics,
+};
+
+fn errors(game: Game, source: &str) -> Vec<String> {
+	let mut diagnostics = Diagnostics::new();
+	check_dialect(&parse_module(source).unwrap(), game, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_structs() {
+	const SOURCE: &str = "ScriptName Test
+	Struct Point
+		Float x
+		Float y = 1.0
+		Actor owner
+	EndStruct
+	Struct Line
+		Point start
+		Int[] values
+		Var anything
+	EndStruct";
+
+	assert_eq!(
+		errors(Game::Fallout4, SOURCE),
+		[
+			"Field start of struct Line cannot be Point, only primitive types, Var and objects are allowed",
+			"Field values of struct Line cannot be Int[], only primitive types, Var and objects are allowed",
+		]
+	);
+
+	assert_eq!(
+		errors(Game::Skyrim, SOURCE),
+		[
+			"Structs are not supported in Skyrim",
+			"Structs are not supported in Skyrim"
+		]
+	);
+
+	// Var members can hold any of the allowed types.
+	let ast = parse_module(
+		"ScriptName Test
+		Struct Entry
+			Var value
+		EndStruct",
+	)
+	.unwrap();
+	assert!(compile(&ast, &CompileOptions::new(Game::Fallout4)).is_ok());
+}
+
+#[test]
+fn test_arrays() {
+	const SOURCE: &str = "ScriptName Test
+	Function Foo(Int size)
+		Int[] a = new Int[128]
+		Int[] b = new Int[size]
+		Int[] c = new Int[0]
+		Int[] d = new Int[][5]
+	EndFunction";
+
+	assert_eq!(
+		errors(Game::Fallout4, SOURCE),
+		["Arrays cannot be multi-dimensional, so Int[] is not a valid element type"]
+	);
+
+	assert_eq!(
+		errors(Game::Skyrim, SOURCE),
+		[
+			"Array size must be a constant between 1 and 128 in Skyrim",
+			"Array size must be a constant between 1 and 128 in Skyrim",
+			"Arrays cannot be multi-dimensional, so Int[] is not a valid element type",
+		]
+	);
+}
+
+#[test]
+fn test_member_access() {
+	let ast = parse_module(
+		"ScriptName Test
+		Struct Point
+			Float x
+		EndStruct
+		Point[] points
+		Float Function First()
+			Float x = points[0].x
+			return points[0].z
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	typecheck(&ast, &resolution, &(), &mut diagnostics);
+
+	let errors = diagnostics
+		.into_iter()
+		.map(|d| d.message)
+		.collect::<Vec<_>>();
+	assert_eq!(errors, ["Point has no member z"]);
+}
+
+#[test]
+fn test_namespaces() {
+	const SOURCE: &str = "ScriptName Outer:Inner:Test";
+
+	assert!(errors(Game::Fallout4, SOURCE).is_empty());
+	assert_eq!(
+		errors(Game::Skyrim, SOURCE),
+		["Script Outer:Inner:Test has a namespace, which is not supported in Skyrim"]
+	);
+}
+
+#[test]
+fn test_opcodes() {
+	const SOURCE: &str = "ScriptName Test
+	Function Foo(Int[] values, Form item, Quest owner)
+		values.Add(3)
+		values.RemoveLast()
+		Int i = values.Find(3)
+		Bool b = item is Form
+		owner.Clear()
+	EndFunction";
+
+	let errors = |game| {
+		let ast = parse_module(SOURCE).unwrap();
+		let mut diagnostics = Diagnostics::new();
+		let resolution = resolve(&ast, &mut diagnostics);
+		let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+		check_opcodes(&ast, game, &typing, &mut diagnostics);
+		diagnostics
+			.into_iter()
+			.map(|d| d.message)
+			.collect::<Vec<_>>()
+	};
+
+	// Methods of objects that share a name with those of arrays are left alone.
+	assert!(errors(Game::Fallout4).is_empty());
+	assert_eq!(
+		errors(Game::Skyrim),
+		[
+			"Array method Add is not supported in Skyrim",
+			"Array method RemoveLast is not supported in Skyrim",
+			"The is operator is not supported in Skyrim",
+		]
+	);
+}
@@ -0,0 +1,87 @@
+/*!
+	Testing which expressions can be used as statements.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_effects, parse_module, Diagnostics};
+
+fn check(source: &str) -> Diagnostics {
+	let mut diagnostics = Diagnostics::new();
+	check_effects(&parse_module(source).unwrap(), &mut diagnostics);
+	diagnostics
+}
+
+#[test]
+fn test_calls() {
+	assert!(check(
+		"ScriptName Valid
+		Event OnInit()
+			Debug.Trace(\"Hello\")
+			GetOwner().Kill()
+			Foo()
+		EndEvent"
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_no_effect() {
+	let diagnostics = check(
+		"ScriptName Invalid
+		Event OnInit()
+			x == 5
+			count + 1
+			\"text\"
+			points[0].x == 2.0
+			(x + 1) * 2
+			x
+		EndEvent",
+	);
+
+	let found = diagnostics
+		.iter()
+		.map(|d| {
+			(
+				d.message.as_str(),
+				d.fix.as_ref().map(|fix| fix.replacement.as_str()),
+			)
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		found,
+		[
+			(
+				"Comparison has no effect as a statement, only function calls can be used on their own",
+				Some("x = 5")
+			),
+			(
+				"Arithmetic has no effect as a statement, only function calls can be used on their own",
+				Some("count += 1")
+			),
+			(
+				"Literal has no effect as a statement, only function calls can be used on their own",
+				None
+			),
+			(
+				"Comparison has no effect as a statement, only function calls can be used on their own",
+				Some("points[0].x = 2")
+			),
+			(
+				"Arithmetic has no effect as a statement, only function calls can be used on their own",
+				None
+			),
+			(
+				"Variable has no effect as a statement, only function calls can be used on their own",
+				None
+			),
+		]
+	);
+
+	// The fix replaces the whole statement.
+	let first = diagnostics.iter().next().unwrap();
+	let fix = first.fix.as_ref().unwrap();
+	assert_eq!(Some(fix.span), first.span);
+	assert_eq!(fix.span.line, 3);
+}
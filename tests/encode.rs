@@ -0,0 +1,625 @@
+/*!
+	Testing the binary PEX writer.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod common;
+
+use common::directory;
+use cyperus::{
+	compiler::{compile, pex::*, CompileOptions, EncodeError, Game},
+	parse_module,
+};
+
+/// Reads a PEX file front to back.
+struct Reader<'a> {
+	bytes: &'a [u8],
+	offset: usize,
+	little_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+	/// Starts after the magic number and version, at the compilation time.
+	fn new(bytes: &'a [u8]) -> Self {
+		Self {
+			bytes,
+			offset: 8,
+			little_endian: bytes[0] == 0xDE,
+		}
+	}
+
+	fn bytes(&mut self, len: usize) -> &'a [u8] {
+		self.offset += len;
+		&self.bytes[self.offset - len..self.offset]
+	}
+
+	fn u8(&mut self) -> u8 {
+		self.bytes(1)[0]
+	}
+
+	fn u16(&mut self) -> usize {
+		let bytes = self.bytes(2).try_into().unwrap();
+		match self.little_endian {
+			true => u16::from_le_bytes(bytes) as usize,
+			false => u16::from_be_bytes(bytes) as usize,
+		}
+	}
+
+	fn wstring(&mut self) -> String {
+		let len = self.u16();
+		String::from_utf8(self.bytes(len).to_vec()).unwrap()
+	}
+
+	/// Skips the rest of the header and reads the string table.
+	fn string_table(&mut self) -> Vec<String> {
+		// Compilation time, then source, user and machine names.
+		self.bytes(8);
+		for _ in 0..3 {
+			self.wstring();
+		}
+
+		let count = self.u16();
+		(0..count).map(|_| self.wstring()).collect()
+	}
+}
+
+/// Compiles a script that has nothing wrong with it.
+fn pex(source: &str, game: Game, debug_info: bool) -> Vec<u8> {
+	let options = CompileOptions {
+		debug_info,
+		..CompileOptions::new(game)
+	};
+	let output = compile(&parse_module(source).unwrap(), &options).unwrap();
+	assert!(output.diagnostics.is_empty(), "{}", output.diagnostics);
+	output.bytes
+}
+
+fn string_table(bytes: &[u8]) -> Vec<String> {
+	Reader::new(bytes).string_table()
+}
+
+#[test]
+fn test_header() {
+	let bytes = pex(
+		"ScriptName Test Extends Form Conditional
+		Int Count = 5
+		Function Foo(Int x)
+		EndFunction
+		Auto State Ready
+			Event OnInit()
+			EndEvent
+		EndState",
+		Game::Skyrim,
+		false,
+	);
+
+	assert_eq!(bytes[..4], [0xFA, 0x57, 0xC0, 0xDE]);
+	assert_eq!(bytes[4..6], [3, 2]);
+	assert_eq!(bytes[6..8], [0, 1]);
+	assert_eq!(bytes[16..18], [0, 8]);
+	assert_eq!(&bytes[18..26], b"Test.psc");
+
+	let table = string_table(&bytes);
+	for name in [
+		"Test",
+		"Form",
+		"Count",
+		"Int",
+		"Foo",
+		"x",
+		"Ready",
+		"OnInit",
+		"None",
+		"conditional",
+	] {
+		assert!(table.iter().any(|s| s == name), "{name} is missing");
+	}
+}
+
+#[test]
+fn test_state_functions() {
+	// Skyrim scripts without a parent define their own GetState and GotoState.
+	for (source, generated) in [
+		("ScriptName Test", true),
+		("ScriptName Test Extends Form", false),
+	] {
+		let bytes = pex(source, Game::Skyrim, false);
+
+		let table = string_table(&bytes);
+		for name in ["GetState", "GotoState", "::State", "onBeginState"] {
+			assert_eq!(table.iter().any(|s| s == name), generated, "{name}");
+		}
+	}
+}
+
+#[test]
+fn test_tables() {
+	let pex = Pex {
+		header: Header {
+			game: Game::Skyrim,
+			compilation_time: 42,
+			source: "A.psc".to_owned(),
+			user: String::new(),
+			machine: String::new(),
+		},
+		debug_info: None,
+		user_flags: vec![],
+		objects: vec![Object {
+			name: "A".to_owned(),
+			parent: String::new(),
+			doc: String::new(),
+			is_const: false,
+			user_flags: 0,
+			auto_state: String::new(),
+			structs: vec![],
+			variables: vec![Variable {
+				name: "x".to_owned(),
+				ty: "Int".to_owned(),
+				user_flags: 0,
+				value: Value::Int(5),
+				is_const: false,
+			}],
+			properties: vec![],
+			states: vec![State {
+				name: String::new(),
+				functions: vec![Function {
+					name: "Foo".to_owned(),
+					return_type: "None".to_owned(),
+					doc: String::new(),
+					user_flags: 0,
+					is_global: false,
+					is_native: false,
+					parameters: vec![],
+					locals: vec![],
+					instructions: vec![Instruction::new(
+						Opcode::CallMethod,
+						vec![
+							Value::Ident("Foo".to_owned()),
+							Value::Ident("self".to_owned()),
+							Value::Ident("::NoneVar".to_owned()),
+							Value::Int(1),
+						],
+					)],
+				}],
+			}],
+		}],
+	};
+
+	#[rustfmt::skip]
+	let expected: &[u8] = &[
+		// Header
+		0xFA, 0x57, 0xC0, 0xDE, 3, 2, 0, 1,
+		0, 0, 0, 0, 0, 0, 0, 42,
+		0, 5, b'A', b'.', b'p', b's', b'c',
+		0, 0,
+		0, 0,
+		// String table
+		0, 8,
+		0, 1, b'A',
+		0, 0,
+		0, 1, b'x',
+		0, 3, b'I', b'n', b't',
+		0, 3, b'F', b'o', b'o',
+		0, 4, b'N', b'o', b'n', b'e',
+		0, 4, b's', b'e', b'l', b'f',
+		0, 9, b':', b':', b'N', b'o', b'n', b'e', b'V', b'a', b'r',
+		// No debug info or user flags
+		0,
+		0, 0,
+		// Object
+		0, 1,
+		0, 0,
+		0, 0, 0, 74,
+		0, 1, 0, 1, 0, 0, 0, 0, 0, 1,
+		// Variables
+		0, 1,
+		0, 2, 0, 3, 0, 0, 0, 0, 3, 0, 0, 0, 5,
+		// Properties
+		0, 0,
+		// States
+		0, 1,
+		0, 1, 0, 1,
+		0, 4, 0, 5, 0, 1, 0, 0, 0, 0, 0,
+		0, 0, 0, 0,
+		0, 1,
+		23, 1, 0, 4, 1, 0, 6, 1, 0, 7, 3, 0, 0, 0, 1, 3, 0, 0, 0, 1,
+	];
+
+	assert_eq!(pex.encode().unwrap(), expected);
+}
+
+#[test]
+fn test_fallout_4() {
+	let bytes = pex(
+		"ScriptName Outer:Inner:Test Const
+		Struct Point
+			Float x = 1.5
+		EndStruct",
+		Game::Fallout4,
+		false,
+	);
+
+	// Little-endian, version 3.9
+	assert_eq!(bytes[..4], [0xDE, 0xC0, 0x57, 0xFA]);
+	assert_eq!(bytes[4..6], [3, 9]);
+	assert_eq!(bytes[6..8], [2, 0]);
+	assert_eq!(bytes[16..18], [20, 0]);
+	assert_eq!(&bytes[18..38], b"Outer\\Inner\\Test.psc");
+
+	let pex = Pex {
+		header: Header {
+			game: Game::Fallout4,
+			compilation_time: 1,
+			source: String::new(),
+			user: String::new(),
+			machine: String::new(),
+		},
+		debug_info: Some(DebugInfo {
+			modification_time: 2,
+			functions: vec![],
+			property_groups: vec![],
+			struct_orders: vec![StructOrder {
+				object: "A:B".to_owned(),
+				name: "P".to_owned(),
+				members: vec!["x".to_owned()],
+			}],
+		}),
+		user_flags: vec![],
+		objects: vec![Object {
+			name: "A:B".to_owned(),
+			parent: String::new(),
+			doc: String::new(),
+			is_const: true,
+			user_flags: 0,
+			auto_state: String::new(),
+			structs: vec![Struct {
+				name: "P".to_owned(),
+				members: vec![StructMember {
+					name: "x".to_owned(),
+					ty: "Bool".to_owned(),
+					user_flags: 0,
+					value: Value::Bool(true),
+					is_const: false,
+					doc: String::new(),
+				}],
+			}],
+			variables: vec![],
+			properties: vec![],
+			states: vec![],
+		}],
+	};
+
+	#[rustfmt::skip]
+	let expected: &[u8] = &[
+		// Header
+		0xDE, 0xC0, 0x57, 0xFA, 3, 9, 2, 0,
+		1, 0, 0, 0, 0, 0, 0, 0,
+		0, 0,
+		0, 0,
+		0, 0,
+		// String table, starting with the strings of the object
+		5, 0,
+		3, 0, b'A', b':', b'B',
+		0, 0,
+		1, 0, b'P',
+		1, 0, b'x',
+		4, 0, b'B', b'o', b'o', b'l',
+		// Debug info, with no functions or groups and one struct order
+		1,
+		2, 0, 0, 0, 0, 0, 0, 0,
+		0, 0,
+		0, 0,
+		1, 0, 0, 0, 2, 0, 1, 0, 3, 0,
+		// No user flags
+		0, 0,
+		// Object
+		1, 0,
+		0, 0,
+		40, 0, 0, 0,
+		1, 0, 1, 0, 1, 0, 0, 0, 0, 1, 0,
+		// Structs
+		1, 0,
+		2, 0, 1, 0,
+		3, 0, 4, 0, 0, 0, 0, 0, 5, 1, 0, 1, 0,
+		// Variables, properties and states
+		0, 0, 0, 0, 0, 0,
+	];
+
+	assert_eq!(pex.encode().unwrap(), expected);
+}
+
+#[test]
+fn test_properties() {
+	let getter = Function {
+		name: String::new(),
+		return_type: "Int".to_owned(),
+		doc: String::new(),
+		user_flags: 0,
+		is_global: false,
+		is_native: false,
+		parameters: vec![],
+		locals: vec![],
+		instructions: vec![Instruction::new(Opcode::Return, vec![Value::Int(1)])],
+	};
+
+	let auto = Property {
+		name: "Count".to_owned(),
+		ty: "Int".to_owned(),
+		doc: String::new(),
+		user_flags: 0,
+		kind: PropertyKind::Auto("::Count_var".to_owned()),
+	};
+	assert_eq!(auto.flags(), 7);
+
+	let full = Property {
+		kind: PropertyKind::Full {
+			get: Some(Box::new(getter)),
+			set: None,
+		},
+		..auto
+	};
+	assert_eq!(full.flags(), Property::READ);
+
+	assert_eq!(Opcode::from_code(25), Some(Opcode::CallStatic));
+	assert_eq!(Opcode::CallStatic.name(), "callstatic");
+	assert!(Opcode::CallParent.is_variadic());
+	assert!(!Opcode::PropGet.is_variadic());
+	assert!(Opcode::StructGet.is_supported(Game::Fallout4));
+	assert!(!Opcode::StructGet.is_supported(Game::Skyrim));
+}
+
+#[test]
+fn test_debug_info() {
+	let source = "ScriptName Test Extends Form
+		Int Function Foo(Int a)
+			If a > 1
+				a += 1
+			EndIf
+			Return a
+		EndFunction
+		State Busy
+			Event OnInit()
+			EndEvent
+		EndState";
+
+	let bytes = pex(source, Game::Skyrim, true);
+
+	let mut reader = Reader::new(&bytes);
+	let table = reader.string_table();
+	assert_eq!(reader.u8(), 1);
+	reader.bytes(8);
+
+	let mut functions = vec![];
+	for _ in 0..reader.u16() {
+		let [object, state, function] = [(); 3].map(|_| table[reader.u16()].clone());
+		let kind = reader.u8();
+		let lines = (0..reader.u16()).map(|_| reader.u16()).collect::<Vec<_>>();
+		functions.push((object, state, function, kind, lines));
+	}
+
+	assert_eq!(
+		functions,
+		[
+			(
+				"Test".into(),
+				"".into(),
+				"Foo".into(),
+				0,
+				vec![3, 3, 4, 3, 6]
+			),
+			("Test".into(), "Busy".into(), "OnInit".into(), 0, vec![]),
+		]
+	);
+
+	// Release builds leave it out.
+	let bytes = pex(source, Game::Skyrim, false);
+	let mut reader = Reader::new(&bytes);
+	reader.string_table();
+	assert_eq!(reader.u8(), 0);
+}
+
+#[test]
+fn test_property_accessors() {
+	let source = "ScriptName Test
+		Float Property Speed = 2 Auto
+		Int Property Max = 10 AutoReadOnly
+		Int Property Count
+			Int Function Get()
+				Return 5
+			EndFunction
+			Function Set(Int value)
+			EndFunction
+		EndProperty";
+
+	let bytes = pex(source, Game::Skyrim, true);
+
+	let mut reader = Reader::new(&bytes);
+	let table = reader.string_table();
+	assert!(table.contains(&"::Speed_var".to_owned()));
+	assert_eq!(reader.u8(), 1);
+	reader.bytes(8);
+
+	// Accessors are recorded under the name of their property.
+	let mut functions = vec![];
+	for _ in 0..reader.u16() {
+		let [_, _, function] = [(); 3].map(|_| table[reader.u16()].clone());
+		let kind = reader.u8();
+		let lines = (0..reader.u16()).map(|_| reader.u16()).collect::<Vec<_>>();
+		functions.push((function, kind, lines));
+	}
+	assert_eq!(
+		functions,
+		[
+			("Max".into(), 1, vec![3]),
+			("Count".into(), 1, vec![6]),
+			("Count".into(), 2, vec![]),
+		]
+	);
+
+	// The Int the backing variable of Speed starts with is written as a Float.
+	let speed = [0x40, 0, 0, 0];
+	assert!(bytes.windows(5).any(|w| w[0] == 4 && w[1..] == speed));
+}
+
+#[test]
+fn test_groups() {
+	let source = "ScriptName Test
+		Struct Point
+			Float y
+			Float x
+		EndStruct
+		Int Property Count Auto
+		Group Stats
+			Int Property Health Auto
+			Int Property Level Auto
+		EndGroup";
+
+	let bytes = pex(source, Game::Fallout4, true);
+
+	let mut reader = Reader::new(&bytes);
+	let table = reader.string_table();
+	assert_eq!(reader.u8(), 1);
+	reader.bytes(8);
+	assert_eq!(reader.u16(), 0);
+
+	let names = |reader: &mut Reader| {
+		(0..reader.u16())
+			.map(|_| table[reader.u16()].as_str())
+			.collect::<Vec<_>>()
+	};
+
+	// Properties outside of any group come first, in an unnamed group.
+	let mut groups = vec![];
+	for _ in 0..reader.u16() {
+		let [object, name, doc] = [(); 3].map(|_| reader.u16());
+		reader.bytes(4);
+		groups.push((
+			table[object].as_str(),
+			table[name].as_str(),
+			table[doc].as_str(),
+			names(&mut reader),
+		));
+	}
+	assert_eq!(
+		groups,
+		[
+			("Test", "", "", vec!["Count"]),
+			("Test", "Stats", "", vec!["Health", "Level"]),
+		]
+	);
+
+	assert_eq!(reader.u16(), 1);
+	let [object, name] = [(); 2].map(|_| reader.u16());
+	assert_eq!(
+		[table[object].as_str(), table[name].as_str()],
+		["Test", "Point"]
+	);
+	assert_eq!(names(&mut reader), ["y", "x"]);
+}
+
+#[test]
+fn test_string_table() {
+	let source = "ScriptName Test Extends Form
+		String Function Greet(String name)
+			String message = \"Hello \" + Name
+			Debug.Trace(message)
+			debug.trace(\"hello\")
+			Return NAME
+		EndFunction";
+
+	let options = CompileOptions {
+		imports: vec![directory(
+			"string-table",
+			&[
+				("Form.psc", "ScriptName Form"),
+				(
+					"Debug.psc",
+					"ScriptName Debug
+					Function Trace(String text) Global Native",
+				),
+			],
+		)],
+		..CompileOptions::new(Game::Skyrim)
+	};
+	let pex = || {
+		compile(&parse_module(source).unwrap(), &options)
+			.unwrap()
+			.bytes
+	};
+
+	// Identifiers keep the spelling they are first written with, while literals keep their own.
+	let table = string_table(&pex());
+	assert_eq!(
+		table,
+		[
+			"Test",
+			"Form",
+			"",
+			"Greet",
+			"String",
+			"name",
+			"message",
+			"::temp0",
+			"::NoneVar",
+			"None",
+			"Hello ",
+			"Debug",
+			"Trace",
+			"hello",
+			"hidden",
+			"conditional",
+		]
+	);
+	assert_eq!(table, string_table(&pex()));
+}
+
+#[test]
+fn test_limits() {
+	let text = "a".repeat(70000);
+	let source = format!("ScriptName Test\nString text = \"{text}\"");
+	let diagnostics = compile(
+		&parse_module(&source).unwrap(),
+		&CompileOptions::new(Game::Skyrim),
+	)
+	.unwrap_err();
+	assert_eq!(
+		diagnostics
+			.into_iter()
+			.map(|d| d.message)
+			.collect::<Vec<_>>(),
+		["A string of 70000 bytes is longer than the 65535 a PEX can hold"]
+	);
+
+	let variable = |i| Variable {
+		name: format!("v{i}"),
+		ty: "Int".to_owned(),
+		user_flags: 0,
+		value: Value::None,
+		is_const: false,
+	};
+	let pex = Pex {
+		header: Header {
+			game: Game::Skyrim,
+			compilation_time: 0,
+			source: "A.psc".to_owned(),
+			user: String::new(),
+			machine: String::new(),
+		},
+		debug_info: None,
+		user_flags: vec![],
+		objects: vec![Object {
+			name: "A".to_owned(),
+			parent: String::new(),
+			doc: String::new(),
+			is_const: false,
+			user_flags: 0,
+			auto_state: String::new(),
+			structs: vec![],
+			variables: (0..70000).map(variable).collect(),
+			properties: vec![],
+			states: vec![],
+		}],
+	};
+	assert_eq!(pex.encode(), Err(EncodeError::TooMany(70000)));
+}
@@ -0,0 +1,185 @@
+/*!
+	Testing flags files and the user flags scripts put on their declarations.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{
+		check_flags, compile,
+		flags::{Error, Flag, Target},
+		CompileOptions, Flags, Game,
+	},
+	parse_module, Diagnostics,
+};
+
+const FLAGS: &str = "; Custom flags
+Flag Hidden 0
+{
+	Script
+	Property
+}
+Flag Conditional 1 { Script Variable }
+Flag MyFlag 7 {Function StructVar Group}";
+
+fn errors(source: &str) -> Vec<String> {
+	let mut diagnostics = Diagnostics::new();
+	let flags = Flags::parse(FLAGS).unwrap();
+	check_flags(&parse_module(source).unwrap(), &flags, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_parse() {
+	let flags = Flags::parse(FLAGS).unwrap();
+	assert_eq!(
+		flags.flags[2],
+		Flag {
+			name: "MyFlag".to_owned(),
+			bit: 7,
+			targets: vec![Target::Function, Target::StructVar, Target::Group],
+		}
+	);
+	assert_eq!(flags.get("myflag"), flags.flags.get(2));
+	assert_eq!(
+		flags.mask(&["hidden".to_owned(), "MYFLAG".to_owned()]),
+		0x81
+	);
+
+	assert_eq!(
+		Flags::parse("Flag Big 32 {}"),
+		Err(Error::InvalidBit {
+			name: "Big".to_owned(),
+			bit: 32,
+			line: 1
+		})
+	);
+	assert_eq!(
+		Flags::parse("Flag A 0 {}\nFlag a 1 {}"),
+		Err(Error::Duplicate {
+			name: "a".to_owned(),
+			line: 2
+		})
+	);
+	assert_eq!(
+		Flags::parse("Flag A 0\n{\n\tObject\n}"),
+		Err(Error::Expected {
+			expected: "Script, Property, Variable, Group, StructVar or Function",
+			got: "Object".to_owned(),
+			line: 3
+		})
+	);
+	assert_eq!(
+		Flags::parse("Flag A 0 { Script"),
+		Err(Error::UnexpectedEnd("}"))
+	);
+
+	// Collapsed stands for both of the flags groups can be collapsed with.
+	let builtin = Flags::builtin(Game::Fallout4);
+	assert_eq!(builtin.mask(&["Collapsed".to_owned()]), 0b11000);
+	assert!(Flags::builtin(Game::Skyrim).get("Mandatory").is_none());
+}
+
+#[test]
+fn test_check() {
+	assert_eq!(
+		errors(
+			"ScriptName Test Hidden Conditional
+			Int x = 5 Conditional
+			Int y Hidden
+			Int Property Count Auto Conditional Hidden
+			Int Property Max = 10 AutoReadOnly Conditional
+			Group Stats MyFlag
+				Int Property Health Auto
+			EndGroup
+			Struct Point
+				Float x MyFlag
+				Float y Unknown
+			EndStruct
+			Struct Line MyFlag Conditional
+				Float length
+			EndStruct
+			Function Foo() MyFlag
+				Int z Conditional
+			EndFunction"
+		),
+		[
+			"User flag Hidden cannot be used on variables",
+			"User flag Conditional cannot be used on properties",
+			"User flag Unknown is not declared in the flags file",
+			"User flag Conditional cannot be used on struct members",
+			"Local variable z cannot have user flags",
+		]
+	);
+}
+
+#[test]
+fn test_bits() {
+	let ast = parse_module(
+		"ScriptName Test Conditional MyFlag
+		Int x = 5 Conditional
+		Function Foo() MyFlag
+		EndFunction",
+	)
+	.unwrap();
+
+	let path = std::env::temp_dir().join(format!("cyperus-{}-bits.flg", std::process::id()));
+	std::fs::write(&path, format!("{FLAGS}\nFlag MyFlag2 3 {{ Script }}")).unwrap();
+	let options = CompileOptions {
+		flags_file: Some(path.clone()),
+		..CompileOptions::new(Game::Skyrim)
+	};
+
+	let diagnostics = compile(&ast, &options).unwrap_err();
+	assert_eq!(
+		diagnostics
+			.into_iter()
+			.map(|d| d.message)
+			.collect::<Vec<_>>(),
+		["User flag MyFlag cannot be used on scripts"]
+	);
+
+	let ast = parse_module(
+		"ScriptName Test Conditional Hidden
+		Int x = 5 Conditional
+		Function Foo() MyFlag
+		EndFunction",
+	)
+	.unwrap();
+	let bytes = compile(&ast, &options).unwrap().bytes;
+
+	// Each is a big-endian u32, followed by the initial value for variables and the function flags for functions.
+	let find = |pattern: &[u8]| bytes.windows(pattern.len()).any(|w| w == pattern);
+	assert!(find(&[0, 0, 0, 0b11]), "script flags");
+	assert!(find(&[0, 0, 0, 0b10, 3, 0, 0, 0, 5]), "variable flags");
+	assert!(find(&[0, 0, 0, 0x80, 0]), "function flags");
+}
+
+#[test]
+fn test_struct_bits() {
+	let ast = parse_module(
+		"ScriptName Test
+		Struct Point MyFlag
+			Float x Secret
+			Float y
+		EndStruct",
+	)
+	.unwrap();
+
+	let path = std::env::temp_dir().join(format!("cyperus-{}-struct.flg", std::process::id()));
+	std::fs::write(&path, format!("{FLAGS}\nFlag Secret 4 {{ StructVar }}")).unwrap();
+	let options = CompileOptions {
+		flags_file: Some(path),
+		assembly: true,
+		..CompileOptions::new(Game::Fallout4)
+	};
+	let assembly = compile(&ast, &options).unwrap().assembly.unwrap();
+
+	// The flags of a struct go on each of its members, along with their own.
+	let flags = |member: &str| {
+		let start = assembly.find(&format!(".variable {member} Float")).unwrap();
+		assembly[start..].lines().nth(1).unwrap().trim().to_owned()
+	};
+	assert_eq!(flags("x"), ".userFlags 144");
+	assert_eq!(flags("y"), ".userFlags 128");
+}
@@ -0,0 +1,141 @@
+/*!
+	Testing return paths and reachability.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_flow, parse_module, Diagnostics};
+
+fn errors(source: &str) -> Vec<String> {
+	let mut diagnostics = Diagnostics::new();
+	check_flow(&parse_module(source).unwrap(), &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_valid() {
+	assert!(errors(
+		"ScriptName Valid
+		Int Function Sign(Int x)
+			If x > 0
+				return 1
+			ElseIf x < 0
+				return -1
+			Else
+				return 0
+			EndIf
+		EndFunction
+
+		Int Function Forever()
+			While True
+				If Utility.RandomInt() == 5
+					return 5
+				EndIf
+			EndWhile
+		EndFunction
+
+		Function Nothing()
+			return
+		EndFunction
+
+		Event OnInit()
+			If True
+				return
+			EndIf
+			Debug.Trace(\"Done\")
+		EndEvent"
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_missing_return() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Int Function NoElse(Int x)
+				If x > 0
+					return 1
+				ElseIf x < 0
+					return -1
+				EndIf
+			EndFunction
+
+			Int Function Loop(Int x)
+				While x > 0
+					return x
+				EndWhile
+			EndFunction
+
+			Int Property Value
+				Int Function Get()
+				EndFunction
+			EndProperty"
+		),
+		[
+			"Function NoElse does not return a value on every path",
+			"Function Loop does not return a value on every path",
+			"Function Get does not return a value on every path",
+		]
+	);
+}
+
+#[test]
+fn test_return_values() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Function Foo()
+				return 5
+			EndFunction
+
+			Event OnInit()
+				return True
+			EndEvent
+
+			Int Function Bar()
+				return
+			EndFunction"
+		),
+		[
+			"Foo has no return type, so it cannot return a value",
+			"OnInit has no return type, so it cannot return a value",
+			"Function Bar must return a value of type Int",
+		]
+	);
+}
+
+#[test]
+fn test_unreachable() {
+	let ast = parse_module(
+		"ScriptName Unreachable
+		Int Function Foo(Int x)
+			If x
+				return 1
+				x = 2
+				x = 3
+			Else
+				return 2
+			EndIf
+			return 3
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	check_flow(&ast, &mut diagnostics);
+
+	// Only the first statement of each unreachable run is reported, as a warning.
+	let found = diagnostics
+		.iter()
+		.map(|d| (d.is_error(), d.message.as_str(), d.span.unwrap().line))
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		found,
+		[
+			(false, "unreachable statement", 5),
+			(false, "unreachable statement", 10)
+		]
+	);
+}
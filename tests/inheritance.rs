@@ -0,0 +1,121 @@
+/*!
+	Testing validation across Extends chains.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_inheritance, parse_module, Diagnostics, Project};
+
+fn errors(scripts: &[&str]) -> Vec<String> {
+	let mut project = Project::new(Vec::<String>::new());
+	for source in scripts {
+		let ast = parse_module(source).unwrap();
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+
+	let name = parse_module(scripts[0]).unwrap().script_info.script_name;
+	let mut diagnostics = Diagnostics::new();
+	check_inheritance(&project, &name, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+const BASE: &str = "ScriptName Base Native
+	Int Property Count Auto
+	Function Wait(Float seconds) Native
+	Int Function Add(Int a, Int b = 1)
+		return a + b
+	EndFunction
+	Event OnInit()
+	EndEvent";
+
+#[test]
+fn test_valid_overrides() {
+	assert!(errors(&[
+		"ScriptName Child Extends Base
+		Int Function add(int A, int B = 1)
+			return 0
+		EndFunction
+		State Busy
+			Event OnInit()
+			EndEvent
+		EndState",
+		BASE
+	])
+	.is_empty());
+}
+
+#[test]
+fn test_signatures() {
+	assert_eq!(
+		errors(&[
+			"ScriptName Child Extends Middle
+			Float Function Add(Int a, Int b = 1)
+			EndFunction
+			Event OnInit(Int extra)
+			EndEvent",
+			"ScriptName Middle Extends Base
+			Int Function Add(Int a, Int b = 1)
+				return 0
+			EndFunction",
+			BASE
+		]),
+		[
+			"Child.Add does not match the signature of Middle.Add: returns Float instead of Int",
+			"Child.OnInit does not match the signature of Base.OnInit: takes 1 parameters instead of 0",
+		]
+	);
+
+	assert_eq!(
+		errors(&[
+			"ScriptName Child Extends Base
+			Int Function Add(Int a, Float b = 1.0)
+			EndFunction",
+			BASE
+		]),
+		["Child.Add does not match the signature of Base.Add: parameter b is Float instead of Int"]
+	);
+
+	assert_eq!(
+		errors(&[
+			"ScriptName Child Extends Base
+			Int Function Add(Int a, Int b = 2)
+			EndFunction",
+			BASE
+		]),
+		["Child.Add does not match the signature of Base.Add: parameter b has a different default value"]
+	);
+}
+
+#[test]
+fn test_redeclarations() {
+	assert_eq!(
+		errors(&[
+			"ScriptName Child Extends Base
+			Float Property Count Auto
+			Function Wait(Float seconds)
+			EndFunction",
+			BASE
+		]),
+		[
+			"Child.Wait overrides native function Base.Wait, but Child is not Native",
+			"Child.Count redeclares Base.Count",
+		]
+	);
+}
+
+#[test]
+fn test_script_flags() {
+	assert_eq!(
+		errors(&[
+			"ScriptName Child Extends Settings Native",
+			"ScriptName Settings Const"
+		]),
+		[
+			"Child extends Const script Settings, but is not Const itself",
+			"Native script Child extends Settings, which is not Native",
+		]
+	);
+
+	assert!(errors(&["ScriptName Child Extends Base Native", BASE]).is_empty());
+}
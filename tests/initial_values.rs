@@ -0,0 +1,58 @@
+/*!
+	Testing initial values of variables, properties and struct members.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_initial_values, parse_module, Diagnostics};
+
+fn errors(source: &str) -> Vec<String> {
+	let ast = parse_module(source).unwrap();
+	let mut diagnostics = Diagnostics::new();
+	check_initial_values(&ast, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_literals() {
+	assert!(errors(
+		"ScriptName Valid
+		Int Count = -5
+		Float Ratio = 0.5
+		String Property Name = \"Test\" Auto
+		Bool Property Enabled = True AutoReadOnly
+		Form Property Target = None Auto
+		Struct Point
+			Float x = -0.5
+			Float y
+		EndStruct"
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_expressions() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Int Count = -5
+			Float Ratio = Count
+			Int Property Total = 1 + 2 Auto
+			Bool Property Enabled = True AutoReadOnly
+			Group Stats
+				String Property Name = None Auto
+				Float Property Speed = 1.5 * 2.0 AutoReadOnly
+			EndGroup
+			Struct Point
+				Float x = 0.5
+				Float y = Count
+			EndStruct"
+		),
+		[
+			"Initial value of Ratio must be a literal, as it is set before any code runs",
+			"Initial value of Total must be a literal, as it is set before any code runs",
+			"Initial value of Speed must be a literal, as it is set before any code runs",
+			"Initial value of y must be a literal, as it is set before any code runs",
+		]
+	);
+}
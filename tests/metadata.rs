@@ -0,0 +1,31 @@
+/*!
+	Testing the build metadata taken from the environment.
+	Kept to a single test, as it changes variables the whole process shares.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::compiler::Metadata;
+
+#[test]
+fn test_from_env() {
+	std::env::remove_var("SOURCE_DATE_EPOCH");
+	std::env::set_var("USER", "builder");
+	std::env::set_var("HOSTNAME", "ci");
+	let metadata = Metadata::from_env();
+	assert_eq!(metadata.user.as_deref(), Some("builder"));
+	assert_eq!(metadata.machine.as_deref(), Some("ci"));
+	assert!(metadata.compilation_time.unwrap() > 1_700_000_000);
+
+	// A fixed time leaves out who built it and where.
+	std::env::set_var("SOURCE_DATE_EPOCH", "1700000000");
+	assert_eq!(
+		Metadata::from_env(),
+		Metadata {
+			compilation_time: Some(1_700_000_000),
+			source: None,
+			user: None,
+			machine: None,
+		}
+	);
+}
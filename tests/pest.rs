@@ -2,6 +2,8 @@
 	Testing the papyrus pest grammar.
 */
 
+#![allow(clippy::tabs_in_doc_comments, clippy::single_element_loop)]
+
 use cyperus::parser::{PestParser, Rule};
 
 fn should_parse(rule: Rule, source: impl AsRef<str>) {
@@ -154,6 +156,7 @@ fn test_declaration() {
 		"float[] z",
 		"Race test",
 		"Actor thatguy",
+		"int x const",
 	] {
 		should_parse(Rule::declaration, case);
 	}
@@ -236,6 +239,12 @@ fn test_struct() {
 		"Struct Foo
 			Int Bar = 55
 		EndStruct",
+		"Struct Foo
+			Int Bar = 55 Const
+		EndStruct",
+		"Struct Foo Hidden
+			Int Bar
+		EndStruct",
 	] {
 		should_parse(Rule::r#struct, case);
 	}
@@ -251,3 +260,35 @@ fn test_whitespace() {
 		should_not_parse(Rule::r#function, case);
 	}
 }
+
+#[test]
+fn test_user_flags() {
+	for (rule, case) in [
+		(
+			Rule::header,
+			"ScriptName Test Extends Form Conditional MyFlag",
+		),
+		(Rule::group, "Group Stats Collapsed MyFlag\nEndGroup"),
+		(
+			Rule::auto_property,
+			"Int Property A Auto Conditional MyFlag",
+		),
+		(
+			Rule::const_property,
+			"Int Property A = 1 AutoReadOnly MyFlag",
+		),
+		(
+			Rule::full_property,
+			"Int Property A Hidden\n\tInt Function Get()\n\tEndFunction\nEndProperty",
+		),
+		(Rule::definition, "Int a = 1 Const MyFlag"),
+		(Rule::declaration, "Int a Conditional MyFlag\n"),
+		(
+			Rule::method_function,
+			"Function Foo() DebugOnly MyFlag\nEndFunction",
+		),
+		(Rule::native_function, "Function Foo() Global Native MyFlag"),
+	] {
+		should_parse(rule, case);
+	}
+}
@@ -0,0 +1,126 @@
+/*!
+	Testing where declarations are allowed to appear.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_placement, parse_module, Diagnostics};
+
+fn errors(source: &str) -> Vec<String> {
+	let mut diagnostics = Diagnostics::new();
+	check_placement(&parse_module(source).unwrap(), &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_valid() {
+	assert!(errors(
+		"ScriptName Valid
+		Import Debug
+		Int Count = 0
+		Struct Point
+			Float X
+		EndStruct
+		Group Settings
+			Int Property Limit = 5 Auto
+		EndGroup
+		Int Property Total
+			Int Function Get()
+				return Count
+			EndFunction
+		EndProperty
+		Function Tick() Native
+		Event OnInit()
+			If Count < 5
+				Int i = 0
+				While i < 5
+					i += 1
+				EndWhile
+			EndIf
+			Trace(\"Done\")
+		EndEvent
+		Auto State Waiting
+			Event OnInit()
+				return
+			EndEvent
+		EndState"
+	)
+	.is_empty());
+}
+
+#[test]
+fn test_script_scope() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Count = 5
+			Trace(\"Hello\")
+			If True
+			EndIf"
+		),
+		[
+			"Assignment is not allowed at script scope, only inside of a function or event",
+			"Expression is not allowed at script scope, only inside of a function or event",
+			"If is not allowed at script scope, only inside of a function or event",
+		]
+	);
+}
+
+#[test]
+fn test_nested() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Event OnInit()
+				If True
+					Function Nested()
+					EndFunction
+				EndIf
+				Struct Point
+					Float X
+				EndStruct
+				While True
+					Import Debug
+				EndWhile
+			EndEvent
+			Function Foo()
+				Int Property Bar Auto
+			EndFunction"
+		),
+		[
+			"Function is not allowed inside of a function or event, only at script scope or inside of a state",
+			"Struct is not allowed inside of a function or event, only at script scope",
+			"Import is not allowed inside of a function or event, only at script scope",
+			"Property is not allowed inside of a function or event, only at script scope or inside of a group",
+		]
+	);
+}
+
+#[test]
+fn test_groups_and_properties() {
+	assert_eq!(
+		errors(
+			"ScriptName Invalid
+			Group Settings
+				Int Count
+				Function Foo()
+				EndFunction
+			EndGroup
+			State Busy
+				Group Nested
+				EndGroup
+			EndState
+			Int Property Total
+				Int Function Compute()
+					return 0
+				EndFunction
+			EndProperty"
+		),
+		[
+			"Variable is not allowed inside of a group, only at script scope or inside of a function or event",
+			"Function is not allowed inside of a group, only at script scope or inside of a state",
+			"Group is not allowed inside of a state, only at script scope",
+			"Property Total can only contain Get and Set functions, not Compute",
+		]
+	);
+}
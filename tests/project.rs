@@ -0,0 +1,118 @@
+/*!
+	Testing loading scripts from import directories.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+mod common;
+
+use common::directory;
+use cyperus::{compiler::Hierarchy, project::Error, Project};
+use std::path::Path;
+
+fn file_name(path: &Path) -> String {
+	path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_load() {
+	let base = directory(
+		"load-base",
+		&[
+			(
+				"form.psc",
+				"ScriptName Form
+				Keyword Function GetKeyword() Native",
+			),
+			("Keyword.psc", "ScriptName Keyword"),
+			(
+				"ObjectReference.psc",
+				"ScriptName ObjectReference Extends Form",
+			),
+			("Actor.psc", "ScriptName Actor Extends ObjectReference"),
+			("Debug.psc", "ScriptName Debug"),
+			("Utility.psc", "ScriptName Utility"),
+			(
+				"Quests/Intro.psc",
+				"ScriptName Quests:Intro Extends Form
+				Import Utility
+				Actor Property Player Auto
+				Event OnInit()
+					Debug.Trace(\"Hi\")
+				EndEvent",
+			),
+		],
+	);
+
+	let mut project = Project::new([&base]);
+	let script = project.load("quests:intro").unwrap();
+	assert_eq!(file_name(&script.path), "Intro.psc");
+
+	// Types used by parents are loaded along with those of the script itself.
+	for name in [
+		"FORM",
+		"Utility",
+		"Actor",
+		"ObjectReference",
+		"Debug",
+		"Keyword",
+	] {
+		assert!(project.get(name).is_some(), "{name} should be loaded");
+	}
+
+	assert_eq!(
+		project
+			.chain("actor")
+			.map(|s| s.ast.script_info.script_name.as_str())
+			.collect::<Vec<_>>(),
+		["Actor", "ObjectReference", "Form"]
+	);
+
+	assert_eq!(project.extends("Actor", "Form"), Some(true));
+	assert_eq!(project.extends("Form", "Actor"), Some(false));
+	assert_eq!(project.extends("Quests:Intro", "Unknown"), Some(false));
+	assert_eq!(project.extends("Unknown", "Form"), None);
+}
+
+#[test]
+fn test_priority() {
+	let first = directory("priority-first", &[("Shared.psc", "ScriptName Shared")]);
+	let second = directory(
+		"priority-second",
+		&[("Shared.psc", "ScriptName Shared\nint Overridden")],
+	);
+
+	let mut project = Project::new([&first, &second]);
+	let script = project.load("Shared").unwrap();
+	assert!(script.path.starts_with(&second));
+	assert_eq!(script.ast.statements.len(), 1);
+}
+
+#[test]
+fn test_errors() {
+	let dir = directory(
+		"errors",
+		&[
+			("Renamed.psc", "ScriptName Original"),
+			("Orphan.psc", "ScriptName Orphan Extends Missing"),
+			("Loop.psc", "ScriptName Loop Extends Loop"),
+		],
+	);
+
+	let mut project = Project::new([&dir]);
+	assert!(matches!(
+		project.load("Renamed"),
+		Err(Error::NameMismatch { name, .. }) if name == "Original"
+	));
+	assert!(matches!(project.load("Orphan"), Err(Error::NotFound(name)) if name == "Missing"));
+	assert!(matches!(
+		project.load("Loop"),
+		Err(Error::CyclicInheritance(_))
+	));
+
+	let ast = cyperus::parse_module("ScriptName Inserted").unwrap();
+	assert!(matches!(
+		project.insert("scripts/Other.psc", ast),
+		Err(Error::NameMismatch { .. })
+	));
+}
@@ -0,0 +1,162 @@
+/*!
+	Testing name resolution.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{resolve, Binding},
+	parse_module,
+	parser::ast::{Expression, Statement},
+	Diagnostics,
+};
+
+fn body(stmt: &Statement) -> &[Statement] {
+	match stmt {
+		Statement::Function { body, .. } | Statement::Event { body, .. } => body,
+		_ => panic!("expected a function, got {stmt:?}"),
+	}
+}
+
+#[test]
+fn test_locals_and_script_scope() {
+	let ast = parse_module(
+		"ScriptName Counter
+		int Count
+		Function Bump(int amount)
+			int total = COUNT + Amount
+			bump(total)
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	let body = body(&ast.statements[1]);
+
+	let Statement::Definition {
+		value: Expression::Addition(lhs, rhs),
+		..
+	} = &body[0]
+	else {
+		panic!("expected a definition, got {:?}", body[0]);
+	};
+	assert!(
+		matches!(resolution.get(lhs), Some(Binding::Variable(Statement::Declaration { name, .. })) if name == "Count")
+	);
+	assert!(matches!(resolution.get(rhs), Some(Binding::Parameter(p)) if p.1 == "amount"));
+
+	let Statement::Expression {
+		expr: Expression::Call(target, args),
+		..
+	} = &body[1]
+	else {
+		panic!("expected a call, got {:?}", body[1]);
+	};
+	assert!(matches!(resolution.get(target), Some(Binding::Function(_))));
+	assert!(matches!(
+		resolution.get(args[0].value()),
+		Some(Binding::Local(_))
+	));
+}
+
+#[test]
+fn test_members() {
+	let ast = parse_module(
+		"ScriptName Points
+		Struct Point
+			int X
+		EndStruct
+		Int Property Total Auto
+		Function Add(Point p)
+			Total = p.x
+			Debug.Trace(Points.Total)
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	let body = body(&ast.statements[2]);
+	assert!(matches!(
+		resolution.target(&body[0]),
+		Some(Binding::Property(_))
+	));
+
+	let Statement::Assignment { value, .. } = &body[0] else {
+		unreachable!()
+	};
+	assert!(matches!(resolution.get(value), Some(Binding::Field(f)) if f.1 == "X"));
+
+	let Statement::Expression {
+		expr: Expression::Call(target, args),
+		..
+	} = &body[1]
+	else {
+		panic!("expected a call, got {:?}", body[1]);
+	};
+
+	let Expression::DotIndex(debug, _) = target.as_ref() else {
+		unreachable!()
+	};
+	assert!(matches!(
+		resolution.get(debug),
+		Some(Binding::Script("Debug"))
+	));
+	assert!(resolution.get(target).is_none());
+	assert!(matches!(
+		resolution.get(args[0].value()),
+		Some(Binding::Property(_))
+	));
+}
+
+#[test]
+fn test_errors() {
+	let ast = parse_module(
+		"ScriptName Broken
+		Function Foo(int a)
+			int a
+			b = 5
+		EndFunction
+		Function foo()
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	resolve(&ast, &mut diagnostics);
+	let errors = diagnostics
+		.into_iter()
+		.map(|d| d.message)
+		.collect::<Vec<_>>();
+
+	assert_eq!(
+		errors,
+		[
+			"foo declared multiple times",
+			"a declared multiple times",
+			"variable b not declared"
+		]
+	);
+}
+
+#[test]
+fn test_inherited() {
+	// Names that might come from the parent script aren't errors.
+	let ast = parse_module(
+		"ScriptName Child Extends Parent
+		Function Foo()
+			Bar(Baz)
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	assert!(diagnostics.is_empty());
+	assert_eq!(resolution.unresolved.len(), 2);
+}
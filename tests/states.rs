@@ -0,0 +1,115 @@
+/*!
+	Testing state validation.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{compiler::check_states, parse_module, Diagnostics, Project};
+
+fn errors(scripts: &[&str]) -> Vec<String> {
+	let mut project = Project::new(Vec::<String>::new());
+	for source in scripts {
+		let ast = parse_module(source).unwrap();
+		let path = format!("{}.psc", ast.script_info.script_name);
+		project.insert(path, ast).unwrap();
+	}
+
+	let name = parse_module(scripts[0]).unwrap().script_info.script_name;
+	let mut diagnostics = Diagnostics::new();
+	check_states(&project, &name, &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_valid() {
+	assert!(errors(&[
+		"ScriptName Door Extends Base
+		Function Open()
+			GotoState(\"Opened\")
+		EndFunction
+		Auto State Closed
+			Function Open()
+				GotoState(\"waiting\")
+			EndFunction
+		EndState
+		State Opened
+			Event OnActivate(ObjectReference akActivator)
+				GotoState(\"\")
+				akActivator.GotoState(\"Elsewhere\")
+			EndEvent
+		EndState",
+		"ScriptName Base
+		Event OnActivate(ObjectReference akActivator)
+		EndEvent
+		State Waiting
+		EndState"
+	])
+	.is_empty());
+}
+
+#[test]
+fn test_functions() {
+	assert_eq!(
+		errors(&[
+			"ScriptName Door
+			Function Open(Bool quickly)
+			EndFunction
+			State Opened
+				Function Open()
+				EndFunction
+				Function Close()
+				EndFunction
+			EndState"
+		]),
+		[
+			"Opened.Open does not match the signature of Open in the empty state: takes 0 parameters instead of 1",
+			"Opened.Close is not defined in the empty state",
+		]
+	);
+
+	// The function may be in a parent that isn't loaded.
+	assert!(errors(&["ScriptName Door Extends Missing
+		State Opened
+			Function Close()
+			EndFunction
+		EndState"])
+	.is_empty());
+}
+
+#[test]
+fn test_declarations() {
+	assert_eq!(
+		errors(&["ScriptName Door
+			Auto State Closed
+				Int Count
+				Int Property Locks Auto
+			EndState
+			Auto State Opened
+			EndState"]),
+		[
+			"Count cannot be declared inside of state Closed",
+			"Locks cannot be declared inside of state Closed",
+			"Only one state can be Auto, but Closed and Opened both are",
+		]
+	);
+}
+
+#[test]
+fn test_goto() {
+	assert_eq!(
+		errors(&["ScriptName Door
+			Event OnInit()
+				GotoState(\"Nowhere\")
+				If True
+					GotoState(\"Closed\")
+					Self.GotoState(\"Gone\")
+				EndIf
+			EndEvent
+			State Closed
+			EndState"]),
+		[
+			"GotoState target Nowhere is not a state of this script or its parents",
+			"GotoState target Gone is not a state of this script or its parents",
+		]
+	);
+}
@@ -2,6 +2,8 @@
 	Testing the AST builder with actual widely used Papyrus code.
 */
 
+#![allow(clippy::tabs_in_doc_comments)]
+
 use cyperus::parse_module;
 
 macro_rules! github {
@@ -0,0 +1,148 @@
+/*!
+	Testing type inference and checking.
+*/
+
+#![allow(clippy::tabs_in_doc_comments)]
+
+use cyperus::{
+	compiler::{resolve, typecheck, Ty},
+	parse_module,
+	parser::ast::Statement,
+	Diagnostics,
+};
+
+fn errors(source: &str) -> Vec<String> {
+	let ast = parse_module(source).unwrap();
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	typecheck(&ast, &resolution, &(), &mut diagnostics);
+	diagnostics.into_iter().map(|d| d.message).collect()
+}
+
+#[test]
+fn test_inference() {
+	let ast = parse_module(
+		"ScriptName Test
+		float x = 5 + 2.0
+		string s = \"Count: \" + 5
+		bool b = (1 as Float) <= 2
+		int[] values
+		Int Function First()
+			return values[0] * values.Length
+		EndFunction",
+	)
+	.unwrap();
+
+	let mut diagnostics = Diagnostics::new();
+	let resolution = resolve(&ast, &mut diagnostics);
+	let typing = typecheck(&ast, &resolution, &(), &mut diagnostics);
+	assert!(diagnostics.is_empty(), "{diagnostics}");
+
+	let ty = |i: usize| match &ast.statements[i] {
+		Statement::Definition { value, .. } => typing.get(value).cloned(),
+		stmt => panic!("expected a definition, got {stmt:?}"),
+	};
+
+	assert_eq!(ty(0), Some(Ty::Float));
+	assert_eq!(ty(1), Some(Ty::String));
+	assert_eq!(ty(2), Some(Ty::Bool));
+}
+
+#[test]
+fn test_mismatches() {
+	assert_eq!(
+		errors(
+			"ScriptName Test
+			int x = 2.5
+			int[] values
+			Int Function Get()
+				values[1] = \"one\"
+				return None
+			EndFunction"
+		),
+		[
+			"expected Int, got Float",
+			"expected Int, got String",
+			"expected Int, got None"
+		]
+	);
+}
+
+#[test]
+fn test_operators() {
+	assert_eq!(
+		errors(
+			"ScriptName Test
+			bool a = \"a\" < 5
+			int b = -\"text\"
+			int c = 5 - True
+			int d = 7 % 2
+			float e = 7.5 % 2"
+		),
+		[
+			"cannot apply < to String and Int",
+			"cannot apply - to String",
+			"cannot apply - to Int and Bool",
+			"cannot apply % to Float and Int"
+		]
+	);
+}
+
+#[test]
+fn test_integer_range() {
+	assert_eq!(
+		errors(
+			"ScriptName Test
+			int a = 2147483647
+			int b = -2147483648
+			int c = 0xFFFFFFFF
+			int d = 2147483648
+			int e = 4294967296 + 1
+			int f = -0x100000000"
+		),
+		[
+			"2147483648 is out of range for Int, which holds -2147483648 to 2147483647",
+			"4294967296 is out of range for Int, which holds -2147483648 to 2147483647",
+			"-4294967296 is out of range for Int, which holds -2147483648 to 2147483647"
+		]
+	);
+}
+
+#[test]
+fn test_arguments() {
+	assert_eq!(
+		errors(
+			"ScriptName Test
+			Function Take(float amount, Test other = None)
+			EndFunction
+			Event OnInit()
+				Take(1)
+				Take(1.0, other = 5)
+				Take(amount = None)
+				Take(1.0, missing = 2)
+			EndEvent"
+		),
+		["expected Test, got Int", "expected Float, got None"]
+	);
+}
+
+#[test]
+fn test_properties() {
+	assert_eq!(
+		errors(
+			"ScriptName Test
+			Struct Point
+				float X = \"zero\"
+			EndStruct
+			Point Property Origin Auto
+			String Property Name = 5 AutoReadOnly
+			Function Move()
+				Origin.X = 1
+				Origin.Y = 2
+			EndFunction"
+		),
+		["expected Float, got String", "Point has no member Y"]
+	);
+}